@@ -12,6 +12,7 @@ export default [
     input: {
       fs: './src/fs.ts',
       path: './src/path.ts',
+      process: './src/process.ts',
       dialog: './src/dialog.ts',
       event: './src/event.ts',
       http: './src/http.ts',
@@ -20,7 +21,9 @@ export default [
       tauri: './src/tauri.ts',
       window: './src/window.ts',
       cli: './src/cli.ts',
-      notification: './src/notification.ts'
+      notification: './src/notification.ts',
+      globalShortcut: './src/globalShortcut.ts',
+      store: './src/store.ts'
     },
     treeshake: true,
     perf: true,
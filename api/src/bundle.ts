@@ -4,11 +4,14 @@ import * as dialog from './dialog'
 import * as event from './event'
 import * as fs from './fs'
 import * as path from './path'
+import * as process from './process'
 import * as http from './http'
 import * as shell from './shell'
 import * as tauri from './tauri'
 import * as window from './window'
 import * as notification from './notification'
+import * as globalShortcut from './globalShortcut'
+import * as store from './store'
 
 export {
   cli,
@@ -16,9 +19,12 @@ export {
   event,
   fs,
   path,
+  process,
   http,
   shell,
   tauri,
   window,
-  notification
+  notification,
+  globalShortcut,
+  store
 }
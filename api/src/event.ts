@@ -30,17 +30,19 @@ function listen<T>(
 }
 
 /**
- * emits an event to the backend
+ * emits an event to the backend and to the listeners of the given window, or of all windows
  *
  * @param event the event name
  * @param [payload] the event payload
+ * @param [windowLabel] the label of the window to emit the event to
  */
-function emit(event: string, payload?: string): void {
+function emit(event: string, payload?: string, windowLabel?: string): void {
   invoke({
     module: 'Event',
     message: {
       cmd: 'emit',
       event,
+      windowLabel,
       payload
     }
   })
@@ -0,0 +1,55 @@
+import { promisified, transformCallback } from './tauri'
+
+export type ShortcutHandler = (shortcut: string) => void
+
+/**
+ * registers a global shortcut, calling the handler whenever the user presses it, even if the app isn't focused
+ *
+ * @param shortcut the shortcut modifiers and key separated by `+`, e.g. `CmdOrCtrl+Shift+K`
+ * @param handler the shortcut handler, called with the shortcut in its canonical form, e.g. `Ctrl+Shift+K`
+ */
+async function register(
+  shortcut: string,
+  handler: ShortcutHandler
+): Promise<void> {
+  return await promisified({
+    module: 'GlobalShortcut',
+    message: {
+      cmd: 'register',
+      shortcut,
+      handler: transformCallback(handler)
+    }
+  })
+}
+
+/**
+ * unregisters a global shortcut
+ *
+ * @param shortcut the shortcut modifiers and key separated by `+`, e.g. `CmdOrCtrl+Shift+K`
+ */
+async function unregister(shortcut: string): Promise<void> {
+  return await promisified({
+    module: 'GlobalShortcut',
+    message: {
+      cmd: 'unregister',
+      shortcut
+    }
+  })
+}
+
+/**
+ * checks whether the app registered the global shortcut
+ *
+ * @param shortcut the shortcut modifiers and key separated by `+`, e.g. `CmdOrCtrl+Shift+K`
+ */
+async function isRegistered(shortcut: string): Promise<boolean> {
+  return await promisified({
+    module: 'GlobalShortcut',
+    message: {
+      cmd: 'isRegistered',
+      shortcut
+    }
+  })
+}
+
+export { register, unregister, isRegistered }
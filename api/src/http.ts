@@ -39,6 +39,10 @@ export interface HttpOptions {
   allowCompression: boolean
   responseType?: ResponseType
   bodyType: BodyType
+  /**
+   * skips the TLS certificate verification, only allowed with `tauri > http > dangerousAllowInvalidCerts`
+   */
+  acceptInvalidCerts?: boolean
 }
 
 export type PartialOptions = Omit<HttpOptions, 'method' | 'url'>
@@ -0,0 +1,30 @@
+import { invoke } from './tauri'
+
+/**
+ * exits the app with the given code, after running the exit hooks of the app and its plugins
+ *
+ * @param [exitCode=0] the process exit code
+ */
+function exit(exitCode: number = 0): void {
+  invoke({
+    module: 'Process',
+    message: {
+      cmd: 'exit',
+      exitCode
+    }
+  })
+}
+
+/**
+ * relaunches the app with the same arguments, after running the exit hooks of the app and its plugins
+ */
+function restart(): void {
+  invoke({
+    module: 'Process',
+    message: {
+      cmd: 'restart'
+    }
+  })
+}
+
+export { exit, restart }
@@ -1,10 +1,10 @@
 import { promisified, invoke } from './tauri'
 
 /**
- * spawns a process
+ * spawns a process defined on the `tauri > shell > scope` configuration
  *
- * @param command the name of the cmd to execute e.g. 'mkdir' or 'node'
- * @param [args] command args
+ * @param command the name of the command on the shell scope
+ * @param [args] the values of the command arguments defined with a validator, in order
  * @return promise resolving to the stdout text
  */
 async function execute(
@@ -0,0 +1,129 @@
+import { promisified } from './tauri'
+import { listen } from './event'
+
+export interface StoreChange<T = unknown> {
+  /** the name of the store */
+  store: string
+  /** the key that changed, or `null` if the store was cleared */
+  key: string | null
+  /** the new value of the key, or `null` if it was deleted */
+  value: T | null
+}
+
+export type StoreChangeHandler<T = unknown> = (change: StoreChange<T>) => void
+
+/**
+ * gets the value of a key
+ *
+ * @param store the store name
+ * @param key the key
+ * @return the value of the key, or `null` if the store doesn't have it
+ */
+async function get<T>(store: string, key: string): Promise<T | null> {
+  return await promisified({
+    module: 'Store',
+    message: {
+      cmd: 'get',
+      store,
+      key
+    }
+  })
+}
+
+/**
+ * sets the value of a key
+ *
+ * @param store the store name
+ * @param key the key
+ * @param value the value, serializable to JSON
+ */
+async function set<T>(store: string, key: string, value: T): Promise<void> {
+  return await promisified({
+    module: 'Store',
+    message: {
+      cmd: 'set',
+      store,
+      key,
+      value
+    }
+  })
+}
+
+/**
+ * deletes a key
+ *
+ * @param store the store name
+ * @param key the key
+ * @return whether the store had the key
+ */
+async function remove(store: string, key: string): Promise<boolean> {
+  return await promisified({
+    module: 'Store',
+    message: {
+      cmd: 'delete',
+      store,
+      key
+    }
+  })
+}
+
+/**
+ * lists the keys of the store
+ *
+ * @param store the store name
+ */
+async function keys(store: string): Promise<string[]> {
+  return await promisified({
+    module: 'Store',
+    message: {
+      cmd: 'keys',
+      store
+    }
+  })
+}
+
+/**
+ * deletes all the keys of the store
+ *
+ * @param store the store name
+ */
+async function clear(store: string): Promise<void> {
+  return await promisified({
+    module: 'Store',
+    message: {
+      cmd: 'clear',
+      store
+    }
+  })
+}
+
+/**
+ * saves the store to its file, which is only needed if the store doesn't autosave
+ *
+ * @param store the store name
+ */
+async function save(store: string): Promise<void> {
+  return await promisified({
+    module: 'Store',
+    message: {
+      cmd: 'save',
+      store
+    }
+  })
+}
+
+/**
+ * listens to the changes of the store, made from Rust or any window
+ *
+ * @param store the store name
+ * @param handler the change handler
+ */
+function onChange<T>(store: string, handler: StoreChangeHandler<T>): void {
+  listen<StoreChange<T>>('store-change', (event) => {
+    if (event.payload.store === store) {
+      handler(event.payload)
+    }
+  })
+}
+
+export { get, set, remove, keys, clear, save, onChange }
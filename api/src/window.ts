@@ -1,4 +1,98 @@
-import { invoke } from './tauri'
+import { invoke, promisified } from './tauri'
+import { listen, EventCallback } from './event'
+
+export interface WindowDef {
+  label: string
+}
+
+declare global {
+  // eslint-disable-next-line @typescript-eslint/no-unused-vars
+  interface Window {
+    __TAURI__: {
+      windows: WindowDef[]
+      currentWindow: WindowDef
+    }
+  }
+}
+
+export interface WindowOptions {
+  /** the window label, must be unique */
+  label: string
+  /** 'app' to load the app's entry, a path relative to it (e.g. 'settings.html' or 'app://settings.html'), or a custom URL */
+  url?: 'app' | string
+  x?: number
+  y?: number
+  width?: number
+  height?: number
+  minWidth?: number
+  minHeight?: number
+  maxWidth?: number
+  maxHeight?: number
+  resizable?: boolean
+  title?: string
+  fullscreen?: boolean
+  transparent?: boolean
+  maximized?: boolean
+  visible?: boolean
+  decorations?: boolean
+  alwaysOnTop?: boolean
+  /** the remote origins whose pages can invoke the app commands, e.g. 'https://tauri.studio'. Only the origins trusted by the current window are kept */
+  trustedOrigins?: string[]
+  /** the API modules, 'plugin:<name>' plugin namespaces and 'InvokeHandler' the trusted remote origins can call, e.g. 'Window' or 'plugin:sql'. Only the API the current window allows is kept */
+  remoteApi?: string[]
+}
+
+/**
+ * gets the list of open windows
+ *
+ * @return {WindowDef[]} the open windows, updated when a window is created or closed
+ */
+function getWindows(): WindowDef[] {
+  return window.__TAURI__.windows
+}
+
+/**
+ * gets the current window
+ *
+ * @return {WindowDef} the window this webview belongs to
+ */
+function getCurrentWindow(): WindowDef {
+  return window.__TAURI__.currentWindow
+}
+
+/**
+ * creates a new window
+ *
+ * @param options the window configuration
+ *
+ * @return {Promise<void>} promise resolving when the window is created
+ */
+async function createWindow(options: WindowOptions): Promise<void> {
+  return await promisified({
+    module: 'Window',
+    message: {
+      cmd: 'createWindow',
+      options
+    }
+  })
+}
+
+/**
+ * closes a window
+ *
+ * @param [label] the window label, defaults to the current window
+ *
+ * @return {Promise<void>} promise resolving when the close message was sent
+ */
+async function closeWindow(label?: string): Promise<void> {
+  return await promisified({
+    module: 'Window',
+    message: {
+      cmd: 'closeWindow',
+      label
+    }
+  })
+}
 
 /**
  * sets the window title
@@ -15,4 +109,281 @@ function setTitle(title: string): void {
   })
 }
 
-export { setTitle }
+/**
+ * sets whether the window is resizable or not
+ *
+ * @param resizable whether the user can resize the window
+ */
+function setResizable(resizable: boolean): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setResizable',
+      resizable
+    }
+  })
+}
+
+/**
+ * resizes the window
+ *
+ * @param width the new window width
+ * @param height the new window height
+ */
+function setSize(width: number, height: number): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setSize',
+      width,
+      height
+    }
+  })
+}
+
+/**
+ * sets the window min size, removing the constraint on undefined dimensions
+ *
+ * @param [minWidth] the min window width
+ * @param [minHeight] the min window height
+ */
+function setMinSize(minWidth?: number, minHeight?: number): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setMinSize',
+      minWidth,
+      minHeight
+    }
+  })
+}
+
+/**
+ * sets the window max size, removing the constraint on undefined dimensions
+ *
+ * @param [maxWidth] the max window width
+ * @param [maxHeight] the max window height
+ */
+function setMaxSize(maxWidth?: number, maxHeight?: number): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setMaxSize',
+      maxWidth,
+      maxHeight
+    }
+  })
+}
+
+/**
+ * moves the window
+ *
+ * @param x the horizontal position of the window's top left corner
+ * @param y the vertical position of the window's top left corner
+ */
+function setPosition(x: number, y: number): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setPosition',
+      x,
+      y
+    }
+  })
+}
+
+/**
+ * enters or leaves fullscreen
+ *
+ * @param fullscreen whether the window should be fullscreen
+ */
+function setFullscreen(fullscreen: boolean): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setFullscreen',
+      fullscreen
+    }
+  })
+}
+
+/**
+ * sets whether the window has borders and bars
+ *
+ * @param decorations whether the window should have borders and bars
+ */
+function setDecorations(decorations: boolean): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setDecorations',
+      decorations
+    }
+  })
+}
+
+/**
+ * sets whether the window is always on top of other windows
+ *
+ * @param alwaysOnTop whether the window should stay above other windows
+ */
+function setAlwaysOnTop(alwaysOnTop: boolean): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setAlwaysOnTop',
+      alwaysOnTop
+    }
+  })
+}
+
+/**
+ * minimizes the window
+ */
+function minimize(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'minimize'
+    }
+  })
+}
+
+/**
+ * restores a minimized window
+ */
+function unminimize(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'unminimize'
+    }
+  })
+}
+
+/**
+ * maximizes the window
+ */
+function maximize(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'maximize'
+    }
+  })
+}
+
+/**
+ * restores a maximized window
+ */
+function unmaximize(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'unmaximize'
+    }
+  })
+}
+
+/**
+ * shows the window
+ */
+function show(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'show'
+    }
+  })
+}
+
+/**
+ * hides the window
+ */
+function hide(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'hide'
+    }
+  })
+}
+
+/**
+ * centers the window on its monitor
+ */
+function center(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'center'
+    }
+  })
+}
+
+/**
+ * starts moving the window with the cursor, call it on the mousedown event of a custom title bar
+ */
+function startDragging(): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'startDragging'
+    }
+  })
+}
+
+/**
+ * sets whether the window stays open when the user requests it to be closed,
+ * e.g. with the title bar close button
+ * the `tauri://close-requested` event is still emitted, so the app can close it with `closeWindow`
+ * requires the `setPreventClose` allowlist entry
+ *
+ * @param prevent whether the close requests should be prevented
+ */
+function setPreventClose(prevent: boolean): void {
+  invoke({
+    module: 'Window',
+    message: {
+      cmd: 'setPreventClose',
+      prevent
+    }
+  })
+}
+
+/**
+ * listens to the close requests of the window, preventing them
+ * call `closeWindow` on the handler to close the window, e.g. after asking the user for confirmation
+ *
+ * @param handler the close request handler
+ */
+function onCloseRequested(handler: EventCallback<null>): void {
+  setPreventClose(true)
+  listen('tauri://close-requested', handler)
+}
+
+export {
+  getWindows,
+  getCurrentWindow,
+  createWindow,
+  closeWindow,
+  setTitle,
+  setResizable,
+  setSize,
+  setMinSize,
+  setMaxSize,
+  setPosition,
+  setFullscreen,
+  setDecorations,
+  setAlwaysOnTop,
+  minimize,
+  unminimize,
+  maximize,
+  unmaximize,
+  show,
+  hide,
+  center,
+  startDragging,
+  setPreventClose,
+  onCloseRequested
+}
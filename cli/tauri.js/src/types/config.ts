@@ -155,6 +155,55 @@ export interface CliConfig {
   subcommands?: { [name: string]: CliConfig }
 }
 
+/**
+ * A nested menu
+ */
+export interface Submenu {
+  /**
+   * the submenu title
+   */
+  title: string
+  /**
+   * whether the submenu can be opened or not
+   */
+  enabled?: boolean
+  /**
+   * the submenu entries
+   */
+  items: MenuEntry[]
+}
+
+/**
+ * A custom menu item, reporting its clicks with the `menu-event` event
+ */
+export interface MenuItem {
+  /**
+   * the item identifier, sent as the `menu-event` payload when the item is clicked
+   */
+  id: string
+  /**
+   * the item title
+   */
+  title: string
+  /**
+   * the keyboard shortcut of the item, e.g. `CmdOrCtrl+O`
+   */
+  accelerator?: string
+  /**
+   * whether the item can be clicked or not
+   */
+  enabled?: boolean
+  /**
+   * whether the item is checked or not, omitted if the item isn't checkable
+   */
+  checked?: boolean
+}
+
+/**
+ * A menu entry: a submenu, a custom item or the name of a native item
+ */
+export type MenuEntry = Submenu | MenuItem | 'separator'
+
 export interface TauriBuildConfig {
   /**
    * the path to the app's dist dir
@@ -284,11 +333,56 @@ export interface TauriConfig {
         height?: number
         resizable?: boolean
         fullscreen?: boolean
+        /**
+         * the window menu bar, defaults to the menu set with `AppBuilder::menu`
+         */
+        menu?: MenuEntry[]
+        /**
+         * the remote origins whose pages can invoke the app commands, e.g. `https://tauri.studio`
+         */
+        trustedOrigins?: string[]
+        /**
+         * the API modules, `plugin:<name>` plugin namespaces and `InvokeHandler` the trusted remote origins can call, e.g. `Window` or `plugin:sql`
+         */
+        remoteApi?: string[]
       }
     ]
     security: {
       csp?: string
     }
+    fs?: {
+      /**
+       * the glob patterns of the paths the file system API can access
+       * they can start with a base directory variable, e.g. `$APP/**` or `$DOWNLOAD/*.csv`
+       */
+      scope?: string[]
+    }
+    http?: {
+      /**
+       * the URL patterns the HTTP API can request, e.g. `https://api.tauri.studio/*`
+       */
+      scope?: string[]
+      /**
+       * whether the HTTP requests can skip the TLS certificate verification with their `acceptInvalidCerts` option
+       */
+      dangerousAllowInvalidCerts?: boolean
+    }
+    shell?: {
+      /**
+       * the commands the shell `execute` API can run
+       * the API call provides the values of the `{ validator }` args, which must fully match the regex
+       */
+      scope?: Array<{
+        name: string
+        cmd: string
+        args?: Array<string | { validator: string }>
+      }>
+    }
+    /**
+     * whether only one instance of the app can run at a time
+     * launching the app again focuses the running instance and sends it the new args
+     */
+    singleInstance?: boolean
   }
   plugins?: {
     [name: string]: {
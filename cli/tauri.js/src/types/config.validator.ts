@@ -232,6 +232,78 @@ export const TauriConfigSchema = {
       },
       type: 'object'
     },
+    MenuEntry: {
+      anyOf: [
+        {
+          $ref: '#/definitions/Submenu'
+        },
+        {
+          $ref: '#/definitions/MenuItem'
+        },
+        {
+          enum: ['separator'],
+          type: 'string'
+        }
+      ],
+      description:
+        'A menu entry: a submenu, a custom item or the name of a native item'
+    },
+    MenuItem: {
+      additionalProperties: false,
+      defaultProperties: [],
+      description:
+        'A custom menu item, reporting its clicks with the `menu-event` event',
+      properties: {
+        accelerator: {
+          description: 'the keyboard shortcut of the item, e.g. `CmdOrCtrl+O`',
+          type: 'string'
+        },
+        checked: {
+          description:
+            "whether the item is checked or not, omitted if the item isn't checkable",
+          type: 'boolean'
+        },
+        enabled: {
+          description: 'whether the item can be clicked or not',
+          type: 'boolean'
+        },
+        id: {
+          description:
+            'the item identifier, sent as the `menu-event` payload when the item is clicked',
+          type: 'string'
+        },
+        title: {
+          description: 'the item title',
+          type: 'string'
+        }
+      },
+      required: ['id', 'title'],
+      type: 'object'
+    },
+    Submenu: {
+      additionalProperties: false,
+      defaultProperties: [],
+      description: 'A nested menu',
+      properties: {
+        enabled: {
+          description: 'whether the submenu can be opened or not',
+          type: 'boolean'
+        },
+        items: {
+          description: 'the submenu entries',
+          items: {
+            $ref: '#/definitions/MenuEntry'
+          },
+          type: 'array'
+        },
+        title: {
+          description: 'the submenu title',
+          type: 'string'
+        }
+      },
+      required: ['items', 'title'],
+      type: 'object'
+    },
     TauriBuildConfig: {
       additionalProperties: false,
       defaultProperties: [],
@@ -462,6 +534,41 @@ export const TauriConfigSchema = {
           },
           type: 'object'
         },
+        fs: {
+          additionalProperties: false,
+          defaultProperties: [],
+          properties: {
+            scope: {
+              description:
+                'The glob patterns of the paths the file system API can access, e.g. `$APP/**`',
+              items: {
+                type: 'string'
+              },
+              type: 'array'
+            }
+          },
+          type: 'object'
+        },
+        http: {
+          additionalProperties: false,
+          defaultProperties: [],
+          properties: {
+            dangerousAllowInvalidCerts: {
+              description:
+                'Whether the HTTP requests can skip the TLS certificate verification with their `acceptInvalidCerts` option',
+              type: 'boolean'
+            },
+            scope: {
+              description:
+                'The URL patterns the HTTP API can request, e.g. `https://api.tauri.studio/*`',
+              items: {
+                type: 'string'
+              },
+              type: 'array'
+            }
+          },
+          type: 'object'
+        },
         security: {
           additionalProperties: false,
           defaultProperties: [],
@@ -472,6 +579,56 @@ export const TauriConfigSchema = {
           },
           type: 'object'
         },
+        shell: {
+          additionalProperties: false,
+          defaultProperties: [],
+          properties: {
+            scope: {
+              description: 'The commands the shell `execute` API can run',
+              items: {
+                additionalProperties: false,
+                defaultProperties: [],
+                properties: {
+                  args: {
+                    items: {
+                      anyOf: [
+                        {
+                          type: 'string'
+                        },
+                        {
+                          additionalProperties: false,
+                          defaultProperties: [],
+                          properties: {
+                            validator: {
+                              type: 'string'
+                            }
+                          },
+                          required: ['validator'],
+                          type: 'object'
+                        }
+                      ]
+                    },
+                    type: 'array'
+                  },
+                  cmd: {
+                    type: 'string'
+                  },
+                  name: {
+                    type: 'string'
+                  }
+                },
+                required: ['cmd', 'name'],
+                type: 'object'
+              },
+              type: 'array'
+            }
+          },
+          type: 'object'
+        },
+        singleInstance: {
+          description: 'Whether only one instance of the app can run at a time',
+          type: 'boolean'
+        },
         windows: {
           additionalItems: {
             anyOf: [
@@ -485,12 +642,36 @@ export const TauriConfigSchema = {
                   height: {
                     type: 'number'
                   },
+                  menu: {
+                    description:
+                      'the window menu bar, defaults to the menu set with `AppBuilder::menu`',
+                    items: {
+                      $ref: '#/definitions/MenuEntry'
+                    },
+                    type: 'array'
+                  },
+                  remoteApi: {
+                    description:
+                      'the API modules, `plugin:<name>` plugin namespaces and `InvokeHandler` the trusted remote origins can call, e.g. `Window` or `plugin:sql`',
+                    items: {
+                      type: 'string'
+                    },
+                    type: 'array'
+                  },
                   resizable: {
                     type: 'boolean'
                   },
                   title: {
                     type: 'string'
                   },
+                  trustedOrigins: {
+                    description:
+                      'the remote origins whose pages can invoke the app commands, e.g. `https://tauri.studio`',
+                    items: {
+                      type: 'string'
+                    },
+                    type: 'array'
+                  },
                   width: {
                     type: 'number'
                   }
@@ -511,12 +692,36 @@ export const TauriConfigSchema = {
                 height: {
                   type: 'number'
                 },
+                menu: {
+                  description:
+                    'the window menu bar, defaults to the menu set with `AppBuilder::menu`',
+                  items: {
+                    $ref: '#/definitions/MenuEntry'
+                  },
+                  type: 'array'
+                },
+                remoteApi: {
+                  description:
+                    'the API modules, `plugin:<name>` plugin namespaces and `InvokeHandler` the trusted remote origins can call, e.g. `Window` or `plugin:sql`',
+                  items: {
+                    type: 'string'
+                  },
+                  type: 'array'
+                },
                 resizable: {
                   type: 'boolean'
                 },
                 title: {
                   type: 'string'
                 },
+                trustedOrigins: {
+                  description:
+                    'the remote origins whose pages can invoke the app commands, e.g. `https://tauri.studio`',
+                  items: {
+                    type: 'string'
+                  },
+                  type: 'array'
+                },
                 width: {
                   type: 'number'
                 }
@@ -3,7 +3,7 @@ use crate::config::{CliArg, CliConfig, Config};
 use clap::{App, Arg, ArgMatches};
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::{collections::HashMap, ffi::OsString};
 
 #[macro_use]
 mod macros;
@@ -54,19 +54,40 @@ impl Matches {
 
 /// Gets the arg matches of the CLI definition.
 pub fn get_matches(config: &Config) -> crate::Result<Matches> {
-  let cli = config
+  let cli = get_cli_config(config)?;
+  let about = get_about(cli);
+  let app = get_app(crate_name!(), Some(&about), cli);
+  let matches = app.get_matches();
+  Ok(get_matches_internal(cli, &matches))
+}
+
+/// Gets the arg matches of the CLI definition from the given args, where the first arg is the binary name.
+/// Unlike [`get_matches`], invalid args are returned as an error instead of exiting the process.
+pub fn get_matches_from<I, T>(config: &Config, args: I) -> crate::Result<Matches>
+where
+  I: IntoIterator<Item = T>,
+  T: Into<OsString> + Clone,
+{
+  let cli = get_cli_config(config)?;
+  let about = get_about(cli);
+  let app = get_app(crate_name!(), Some(&about), cli);
+  let matches = app.try_get_matches_from(args)?;
+  Ok(get_matches_internal(cli, &matches))
+}
+
+fn get_cli_config(config: &Config) -> crate::Result<&CliConfig> {
+  config
     .tauri
     .cli
     .as_ref()
-    .ok_or_else(|| crate::Error::CliNotConfigured)?;
+    .ok_or_else(|| crate::Error::CliNotConfigured)
+}
 
-  let about = cli
+fn get_about(cli: &CliConfig) -> String {
+  cli
     .description()
     .unwrap_or(&crate_description!().to_string())
-    .to_string();
-  let app = get_app(crate_name!(), Some(&about), cli);
-  let matches = app.get_matches();
-  Ok(get_matches_internal(cli, &matches))
+    .to_string()
 }
 
 fn get_matches_internal(config: &CliConfig, matches: &ArgMatches) -> Matches {
@@ -19,6 +19,10 @@ pub enum Error {
   /// CLI config not set.
   #[error("CLI configuration not set on tauri.conf.json")]
   CliNotConfigured,
+  /// The CLI arguments don't match the CLI configuration.
+  #[cfg(feature = "cli")]
+  #[error("invalid CLI arguments: {0}")]
+  CliArgs(#[from] clap::Error),
   /// The HTTP response error.
   #[error("HTTP Response Error: {0}")]
   Response(attohttpc::StatusCode),
@@ -62,6 +62,9 @@ pub struct HttpRequestOptions {
   pub body_type: Option<BodyType>,
   /// The response type (defaults to Json)
   pub response_type: Option<ResponseType>,
+  /// Whether to skip the TLS certificate and hostname verification.
+  /// Dangerous: the request can be intercepted by anyone in the middle.
+  pub accept_invalid_certs: Option<bool>,
 }
 
 /// The builder for HttpRequestOptions.
@@ -107,6 +110,9 @@ pub struct HttpRequestBuilder {
   pub body_type: Option<BodyType>,
   /// The response type (defaults to Json)
   pub response_type: Option<ResponseType>,
+  /// Whether to skip the TLS certificate and hostname verification.
+  /// Dangerous: the request can be intercepted by anyone in the middle.
+  pub accept_invalid_certs: Option<bool>,
 }
 
 impl HttpRequestBuilder {
@@ -126,6 +132,7 @@ impl HttpRequestBuilder {
       allow_compression: None,
       body_type: None,
       response_type: None,
+      accept_invalid_certs: None,
     }
   }
 
@@ -195,6 +202,12 @@ impl HttpRequestBuilder {
     self
   }
 
+  /// Sets whether the request skips the TLS certificate and hostname verification.
+  pub fn accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+    self.accept_invalid_certs = Some(accept_invalid_certs);
+    self
+  }
+
   /// Builds the HttpRequestOptions.
   pub fn build(self) -> HttpRequestOptions {
     HttpRequestOptions {
@@ -211,6 +224,7 @@ impl HttpRequestBuilder {
       allow_compression: self.allow_compression,
       body_type: self.body_type,
       response_type: self.response_type,
+      accept_invalid_certs: self.accept_invalid_certs,
     }
   }
 }
@@ -252,9 +266,11 @@ pub fn make_request(options: HttpRequestOptions) -> crate::Result<Value> {
   if let Some(allow_compression) = options.allow_compression {
     builder = builder.allow_compression(allow_compression);
   }
-  builder = builder
-    .danger_accept_invalid_certs(true)
-    .danger_accept_invalid_hostnames(true);
+  if options.accept_invalid_certs.unwrap_or(false) {
+    builder = builder
+      .danger_accept_invalid_certs(true)
+      .danger_accept_invalid_hostnames(true);
+  }
 
   let response = if let Some(body) = options.body {
     match options.body_type.unwrap_or(BodyType::Auto) {
@@ -300,3 +316,59 @@ pub fn make_request(options: HttpRequestOptions) -> crate::Result<Value> {
     Err(response.status().into())
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::{make_request, HttpRequestBuilder, ResponseType};
+  use native_tls::{Identity, TlsAcceptor};
+  use serde_json::Value;
+  use std::{
+    io::{Read, Write},
+    net::TcpListener,
+  };
+
+  /// Starts an HTTPS server with a self-signed certificate, returning its URL.
+  fn https_server() -> String {
+    let identity =
+      Identity::from_pkcs12(include_bytes!("../test/fixture/localhost.p12"), "tauri").unwrap();
+    let acceptor = TlsAcceptor::new(identity).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        if let Ok(mut stream) = acceptor.accept(stream) {
+          let mut request = Vec::new();
+          let mut buffer = [0; 1024];
+          while !request.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut buffer) {
+              Ok(0) | Err(_) => break,
+              Ok(read) => request.extend_from_slice(&buffer[..read]),
+            }
+          }
+          let _ = stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+        }
+      }
+    });
+    format!("https://localhost:{}", port)
+  }
+
+  #[test]
+  fn verifies_tls_certificates() {
+    let url = https_server();
+
+    let options = HttpRequestBuilder::new("GET", &url)
+      .response_type(ResponseType::Text)
+      .build();
+    assert!(make_request(options).is_err());
+
+    let options = HttpRequestBuilder::new("GET", &url)
+      .response_type(ResponseType::Text)
+      .accept_invalid_certs(true)
+      .build();
+    assert_eq!(
+      make_request(options).unwrap(),
+      Value::String("ok".to_string())
+    );
+  }
+}
@@ -51,5 +51,7 @@ pub mod private {
     fn raw_config() -> &'static str;
     fn assets() -> &'static crate::assets::Assets;
     fn raw_tauri_script() -> &'static str;
+    fn inline_script_hashes() -> &'static [&'static str];
+    fn inline_style_hashes() -> &'static [&'static str];
   }
 }
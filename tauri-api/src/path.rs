@@ -49,6 +49,58 @@ pub enum BaseDirectory {
   App,
 }
 
+impl BaseDirectory {
+  /// The variable referencing the directory on path patterns, e.g. `$APP` or `$DOWNLOAD`.
+  pub fn variable(&self) -> &'static str {
+    match self {
+      Self::Audio => "$AUDIO",
+      Self::Cache => "$CACHE",
+      Self::Config => "$CONFIG",
+      Self::Data => "$DATA",
+      Self::LocalData => "$LOCALDATA",
+      Self::Desktop => "$DESKTOP",
+      Self::Document => "$DOCUMENT",
+      Self::Download => "$DOWNLOAD",
+      Self::Executable => "$EXE",
+      Self::Font => "$FONT",
+      Self::Home => "$HOME",
+      Self::Picture => "$PICTURE",
+      Self::Public => "$PUBLIC",
+      Self::Runtime => "$RUNTIME",
+      Self::Template => "$TEMPLATE",
+      Self::Video => "$VIDEO",
+      Self::Resource => "$RESOURCE",
+      Self::App => "$APP",
+    }
+  }
+
+  /// Gets the directory referenced by the given variable, e.g. `$APP`.
+  pub fn from_variable(variable: &str) -> Option<Self> {
+    let directory = match variable {
+      "$AUDIO" => Self::Audio,
+      "$CACHE" => Self::Cache,
+      "$CONFIG" => Self::Config,
+      "$DATA" => Self::Data,
+      "$LOCALDATA" => Self::LocalData,
+      "$DESKTOP" => Self::Desktop,
+      "$DOCUMENT" => Self::Document,
+      "$DOWNLOAD" => Self::Download,
+      "$EXE" => Self::Executable,
+      "$FONT" => Self::Font,
+      "$HOME" => Self::Home,
+      "$PICTURE" => Self::Picture,
+      "$PUBLIC" => Self::Public,
+      "$RUNTIME" => Self::Runtime,
+      "$TEMPLATE" => Self::Template,
+      "$VIDEO" => Self::Video,
+      "$RESOURCE" => Self::Resource,
+      "$APP" => Self::App,
+      _ => return None,
+    };
+    Some(directory)
+  }
+}
+
 /// Resolves the path with the optional base directory.
 ///
 /// # Example
@@ -1,14 +1,98 @@
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 
+/// The JS function that receives the messages sent by the runtime.
+pub const IPC_DISPATCHER: &str = "window.__TAURI_IPC__";
+
+/// The script that registers the [`IPC_DISPATCHER`] function.
+///
+/// The dispatcher parses the message and calls the `window[id]` callback with its payload.
+/// Messages without an id are logged to the console.
+pub const IPC_INITIALIZATION_SCRIPT: &str = r#"
+  Object.defineProperty(window, '__TAURI_IPC__', {
+    value: function (rawMessage) {
+      var message = JSON.parse(rawMessage)
+      if (message.id === null) {
+        if (message.ok) {
+          console.log(message.payload)
+        } else {
+          console.error(message.payload)
+        }
+      } else if (typeof window[message.id] === 'function') {
+        window[message.id](message.payload)
+      } else {
+        console.warn("[TAURI] Couldn't find callback id " + message.id + " in window. This happens when the app is reloaded while Rust is running an asynchronous operation.")
+      }
+    },
+    writable: false,
+    configurable: false
+  })
+"#;
+
+/// A message sent to the IPC dispatcher.
+#[derive(Serialize)]
+struct IpcMessage<'a> {
+  id: Option<&'a str>,
+  ok: bool,
+  payload: JsonValue,
+}
+
+/// Formats a JS string literal with the given content.
+///
+/// Besides quotes, backslashes and control characters, it escapes the characters that could
+/// end an HTML script block or a JS statement, so the literal is safe anywhere in a script.
+///
+/// # Examples
+/// ```
+/// use tauri_api::rpc::format_js_string;
+/// assert_eq!(format_js_string(r#"'"\</script>"#), r#""'\"\\\u003c/script\u003e""#);
+/// ```
+pub fn format_js_string(value: &str) -> String {
+  let literal = JsonValue::String(value.to_string()).to_string();
+  let mut escaped = String::with_capacity(literal.len());
+  for c in literal.chars() {
+    match c {
+      '<' => escaped.push_str("\\u003c"),
+      '>' => escaped.push_str("\\u003e"),
+      '&' => escaped.push_str("\\u0026"),
+      '\u{2028}' => escaped.push_str("\\u2028"),
+      '\u{2029}' => escaped.push_str("\\u2029"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// Formats the script that sends a message to the [`IPC_DISPATCHER`].
+///
+/// The message is serialized to JSON and passed as a string literal,
+/// so neither the id nor the payload is ever evaluated as code.
+///
+/// # Examples
+/// ```
+/// use tauri_api::rpc::format_ipc_message;
+/// let js = format_ipc_message(Some("callback-id"), true, "response".into());
+/// assert_eq!(
+///   js,
+///   r#"window.__TAURI_IPC__("{\"id\":\"callback-id\",\"ok\":true,\"payload\":\"response\"}")"#
+/// );
+/// ```
+pub fn format_ipc_message(id: Option<&str>, ok: bool, payload: JsonValue) -> String {
+  let message = serde_json::to_string(&IpcMessage { id, ok, payload })
+    .expect("failed to serialize IPC message");
+  format!("{}({})", IPC_DISPATCHER, format_js_string(&message))
+}
+
 /// Formats a function name and argument to be evaluated as callback.
+/// The callback is called by the [`IPC_DISPATCHER`].
 ///
 /// # Examples
 /// ```
 /// use tauri_api::rpc::format_callback;
 /// // callback with a string argument
 /// let cb = format_callback("callback-function-name", "the string response");
-/// assert!(cb.contains(r#"window["callback-function-name"]("the string response")"#));
+/// assert!(cb.contains(r#"\"id\":\"callback-function-name\""#));
+/// assert!(cb.contains(r#"\"payload\":\"the string response\""#));
 /// ```
 ///
 /// ```
@@ -22,20 +106,10 @@ use serde_json::Value as JsonValue;
 /// let cb = format_callback("callback-function-name", serde_json::to_value(&MyResponse {
 ///   value: "some value".to_string()
 /// }).expect("failed to serialize"));
-/// assert!(cb.contains(r#"window["callback-function-name"]({"value":"some value"})"#));
+/// assert!(cb.contains(r#"\"payload\":{\"value\":\"some value\"}"#));
 /// ```
 pub fn format_callback<T: Into<JsonValue>, S: AsRef<str>>(function_name: S, arg: T) -> String {
-  format!(
-    r#"
-      if (window["{fn}"]) {{
-        window["{fn}"]({arg})
-      }} else {{
-        console.warn("[TAURI] Couldn't find callback id {fn} in window. This happens when the app is reloaded while Rust is running an asynchronous operation.")
-      }}
-    "#,
-    fn = function_name.as_ref(),
-    arg = arg.into().to_string()
-  )
+  format_ipc_message(Some(function_name.as_ref()), true, arg.into())
 }
 
 /// Formats a Result type to its Promise response.
@@ -54,11 +128,11 @@ pub fn format_callback<T: Into<JsonValue>, S: AsRef<str>>(function_name: S, arg:
 /// use tauri_api::rpc::format_callback_result;
 /// let res: Result<u8, &str> = Ok(5);
 /// let cb = format_callback_result(res, "success_cb", "error_cb").expect("failed to format");
-/// assert!(cb.contains(r#"window["success_cb"](5)"#));
+/// assert!(cb.contains(r#"{\"id\":\"success_cb\",\"ok\":true,\"payload\":5}"#));
 ///
 /// let res: Result<&str, &str> = Err("error message here");
 /// let cb = format_callback_result(res, "success_cb", "error_cb").expect("failed to format");
-/// assert!(cb.contains(r#"window["error_cb"]("error message here")"#));
+/// assert!(cb.contains(r#"{\"id\":\"error_cb\",\"ok\":false,\"payload\":\"error message here\"}"#));
 /// ```
 pub fn format_callback_result<T: Serialize, E: Serialize>(
   result: Result<T, E>,
@@ -66,8 +140,16 @@ pub fn format_callback_result<T: Serialize, E: Serialize>(
   error_callback: impl AsRef<str>,
 ) -> crate::Result<String> {
   let rpc = match result {
-    Ok(res) => format_callback(success_callback, serde_json::to_value(res)?),
-    Err(err) => format_callback(error_callback, serde_json::to_value(err)?),
+    Ok(res) => format_ipc_message(
+      Some(success_callback.as_ref()),
+      true,
+      serde_json::to_value(res)?,
+    ),
+    Err(err) => format_ipc_message(
+      Some(error_callback.as_ref()),
+      false,
+      serde_json::to_value(err)?,
+    ),
   };
   Ok(rpc)
 }
@@ -76,22 +158,23 @@ pub fn format_callback_result<T: Serialize, E: Serialize>(
 mod test {
   use crate::rpc::*;
   use quickcheck_macros::quickcheck;
+  use serde_json::Value as JsonValue;
+
+  // parses the message sent to the IPC dispatcher by the given script
+  fn parse_ipc_message(js: &str) -> JsonValue {
+    let literal = js
+      .strip_prefix(&format!("{}(", IPC_DISPATCHER))
+      .and_then(|js| js.strip_suffix(')'))
+      .expect("not an IPC dispatcher call");
+    let message: String = serde_json::from_str(literal).expect("invalid string literal");
+    serde_json::from_str(&message).expect("invalid IPC message")
+  }
 
   // check abritrary strings in the format callback function
   #[quickcheck]
   fn qc_formating(f: String, a: String) -> bool {
-    // can not accept empty strings
-    if !f.is_empty() && !a.is_empty() {
-      // call format callback
-      let fc = format_callback(f.clone(), a.clone());
-      fc.contains(&format!(
-        r#"window["{}"]({})"#,
-        f,
-        serde_json::Value::String(a),
-      ))
-    } else {
-      true
-    }
+    let message = parse_ipc_message(&format_callback(f.clone(), a.clone()));
+    message["id"] == f && message["ok"] == true && message["payload"] == a
   }
 
   // check arbitrary strings in format_callback_result
@@ -99,15 +182,41 @@ mod test {
   fn qc_format_res(result: Result<String, String>, c: String, ec: String) -> bool {
     let resp = format_callback_result(result.clone(), c.clone(), ec.clone())
       .expect("failed to format callback result");
-    let (function, value) = match result {
-      Ok(v) => (c, v),
-      Err(e) => (ec, e),
+    let message = parse_ipc_message(&resp);
+    let (function, ok, value) = match result {
+      Ok(v) => (c, true, v),
+      Err(e) => (ec, false, e),
     };
 
-    resp.contains(&format!(
-      r#"window["{}"]({})"#,
-      function,
-      serde_json::Value::String(value),
-    ))
+    message["id"] == function && message["ok"] == ok && message["payload"] == value
+  }
+
+  #[test]
+  fn escapes_ipc_messages() {
+    let payloads = vec![
+      r#"'); alert('quote"#,
+      r#""); alert("double quote"#,
+      r#"\"); alert("backslash"#,
+      "</script><script>alert('script')</script>",
+      "line\u{2028}separator\u{2029}",
+    ];
+    for payload in payloads {
+      let js = format_ipc_message(Some(payload), false, payload.into());
+      assert!(!js.contains("</script>"), "{}", js);
+      assert!(
+        !js.contains('\u{2028}') && !js.contains('\u{2029}'),
+        "{}",
+        js
+      );
+      let message = parse_ipc_message(&js);
+      assert_eq!(message["id"], payload);
+      assert_eq!(message["ok"], false);
+      assert_eq!(message["payload"], payload);
+    }
+  }
+
+  #[quickcheck]
+  fn qc_js_string(value: String) -> bool {
+    serde_json::from_str::<String>(&format_js_string(&value)).ok() == Some(value)
   }
 }
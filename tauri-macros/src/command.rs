@@ -0,0 +1,220 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+  parse::{Parse, ParseStream},
+  punctuated::Punctuated,
+  FnArg, ItemFn, Pat, Path, Token,
+};
+
+/// The paths of the commands given to `generate_handler!`.
+pub(crate) struct CommandPaths(Punctuated<Path, Token![,]>);
+
+impl Parse for CommandPaths {
+  fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+    Punctuated::parse_terminated(input).map(Self)
+  }
+}
+
+/// The name of the function that runs the command from an invoke message.
+fn wrapper_ident(command: &Ident) -> Ident {
+  format_ident!("__tauri_command_{}", command)
+}
+
+/// Converts a snake_case argument name to the camelCase key used by JS.
+fn camel_case(name: &str) -> String {
+  let mut key = String::new();
+  let mut uppercase_next = false;
+  for c in name.trim_start_matches('_').chars() {
+    if c == '_' {
+      uppercase_next = true;
+    } else if uppercase_next {
+      key.extend(c.to_uppercase());
+      uppercase_next = false;
+    } else {
+      key.push(c);
+    }
+  }
+  key
+}
+
+/// Rejects the promise with the error and returns from the generated function.
+fn reject(error: TokenStream) -> TokenStream {
+  quote! {
+    {
+      let error = #error.to_string();
+      message
+        .respond(&webview_manager, async move { Err::<(), _>(error) })
+        .await;
+      return Ok(());
+    }
+  }
+}
+
+/// Keeps the command function and adds the function that runs it from an invoke message.
+///
+/// The arguments are resolved with `tauri::CommandArg` and the returned value is turned into
+/// the promise result by method resolution, so the argument and return types are never matched by name.
+pub(crate) fn command(function: ItemFn) -> syn::Result<TokenStream> {
+  let ident = &function.sig.ident;
+  let vis = &function.vis;
+  let wrapper = wrapper_ident(ident);
+  let reject_arg = reject(quote!(e));
+
+  let mut args = Vec::new();
+  let mut arg_values = Vec::new();
+  for (index, input) in function.sig.inputs.iter().enumerate() {
+    let input = match input {
+      FnArg::Typed(input) => input,
+      FnArg::Receiver(receiver) => {
+        return Err(syn::Error::new_spanned(
+          receiver,
+          "commands can't take `self`",
+        ))
+      }
+    };
+    let key = match &*input.pat {
+      Pat::Ident(pat) => camel_case(&pat.ident.to_string()),
+      pat => {
+        return Err(syn::Error::new_spanned(
+          pat,
+          "command arguments must be plain identifiers",
+        ))
+      }
+    };
+    let arg = format_ident!("arg{}", index);
+    // rejects the promise if the argument can't be resolved
+    arg_values.push(quote! {
+      let #arg = match ::tauri::CommandArg::from_command(#key, &message, &webview_manager) {
+        Ok(value) => value,
+        Err(e) => #reject_arg,
+      };
+    });
+    args.push(arg);
+  }
+
+  // sync commands run on the blocking thread pool so they don't stall the async workers
+  let call = if function.sig.asyncness.is_some() {
+    quote!(#ident(#(#args),*).await)
+  } else {
+    let reject_join = reject(quote!(e));
+    quote! {
+      match ::tauri::async_runtime::spawn_blocking(move || #ident(#(#args),*)).await {
+        Ok(response) => response,
+        Err(e) => #reject_join,
+      }
+    }
+  };
+
+  Ok(quote! {
+    #function
+
+    #[doc(hidden)]
+    #vis async fn #wrapper<D: ::tauri::ApplicationDispatcherExt + 'static>(
+      webview_manager: ::tauri::WebviewManager<D>,
+      message: ::tauri::InvokeMessage,
+    ) -> ::tauri::Result<()> {
+      #[allow(unused_imports)]
+      use ::tauri::command_private::{ResultKind as _, ValueKind as _};
+      #(#arg_values)*
+      let response = #call;
+      let kind = (&response).command_kind();
+      message
+        .respond(&webview_manager, async move { kind.into_result(response) })
+        .await;
+      Ok(())
+    }
+  })
+}
+
+/// Builds the list of commands registered with `AppBuilder::commands`, keyed by the function names.
+pub(crate) fn generate_handler(paths: CommandPaths) -> syn::Result<TokenStream> {
+  let mut names: Vec<String> = Vec::new();
+  let mut wrappers = Vec::new();
+  for path in paths.0 {
+    let mut wrapper = path.clone();
+    let last = wrapper
+      .segments
+      .last_mut()
+      .ok_or_else(|| syn::Error::new_spanned(&path, "expected a command path"))?;
+    let name = last.ident.to_string();
+    if names.contains(&name) {
+      return Err(syn::Error::new_spanned(
+        &path,
+        format!("command `{}` is already registered", name),
+      ));
+    }
+    names.push(name);
+    last.ident = wrapper_ident(&last.ident);
+    wrappers.push(wrapper);
+  }
+
+  Ok(quote! {
+    vec![#(::tauri::Command::new(#names, #wrappers)),*]
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::{camel_case, command, generate_handler, CommandPaths};
+  use syn::parse_quote;
+
+  #[test]
+  fn converts_argument_names_to_camel_case() {
+    assert_eq!(camel_case("first_arg"), "firstArg");
+    assert_eq!(camel_case("_state"), "state");
+    assert_eq!(camel_case("url"), "url");
+  }
+
+  #[test]
+  fn resolves_arguments_without_matching_type_names() {
+    let tokens = command(parse_quote! {
+      fn save(state: my::State, manager: WebviewManager) -> my::Result {}
+    })
+    .unwrap()
+    .to_string();
+    assert!(!tokens.contains("webview_manager . state"));
+    assert_eq!(tokens.matches("CommandArg :: from_command").count(), 2);
+    assert!(tokens.contains("\"state\""));
+    assert!(tokens.contains("\"manager\""));
+  }
+
+  #[test]
+  fn runs_sync_commands_on_the_blocking_pool() {
+    let sync = command(parse_quote!(
+      fn greet() {}
+    ))
+    .unwrap()
+    .to_string();
+    assert!(sync.contains("spawn_blocking"));
+    let async_ = command(parse_quote!(
+      async fn greet() {}
+    ))
+    .unwrap()
+    .to_string();
+    assert!(!async_.contains("spawn_blocking"));
+    assert!(async_.contains("greet () . await"));
+  }
+
+  #[test]
+  fn rejects_invalid_commands() {
+    assert!(command(parse_quote!(
+      fn greet(&self) {}
+    ))
+    .is_err());
+    assert!(command(parse_quote!(
+      fn greet((a, b): (u8, u8)) {}
+    ))
+    .is_err());
+  }
+
+  #[test]
+  fn registers_commands_by_name() {
+    let paths: CommandPaths = parse_quote!(greet, cmd::save);
+    let tokens = generate_handler(paths).unwrap().to_string();
+    assert!(tokens.contains("\"greet\" , __tauri_command_greet"));
+    assert!(tokens.contains("\"save\" , cmd :: __tauri_command_save"));
+
+    let paths: CommandPaths = parse_quote!(greet, other::greet);
+    assert!(generate_handler(paths).is_err());
+  }
+}
@@ -11,6 +11,7 @@ pub(crate) enum Error {
   IncludeDirEmptyFilename,
   ConfigDir,
   Serde(PathBuf, serde_json::Error),
+  Csp(String),
   Io(PathBuf, IoError),
 }
 
@@ -35,6 +36,10 @@ impl Error {
         error,
         path.display()
       ),
+      Csp(csp) => format!(
+        "The `tauri > security > csp` config `{}` isn't a valid header value, it can only contain visible ASCII characters and spaces",
+        csp
+      ),
       Io(path, error) => format!(
         "{:?} encountered for {} during tauri-macros",
         error.kind(),
@@ -63,6 +68,14 @@ impl Error {
         fn raw_tauri_script() -> &'static str {
           unimplemented!()
         }
+
+        fn inline_script_hashes() -> &'static [&'static str] {
+          unimplemented!()
+        }
+
+        fn inline_style_hashes() -> &'static [&'static str] {
+          unimplemented!()
+        }
       }
     }
   }
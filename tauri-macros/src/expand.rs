@@ -9,7 +9,11 @@ use std::{
   path::{Path, PathBuf},
 };
 use syn::{DeriveInput, Lit::Str, Meta::NameValue, MetaNameValue};
-use tauri_utils::{assets::AssetCompression, config::Config};
+use tauri_utils::{
+  assets::AssetCompression,
+  config::Config,
+  html::{csp_hash, inline_scripts, inline_styles, is_valid_csp},
+};
 
 pub(crate) fn load_context(input: DeriveInput) -> Result<TokenStream, Error> {
   let name = input.ident;
@@ -33,6 +37,13 @@ pub(crate) fn load_context(input: DeriveInput) -> Result<TokenStream, Error> {
 
   let full_config_path = Path::new(&manifest).join(config_file_path);
   let config = get_config(&full_config_path)?;
+
+  // the embedded server sends the CSP as a header
+  if let Some(csp) = &config.tauri.security.csp {
+    if !is_valid_csp(csp) {
+      return Err(Error::Csp(csp.clone()));
+    }
+  }
   let config_dir = full_config_path.parent().ok_or(Error::ConfigDir)?;
   let dist_dir = config_dir.join(config.build.dist_dir);
 
@@ -41,6 +52,17 @@ pub(crate) fn load_context(input: DeriveInput) -> Result<TokenStream, Error> {
 
   let tauri_script_path = dist_dir.join("__tauri.js");
 
+  // hash the inline scripts and styles of the entry so they're allowed by the CSP
+  let index_html = std::fs::read_to_string(dist_dir.join("index.html")).unwrap_or_default();
+  let inline_script_hashes: Vec<String> = inline_scripts(&index_html)
+    .iter()
+    .map(|script| csp_hash(script))
+    .collect();
+  let inline_style_hashes: Vec<String> = inline_styles(&index_html)
+    .iter()
+    .map(|style| csp_hash(style))
+    .collect();
+
   // format paths into a string to use them in quote!
   let tauri_config_path = full_config_path.display().to_string();
   let tauri_script_path = tauri_script_path.display().to_string();
@@ -66,6 +88,14 @@ pub(crate) fn load_context(input: DeriveInput) -> Result<TokenStream, Error> {
           fn raw_tauri_script() -> &'static str {
             include_str!(#tauri_script_path)
           }
+
+          fn inline_script_hashes() -> &'static [&'static str] {
+            &[#(#inline_script_hashes),*]
+          }
+
+          fn inline_style_hashes() -> &'static [&'static str] {
+            &[#(#inline_style_hashes),*]
+          }
       }
   })
 }
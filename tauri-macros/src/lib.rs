@@ -1,7 +1,8 @@
 extern crate proc_macro;
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, DeriveInput, ItemFn};
 
+mod command;
 mod error;
 mod expand;
 mod include_dir;
@@ -17,3 +18,29 @@ pub fn load_context(ast: TokenStream) -> TokenStream {
     .unwrap_or_else(|e| e.into_compile_error(&name))
     .into()
 }
+
+/// Turns a function into a command that JS can call with the `promisified` helper,
+/// e.g. `promisified({ cmd: 'my_command', firstArg: 'value' })`.
+///
+/// The arguments are resolved with `tauri::CommandArg`: the `WebviewManager` and `State` arguments are injected
+/// and the others are deserialized from the camelCase keys of the invoke message.
+/// A returned `Result` resolves or rejects the JS promise and any other serializable value resolves it.
+/// Sync functions run on the blocking thread pool of the Tauri runtime.
+#[proc_macro_attribute]
+pub fn command(_attributes: TokenStream, item: TokenStream) -> TokenStream {
+  let function = parse_macro_input!(item as ItemFn);
+  command::command(function)
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
+
+/// Builds the commands that route the invoke messages to the given `#[command]` functions by name.
+///
+/// Use it with `AppBuilder::commands(generate_handler![first_command, second_command])`.
+#[proc_macro]
+pub fn generate_handler(item: TokenStream) -> TokenStream {
+  let paths = parse_macro_input!(item as command::CommandPaths);
+  command::generate_handler(paths)
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
@@ -6,18 +6,20 @@ use serde_json::Value as JsonValue;
 
 use std::collections::HashMap;
 
+use crate::menu::Menu;
+
 /// The window webview URL options.
 #[derive(PartialEq, Debug, Clone)]
 pub enum WindowUrl {
-  /// The app's index URL.
-  App,
+  /// The app's index URL, or the path of a page relative to it.
+  App(Option<String>),
   /// A custom URL.
   Custom(String),
 }
 
 impl Default for WindowUrl {
   fn default() -> Self {
-    Self::App
+    Self::App(None)
   }
 }
 
@@ -30,7 +32,7 @@ impl<'de> Deserialize<'de> for WindowUrl {
     impl<'de> Visitor<'de> for StringVisitor {
       type Value = WindowUrl;
       fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        formatter.write_str("a string representing an url")
+        formatter.write_str("a string representing an url or a path relative to the app")
       }
 
       fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -38,9 +40,19 @@ impl<'de> Deserialize<'de> for WindowUrl {
         E: serde::de::Error,
       {
         if v.to_lowercase() == "app" {
-          Ok(WindowUrl::App)
+          return Ok(WindowUrl::App(None));
+        }
+        let path = match v.strip_prefix("app://") {
+          Some(path) => path,
+          // a value with a scheme is a custom URL
+          None if v.contains(':') => return Ok(WindowUrl::Custom(v.to_string())),
+          None => v,
+        };
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+          Ok(WindowUrl::App(None))
         } else {
-          Ok(WindowUrl::Custom(v.to_string()))
+          Ok(WindowUrl::App(Some(path.to_string())))
         }
       }
     }
@@ -100,6 +112,17 @@ pub struct WindowConfig {
   /// Whether the window should always be on top of other windows.
   #[serde(default)]
   pub always_on_top: bool,
+  /// The window menu bar. Defaults to the menu set with `AppBuilder::menu`.
+  #[serde(default)]
+  pub menu: Option<Menu>,
+  /// The remote origins whose pages can invoke the app commands, e.g. `https://tauri.studio`.
+  /// The app content is always trusted and every other origin is denied.
+  #[serde(default)]
+  pub trusted_origins: Vec<String>,
+  /// The API modules, `plugin:<name>` plugin namespaces and `InvokeHandler` the trusted remote origins can call,
+  /// e.g. `Window` or `plugin:sql`. The app commands are callable by every trusted origin.
+  #[serde(default)]
+  pub remote_api: Vec<String>,
 }
 
 fn default_window_label() -> String {
@@ -134,7 +157,7 @@ impl Default for WindowConfig {
   fn default() -> Self {
     Self {
       label: default_window_label(),
-      url: WindowUrl::App,
+      url: WindowUrl::App(None),
       x: None,
       y: None,
       width: default_width(),
@@ -151,6 +174,9 @@ impl Default for WindowConfig {
       visible: default_visible(),
       decorations: default_decorations(),
       always_on_top: false,
+      menu: None,
+      trusted_origins: Vec::new(),
+      remote_api: Vec::new(),
     }
   }
 }
@@ -419,6 +445,77 @@ impl CliConfig {
   }
 }
 
+/// The security configuration object.
+#[derive(PartialEq, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityConfig {
+  /// The Content Security Policy of the app content.
+  /// It's sent as a header by the embedded server and injected as a `<meta>` tag in the HTML assets,
+  /// with the hashes of the inline scripts and styles added to its `script-src` and `style-src` directives.
+  pub csp: Option<String>,
+}
+
+/// The file system API configuration object.
+#[derive(PartialEq, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FsConfig {
+  /// The glob patterns of the paths the file system API can access.
+  /// A pattern can start with a base directory variable, e.g. `$APP/**` or `$DOWNLOAD/*.csv`.
+  /// No path can be accessed when the scope is empty.
+  #[serde(default)]
+  pub scope: Vec<String>,
+}
+
+/// An argument of a shell command allowed on the scope.
+#[derive(PartialEq, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ShellAllowedArg {
+  /// A literal argument, always passed to the command.
+  Fixed(String),
+  /// An argument provided by the API call, which must fully match the validator regex.
+  Var {
+    /// The regex the argument must match.
+    validator: String,
+  },
+}
+
+/// A shell command the API can execute.
+#[derive(PartialEq, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellAllowedCommand {
+  /// The name the API calls the command by.
+  pub name: String,
+  /// The program to execute.
+  pub cmd: String,
+  /// The arguments of the command.
+  /// The API call provides the values of the `{ "validator": "regex" }` arguments, in order.
+  #[serde(default)]
+  pub args: Vec<ShellAllowedArg>,
+}
+
+/// The shell API configuration object.
+#[derive(PartialEq, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellConfig {
+  /// The commands the `execute` API can run.
+  #[serde(default)]
+  pub scope: Vec<ShellAllowedCommand>,
+}
+
+/// The HTTP API configuration object.
+#[derive(PartialEq, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpConfig {
+  /// The URL patterns the HTTP API can request, e.g. `https://api.tauri.studio/*`.
+  /// `*` matches any sequence of characters of the host or path.
+  /// No URL can be requested when the scope is empty.
+  #[serde(default)]
+  pub scope: Vec<String>,
+  /// Whether the HTTP API requests can skip the TLS certificate verification with their `acceptInvalidCerts` option.
+  #[serde(default)]
+  pub dangerous_allow_invalid_certs: bool,
+}
+
 /// The bundler configuration object.
 #[derive(PartialEq, Deserialize, Debug)]
 #[serde(tag = "bundle", rename_all = "camelCase")]
@@ -455,6 +552,22 @@ pub struct TauriConfig {
   /// The bundler configuration.
   #[serde(default)]
   pub bundle: BundleConfig,
+  /// Whether only one instance of the app can run at a time.
+  /// Launching the app again focuses the running instance and sends it the new args.
+  #[serde(default)]
+  pub single_instance: bool,
+  /// The security configuration.
+  #[serde(default)]
+  pub security: SecurityConfig,
+  /// The file system API configuration.
+  #[serde(default)]
+  pub fs: FsConfig,
+  /// The shell API configuration.
+  #[serde(default)]
+  pub shell: ShellConfig,
+  /// The HTTP API configuration.
+  #[serde(default)]
+  pub http: HttpConfig,
 }
 
 impl Default for TauriConfig {
@@ -464,6 +577,11 @@ impl Default for TauriConfig {
       embedded_server: EmbeddedServerConfig::default(),
       cli: None,
       bundle: BundleConfig::default(),
+      single_instance: false,
+      security: SecurityConfig::default(),
+      fs: FsConfig::default(),
+      shell: ShellConfig::default(),
+      http: HttpConfig::default(),
     }
   }
 }
@@ -533,6 +651,55 @@ mod test {
 
   // TODO: create a test that compares a config to a json config
 
+  #[test]
+  fn parses_window_urls() {
+    let parse = |url: &str| serde_json::from_value::<WindowUrl>(serde_json::json!(url)).unwrap();
+    assert_eq!(parse("app"), WindowUrl::App(None));
+    assert_eq!(parse("App"), WindowUrl::App(None));
+    assert_eq!(parse("app://"), WindowUrl::App(None));
+    assert_eq!(
+      parse("settings.html"),
+      WindowUrl::App(Some("settings.html".to_string()))
+    );
+    assert_eq!(
+      parse("app://settings/index.html"),
+      WindowUrl::App(Some("settings/index.html".to_string()))
+    );
+    assert_eq!(
+      parse("/settings.html"),
+      WindowUrl::App(Some("settings.html".to_string()))
+    );
+    assert_eq!(
+      parse("https://tauri.studio"),
+      WindowUrl::Custom("https://tauri.studio".to_string())
+    );
+    assert_eq!(
+      parse("about:blank"),
+      WindowUrl::Custom("about:blank".to_string())
+    );
+  }
+
+  #[test]
+  fn parses_shell_scope() {
+    let shell: ShellConfig = serde_json::from_value(serde_json::json!({
+      "scope": [{ "name": "list", "cmd": "ls", "args": ["-l", { "validator": "\\w+" }] }]
+    }))
+    .unwrap();
+    assert_eq!(
+      shell.scope,
+      vec![ShellAllowedCommand {
+        name: "list".to_string(),
+        cmd: "ls".to_string(),
+        args: vec![
+          ShellAllowedArg::Fixed("-l".to_string()),
+          ShellAllowedArg::Var {
+            validator: "\\w+".to_string()
+          }
+        ],
+      }]
+    );
+  }
+
   #[test]
   // test all of the default functions
   fn test_defaults() {
@@ -555,7 +722,7 @@ mod test {
     let tauri = TauriConfig {
       windows: vec![WindowConfig {
         label: "main".to_string(),
-        url: WindowUrl::App,
+        url: WindowUrl::App(None),
         x: None,
         y: None,
         width: 800f64,
@@ -572,6 +739,9 @@ mod test {
         visible: true,
         decorations: true,
         always_on_top: false,
+        menu: None,
+        trusted_origins: Vec::new(),
+        remote_api: Vec::new(),
       }],
       embedded_server: EmbeddedServerConfig {
         host: String::from("http://127.0.0.1"),
@@ -582,6 +752,14 @@ mod test {
         identifier: String::from(""),
       },
       cli: None,
+      single_instance: false,
+      security: SecurityConfig { csp: None },
+      fs: FsConfig { scope: Vec::new() },
+      shell: ShellConfig { scope: Vec::new() },
+      http: HttpConfig {
+        scope: Vec::new(),
+        dangerous_allow_invalid_certs: false,
+      },
     };
 
     // create a build config
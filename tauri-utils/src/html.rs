@@ -0,0 +1,217 @@
+use sha2::{Digest, Sha256};
+
+/// Finds the first opening tag with the given name, e.g. `head`, starting at the given byte index.
+/// Returns the byte indexes of the end of its name and of the end of the tag, right after its `>`.
+/// The document must be lowercased.
+fn opening_tag_end(lowercase_html: &str, tag: &str, from: usize) -> Option<(usize, usize)> {
+  let open = format!("<{}", tag);
+  let mut cursor = from;
+  while let Some(start) = lowercase_html[cursor..].find(&open) {
+    let name_end = cursor + start + open.len();
+    // skip the tags with a longer name, e.g. `<header>` when looking for `<head>`
+    match lowercase_html[name_end..].chars().next() {
+      Some(c) if c == '>' || c == '/' || c.is_whitespace() => {
+        let end = name_end + lowercase_html[name_end..].find('>')?;
+        return Some((name_end, end + 1));
+      }
+      _ => cursor = name_end,
+    }
+  }
+  None
+}
+
+/// The contents of the tags with the given name, skipping the tags rejected by the attributes filter.
+fn tag_contents<F: Fn(&str) -> bool>(html: &str, tag: &str, filter: F) -> Vec<String> {
+  let lowercase_html = html.to_ascii_lowercase();
+  let close = format!("</{}", tag);
+  let mut contents = Vec::new();
+  let mut cursor = 0;
+  while let Some((name_end, content_start)) = opening_tag_end(&lowercase_html, tag, cursor) {
+    let content_end = match lowercase_html[content_start..].find(&close) {
+      Some(end) => content_start + end,
+      None => break,
+    };
+    if filter(&lowercase_html[name_end..content_start]) {
+      contents.push(html[content_start..content_end].to_string());
+    }
+    cursor = content_end + close.len();
+  }
+  contents
+}
+
+/// The contents of the inline `<script>` tags of the HTML document.
+/// The scripts loaded with a `src` attribute are skipped.
+pub fn inline_scripts(html: &str) -> Vec<String> {
+  tag_contents(html, "script", |attributes| {
+    !attributes
+      .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+      .any(|attribute| attribute == "src" || attribute.starts_with("src="))
+  })
+}
+
+/// The contents of the `<style>` tags of the HTML document.
+pub fn inline_styles(html: &str) -> Vec<String> {
+  tag_contents(html, "style", |_| true)
+}
+
+/// The CSP source allowing the given inline script or style by its SHA-256 hash, e.g. `'sha256-...'`.
+pub fn csp_hash(content: &str) -> String {
+  format!(
+    "'sha256-{}'",
+    base64::encode(Sha256::digest(content.as_bytes()))
+  )
+}
+
+/// Whether the CSP can be sent as an HTTP header value: visible ASCII characters and spaces, without line breaks.
+pub fn is_valid_csp(csp: &str) -> bool {
+  csp
+    .bytes()
+    .all(|byte| byte == b' ' || byte.is_ascii_graphic())
+}
+
+/// Adds the hashes to a directive of the CSP, or to its `default-src` directive if it isn't defined.
+///
+/// Directives allowing `'unsafe-inline'` are left as is, since a hash would disable it.
+fn add_hashes(directives: &mut Vec<String>, directive: &str, hashes: &[String]) {
+  if hashes.is_empty() {
+    return;
+  }
+  let name = |d: &String| {
+    d.split_whitespace()
+      .next()
+      .unwrap_or_default()
+      .to_ascii_lowercase()
+  };
+  let index = directives
+    .iter()
+    .position(|d| name(d) == directive)
+    .or_else(|| directives.iter().position(|d| name(d) == "default-src"));
+  if let Some(index) = index {
+    let value = &mut directives[index];
+    if !value.to_ascii_lowercase().contains("'unsafe-inline'") {
+      for hash in hashes {
+        if !value.split_whitespace().any(|source| source == hash) {
+          value.push(' ');
+          value.push_str(hash);
+        }
+      }
+    }
+  }
+}
+
+/// Adds the hashes of the inline scripts and styles to the `script-src` and `style-src` directives of the CSP,
+/// falling back to `default-src`.
+pub fn csp_with_hashes(csp: &str, script_hashes: &[String], style_hashes: &[String]) -> String {
+  let mut directives: Vec<String> = csp
+    .split(';')
+    .map(|directive| directive.trim().to_string())
+    .filter(|directive| !directive.is_empty())
+    .collect();
+  add_hashes(&mut directives, "script-src", script_hashes);
+  add_hashes(&mut directives, "style-src", style_hashes);
+  directives.join("; ")
+}
+
+/// Injects the CSP as a `<meta http-equiv>` tag at the start of the `<head>` of the HTML document.
+pub fn inject_csp(html: &str, csp: &str) -> String {
+  let meta = format!(
+    r#"<meta http-equiv="Content-Security-Policy" content="{}">"#,
+    csp.replace('&', "&amp;").replace('"', "&quot;")
+  );
+  let lowercase_html = html.to_ascii_lowercase();
+  let position = opening_tag_end(&lowercase_html, "head", 0)
+    .or_else(|| opening_tag_end(&lowercase_html, "html", 0))
+    .map(|(_, end)| end)
+    .unwrap_or(0);
+  format!("{}{}{}", &html[..position], meta, &html[position..])
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn finds_inline_tags() {
+    let html = r#"<html>
+      <head>
+        <SCRIPT>window.a = 1</SCRIPT>
+        <script src="app.js"></script>
+        <script type="module" defer>window.b = 2</script>
+        <style>body { margin: 0 }</style>
+      </head>
+      <body><scripts>not a script</scripts></body>
+    </html>"#;
+    assert_eq!(
+      inline_scripts(html),
+      vec!["window.a = 1".to_string(), "window.b = 2".to_string()]
+    );
+    assert_eq!(inline_styles(html), vec!["body { margin: 0 }".to_string()]);
+    assert!(inline_scripts("<script>unterminated").is_empty());
+  }
+
+  #[test]
+  fn hashes_inline_tags() {
+    assert_eq!(
+      csp_hash("alert('Hello, world.');"),
+      "'sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng='"
+    );
+  }
+
+  #[test]
+  fn validates_the_csp() {
+    assert!(is_valid_csp("default-src 'self'; img-src https://*"));
+    assert!(!is_valid_csp("default-src 'self'\r\nSet-Cookie: a=b"));
+    assert!(!is_valid_csp("default-src 'self'\tscript-src *"));
+    assert!(!is_valid_csp("default-src https://tauri.stüdio"));
+  }
+
+  #[test]
+  fn adds_hashes_to_the_csp() {
+    let script_hashes = vec!["'sha256-a'".to_string()];
+    let style_hashes = vec!["'sha256-b'".to_string()];
+    assert_eq!(
+      csp_with_hashes(
+        "default-src 'self'; script-src 'self';",
+        &script_hashes,
+        &style_hashes
+      ),
+      "default-src 'self' 'sha256-b'; script-src 'self' 'sha256-a'"
+    );
+    // the hashes would disable 'unsafe-inline'
+    assert_eq!(
+      csp_with_hashes(
+        "default-src 'self' 'unsafe-inline'",
+        &script_hashes,
+        &style_hashes
+      ),
+      "default-src 'self' 'unsafe-inline'"
+    );
+    // the sources that don't restrict scripts and styles are left as is
+    assert_eq!(
+      csp_with_hashes("img-src *", &script_hashes, &style_hashes),
+      "img-src *"
+    );
+  }
+
+  #[test]
+  fn injects_the_csp() {
+    assert_eq!(
+      inject_csp(
+        "<html><head><title>app</title></head></html>",
+        r#"default-src "self""#
+      ),
+      r#"<html><head><meta http-equiv="Content-Security-Policy" content="default-src &quot;self&quot;"><title>app</title></head></html>"#
+    );
+    assert_eq!(
+      inject_csp(
+        "<html lang=\"en\"><header></header></html>",
+        "default-src 'self'"
+      ),
+      r#"<html lang="en"><meta http-equiv="Content-Security-Policy" content="default-src 'self'"><header></header></html>"#
+    );
+    assert_eq!(
+      inject_csp("<p>hi</p>", "default-src 'self'"),
+      r#"<meta http-equiv="Content-Security-Policy" content="default-src 'self'"><p>hi</p>"#
+    );
+  }
+}
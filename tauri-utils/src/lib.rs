@@ -5,6 +5,10 @@
 pub mod assets;
 /// Tauri config definition.
 pub mod config;
+/// HTML helpers for the Content Security Policy.
+pub mod html;
+/// Native menu definition.
+pub mod menu;
 /// Platform helpers
 pub mod platform;
 /// Process helpers
@@ -0,0 +1,217 @@
+use serde::Deserialize;
+
+/// A native menu, e.g. the menu bar of a window.
+#[derive(PartialEq, Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct Menu {
+  /// The menu entries, usually submenus.
+  pub items: Vec<MenuEntry>,
+}
+
+impl Menu {
+  /// Creates a menu with the given entries.
+  pub fn new(items: Vec<MenuEntry>) -> Self {
+    Self { items }
+  }
+
+  /// Gets the item with the given id, searching the submenus recursively.
+  pub fn item(&self, id: &str) -> Option<&MenuItem> {
+    find_item(&self.items, id)
+  }
+
+  /// Gets the item with the given id mutably, searching the submenus recursively.
+  pub fn item_mut(&mut self, id: &str) -> Option<&mut MenuItem> {
+    find_item_mut(&mut self.items, id)
+  }
+}
+
+fn find_item<'a>(entries: &'a [MenuEntry], id: &str) -> Option<&'a MenuItem> {
+  entries.iter().find_map(|entry| match entry {
+    MenuEntry::Item(item) if item.id == id => Some(item),
+    MenuEntry::Submenu(submenu) => find_item(&submenu.items, id),
+    _ => None,
+  })
+}
+
+fn find_item_mut<'a>(entries: &'a mut [MenuEntry], id: &str) -> Option<&'a mut MenuItem> {
+  entries.iter_mut().find_map(|entry| match entry {
+    MenuEntry::Item(item) if item.id == id => Some(item),
+    MenuEntry::Submenu(submenu) => find_item_mut(&mut submenu.items, id),
+    _ => None,
+  })
+}
+
+/// An entry of a menu.
+///
+/// In the config, a submenu is an object with `items`, an item is an object with an `id`
+/// and a native item is its name, e.g. `"separator"`.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum MenuEntry {
+  /// A nested menu.
+  Submenu(Submenu),
+  /// A custom item, reporting its clicks with the `menu-event` event.
+  Item(MenuItem),
+  /// An item provided by the OS.
+  Native(NativeMenuItem),
+}
+
+impl MenuEntry {
+  /// A native separator line.
+  pub fn separator() -> Self {
+    Self::Native(NativeMenuItem::Separator)
+  }
+}
+
+impl From<Submenu> for MenuEntry {
+  fn from(submenu: Submenu) -> Self {
+    Self::Submenu(submenu)
+  }
+}
+
+impl From<MenuItem> for MenuEntry {
+  fn from(item: MenuItem) -> Self {
+    Self::Item(item)
+  }
+}
+
+/// A nested menu.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Submenu {
+  /// The submenu title.
+  pub title: String,
+  /// Whether the submenu can be opened or not.
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+  /// The submenu entries.
+  pub items: Vec<MenuEntry>,
+}
+
+impl Submenu {
+  /// Creates a submenu with the given title and entries.
+  pub fn new(title: impl Into<String>, items: Vec<MenuEntry>) -> Self {
+    Self {
+      title: title.into(),
+      enabled: true,
+      items,
+    }
+  }
+
+  /// Sets whether the submenu can be opened or not.
+  pub fn enabled(mut self, enabled: bool) -> Self {
+    self.enabled = enabled;
+    self
+  }
+}
+
+/// A custom menu item.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MenuItem {
+  /// The item identifier, sent as the `menu-event` payload when the item is clicked.
+  pub id: String,
+  /// The item title.
+  pub title: String,
+  /// The keyboard shortcut of the item, e.g. `CmdOrCtrl+O`.
+  pub accelerator: Option<String>,
+  /// Whether the item can be clicked or not.
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+  /// Whether the item is checked or not. `None` means the item isn't checkable.
+  pub checked: Option<bool>,
+}
+
+impl MenuItem {
+  /// Creates an item with the given id and title.
+  pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+    Self {
+      id: id.into(),
+      title: title.into(),
+      accelerator: None,
+      enabled: true,
+      checked: None,
+    }
+  }
+
+  /// Sets the keyboard shortcut of the item.
+  pub fn accelerator(mut self, accelerator: impl Into<String>) -> Self {
+    self.accelerator = Some(accelerator.into());
+    self
+  }
+
+  /// Sets whether the item can be clicked or not.
+  pub fn enabled(mut self, enabled: bool) -> Self {
+    self.enabled = enabled;
+    self
+  }
+
+  /// Makes the item checkable, with the given state.
+  pub fn checked(mut self, checked: bool) -> Self {
+    self.checked = Some(checked);
+    self
+  }
+}
+
+/// A menu item provided by the OS.
+#[derive(PartialEq, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum NativeMenuItem {
+  /// A separator line.
+  Separator,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parses_menus() {
+    let menu: Menu = serde_json::from_value(serde_json::json!([
+      {
+        "title": "File",
+        "items": [
+          { "id": "open", "title": "Open", "accelerator": "CmdOrCtrl+O" },
+          "separator",
+          { "id": "autosave", "title": "Auto Save", "checked": true, "enabled": false }
+        ]
+      }
+    ]))
+    .unwrap();
+
+    assert_eq!(
+      menu,
+      Menu::new(vec![Submenu::new(
+        "File",
+        vec![
+          MenuItem::new("open", "Open")
+            .accelerator("CmdOrCtrl+O")
+            .into(),
+          MenuEntry::separator(),
+          MenuItem::new("autosave", "Auto Save")
+            .checked(true)
+            .enabled(false)
+            .into(),
+        ]
+      )
+      .into()])
+    );
+  }
+
+  #[test]
+  fn finds_nested_items() {
+    let mut menu = Menu::new(vec![Submenu::new(
+      "Edit",
+      vec![Submenu::new("Find", vec![MenuItem::new("find", "Find").into()]).into()],
+    )
+    .into()]);
+
+    menu.item_mut("find").unwrap().enabled = false;
+    assert!(!menu.item("find").unwrap().enabled);
+    assert!(menu.item("missing").is_none());
+  }
+}
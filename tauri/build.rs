@@ -24,10 +24,31 @@ fn main() {
 
     // window
     set_title: { any(all_api, feature = "set-title") },
+    create_window: { any(all_api, feature = "create-window") },
+    close_window: { any(all_api, feature = "close-window") },
+    set_prevent_close: { any(all_api, feature = "set-prevent-close") },
+    set_resizable: { any(all_api, feature = "set-resizable") },
+    set_size: { any(all_api, feature = "set-size") },
+    set_min_size: { any(all_api, feature = "set-min-size") },
+    set_max_size: { any(all_api, feature = "set-max-size") },
+    set_position: { any(all_api, feature = "set-position") },
+    set_fullscreen: { any(all_api, feature = "set-fullscreen") },
+    set_decorations: { any(all_api, feature = "set-decorations") },
+    set_always_on_top: { any(all_api, feature = "set-always-on-top") },
+    minimize_window: { any(all_api, feature = "minimize-window") },
+    unminimize_window: { any(all_api, feature = "unminimize-window") },
+    maximize_window: { any(all_api, feature = "maximize-window") },
+    unmaximize_window: { any(all_api, feature = "unmaximize-window") },
+    show_window: { any(all_api, feature = "show-window") },
+    hide_window: { any(all_api, feature = "hide-window") },
+    center_window: { any(all_api, feature = "center-window") },
+    start_dragging: { any(all_api, feature = "start-dragging") },
     open: { any(all_api, feature = "open") },
 
     // process
     execute: { any(all_api, feature = "execute") },
+    process_exit: { any(all_api, feature = "process-exit") },
+    process_restart: { any(all_api, feature = "process-restart") },
 
     // event
     event: { any(all_api, feature = "event") },
@@ -44,5 +65,11 @@ fn main() {
 
     // notification
     notification: { any(all_api, feature = "notification") },
+
+    // global shortcut
+    global_shortcut: { any(all_api, feature = "global-shortcut") },
+
+    // store
+    store: { any(all_api, feature = "store") },
   }
 }
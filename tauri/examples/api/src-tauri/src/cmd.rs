@@ -6,17 +6,17 @@ pub struct RequestBody {
   name: String,
 }
 
-#[derive(Deserialize)]
-#[serde(tag = "cmd", rename_all = "camelCase")]
-pub enum Cmd {
-  LogOperation {
-    event: String,
-    payload: Option<String>,
-  },
-  PerformRequest {
-    endpoint: String,
-    body: RequestBody,
-    callback: String,
-    error: String,
-  },
+#[tauri::command]
+pub fn log_operation(event: String, payload: Option<String>) {
+  println!("{} {:?}", event, payload);
+}
+
+// the command arguments are deserialized from the invoke message
+// if the returned value is Ok, the promise will be resolved with its value
+// if the returned value is Err, the promise will be rejected with its value
+#[tauri::command]
+pub async fn perform_request(endpoint: String, body: RequestBody) -> Result<String, String> {
+  println!("{} {:?}", endpoint, body);
+  // perform an async operation here
+  Ok("{ key: 'response', value: [{ id: 3 }] }".to_string())
 }
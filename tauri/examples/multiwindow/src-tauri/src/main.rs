@@ -13,9 +13,9 @@ fn main() {
       let current_webview = webview_manager.current_webview().unwrap().clone();
       let current_webview_ = current_webview.clone();
       let event_name = format!("window://{}", webview_manager.current_window_label());
-      current_webview.listen(event_name.clone(), move |msg| {
+      current_webview.listen(event_name.clone(), move |event| {
         current_webview_
-          .emit(&event_name, msg)
+          .emit(&event_name, event.payload())
           .expect("failed to emit");
       });
     })
@@ -1,43 +1,156 @@
-use crate::ApplicationExt;
+#[cfg(http_request)]
+use crate::scope::HttpScope;
+#[cfg(execute)]
+use crate::scope::ShellScope;
+use crate::{
+  api::menu::Menu,
+  async_runtime::Handle,
+  plugin::{PluginCollection, PluginStore},
+  scope::FsScope,
+  settings::{StoreBuilder, StoreRegistry},
+  ApplicationExt, SystemTray, UriSchemeProtocol, UriSchemeResponse, WindowEvent,
+};
 use futures::future::BoxFuture;
-use std::marker::PhantomData;
-use tauri_api::{config::Config, private::AsTauriContext};
+use std::{
+  collections::HashMap,
+  marker::PhantomData,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+use tauri_api::{config::Config, html::csp_with_hashes, private::AsTauriContext};
 
+mod command;
 pub(crate) mod event;
+mod invoke;
+mod ipc_scope;
+pub(crate) mod protocol;
+mod router;
 mod runner;
+mod single_instance;
+mod state;
 mod webview_manager;
 
+#[doc(hidden)]
+pub use command::private as command_private;
+pub use command::CommandArg;
+pub use event::{Event, EventHandler};
+pub use invoke::InvokeMessage;
+pub use router::Command;
+pub use state::State;
 pub use webview_manager::{WebviewDispatcher, WebviewManager};
 
+use event::EventBus;
+use router::Router;
+use state::StateManager;
+
 type InvokeHandler<D> =
   dyn Fn(WebviewManager<D>, String) -> BoxFuture<'static, crate::Result<()>> + Send + Sync;
 type Setup<D> = dyn Fn(WebviewManager<D>) -> BoxFuture<'static, ()> + Send + Sync;
+type OnExit<D> = dyn Fn(WebviewManager<D>) -> BoxFuture<'static, ()> + Send + Sync;
+type OnWindowEvent<D> = dyn Fn(&WebviewManager<D>, &WindowEvent) + Send + Sync;
 
 /// `App` runtime information.
 pub struct Context {
   pub(crate) config: Config,
+  pub(crate) config_path: &'static Path,
   pub(crate) tauri_script: &'static str,
   pub(crate) assets: &'static tauri_api::assets::Assets,
+  /// The CSP hashes of the inline scripts of the `index.html`, computed at compile time.
+  pub(crate) inline_script_hashes: &'static [&'static str],
+  /// The CSP hashes of the inline styles of the `index.html`, computed at compile time.
+  pub(crate) inline_style_hashes: &'static [&'static str],
+  /// The paths the file system API can access.
+  pub(crate) fs_scope: FsScope,
+  /// The requests the HTTP API can send.
+  #[cfg(http_request)]
+  pub(crate) http_scope: HttpScope,
+  /// The commands the shell API can execute.
+  #[cfg(execute)]
+  pub(crate) shell_scope: ShellScope,
 }
 
 impl Context {
   pub(crate) fn new<Context: AsTauriContext>() -> crate::Result<Self> {
+    let config: Config = serde_json::from_str(Context::raw_config())?;
     Ok(Self {
-      config: serde_json::from_str(Context::raw_config())?,
+      fs_scope: FsScope::new(&config.tauri.fs.scope),
+      #[cfg(http_request)]
+      http_scope: HttpScope::new(&config.tauri.http),
+      #[cfg(execute)]
+      shell_scope: ShellScope::new(&config.tauri.shell.scope)?,
+      config,
+      config_path: Context::config_path(),
       tauri_script: Context::raw_tauri_script(),
       assets: Context::assets(),
+      inline_script_hashes: Context::inline_script_hashes(),
+      inline_style_hashes: Context::inline_style_hashes(),
     })
   }
+
+  /// The `tauri > security > csp` config, allowing the inline scripts and styles of the dist `index.html` by their hashes.
+  ///
+  /// The initialization scripts are injected by the webview, so the CSP doesn't need to allow them.
+  pub(crate) fn csp(&self) -> Option<String> {
+    let csp = self.config.tauri.security.csp.as_ref()?;
+    let script_hashes: Vec<String> = self
+      .inline_script_hashes
+      .iter()
+      .map(ToString::to_string)
+      .collect();
+    let style_hashes: Vec<String> = self
+      .inline_style_hashes
+      .iter()
+      .map(ToString::to_string)
+      .collect();
+    Some(csp_with_hashes(csp, &script_hashes, &style_hashes))
+  }
+
+  /// The `devPath` directory, if the app runs in dev mode and the `devPath` isn't a dev server URL.
+  pub(crate) fn dev_dir(&self) -> Option<PathBuf> {
+    let dev_path = &self.config.build.dev_path;
+    if cfg!(dev) && !dev_path.starts_with("http") {
+      let config_dir = self.config_path.parent().unwrap_or_else(|| Path::new(""));
+      Some(config_dir.join(dev_path))
+    } else {
+      None
+    }
+  }
 }
 
 /// The application runner.
 pub struct App<A: ApplicationExt> {
-  /// The JS message handler.
+  /// The JS message handler, for messages that don't name a command.
   invoke_handler: Option<Box<InvokeHandler<A::Dispatcher>>>,
+  /// The commands JS can invoke by name.
+  router: Router<A::Dispatcher>,
   /// The setup callback, invoked when the webview is ready.
   setup: Option<Box<Setup<A::Dispatcher>>>,
+  /// The exit callback, invoked before the app exits.
+  on_exit: Option<Box<OnExit<A::Dispatcher>>>,
+  /// The window event callback, invoked with the events of every window.
+  on_window_event: Option<Box<OnWindowEvent<A::Dispatcher>>>,
+  /// Whether only one instance of the app can run at a time, overriding the `tauri > singleInstance` config.
+  single_instance: Option<bool>,
+  /// Whether closing the last window exits the app.
+  pub(crate) exit_on_last_window_closed: bool,
+  /// The custom URI scheme protocols registered on the webviews, keyed by scheme.
+  pub(crate) uri_scheme_protocols: HashMap<String, UriSchemeProtocol>,
+  /// The menu bar of the windows that don't define their own menu.
+  pub(crate) menu: Option<Menu>,
+  /// The system tray icon, with its icon path resolved.
+  pub(crate) system_tray: Option<SystemTray>,
+  /// The plugins of the app.
+  pub(crate) plugins: PluginStore<A::Dispatcher>,
+  /// The runtime running the app tasks.
+  pub(crate) runtime: Handle,
+  /// The Rust event listeners of the app.
+  pub(crate) events: EventBus,
+  /// The persistent key-value stores of the app.
+  pub(crate) stores: Arc<StoreRegistry>,
   /// The context the App was created with
   pub(crate) context: Context,
+  /// The values managed by the app.
+  pub(crate) state: Arc<StateManager>,
 }
 
 impl<A: ApplicationExt + 'static> App<A> {
@@ -46,26 +159,29 @@ impl<A: ApplicationExt + 'static> App<A> {
     runner::run(self).expect("Failed to build webview");
   }
 
-  /// Runs the invoke handler if defined.
-  /// Returns whether the message was consumed or not.
-  /// The message is considered consumed if the handler exists and returns an Ok Result.
-  pub(crate) async fn run_invoke_handler(
+  /// Runs the setup callback if defined.
+  pub(crate) async fn run_setup(&self, dispatcher: &WebviewManager<A::Dispatcher>) {
+    if let Some(ref setup) = self.setup {
+      let fut = setup(dispatcher.clone());
+      fut.await;
+    }
+  }
+
+  /// Runs the window event callback if defined.
+  pub(crate) fn run_on_window_event(
     &self,
     dispatcher: &WebviewManager<A::Dispatcher>,
-    arg: &str,
-  ) -> crate::Result<bool> {
-    if let Some(ref invoke_handler) = self.invoke_handler {
-      let fut = invoke_handler(dispatcher.clone(), arg.to_string());
-      fut.await.map(|_| true)
-    } else {
-      Ok(false)
+    event: &WindowEvent,
+  ) {
+    if let Some(ref on_window_event) = self.on_window_event {
+      on_window_event(dispatcher, event);
     }
   }
 
-  /// Runs the setup callback if defined.
-  pub(crate) async fn run_setup(&self, dispatcher: &WebviewManager<A::Dispatcher>) {
-    if let Some(ref setup) = self.setup {
-      let fut = setup(dispatcher.clone());
+  /// Runs the exit callback if defined.
+  pub(crate) async fn run_on_exit(&self, dispatcher: &WebviewManager<A::Dispatcher>) {
+    if let Some(ref on_exit) = self.on_exit {
+      let fut = on_exit(dispatcher.clone());
       fut.await;
     }
   }
@@ -74,10 +190,38 @@ impl<A: ApplicationExt + 'static> App<A> {
 /// The App builder.
 #[derive(Default)]
 pub struct AppBuilder<A: ApplicationExt, C: AsTauriContext> {
-  /// The JS message handler.
+  /// The JS message handler, for messages that don't name a command.
   invoke_handler: Option<Box<InvokeHandler<A::Dispatcher>>>,
+  /// The commands JS can invoke by name.
+  commands: Vec<Command<A::Dispatcher>>,
+  /// The first system tray, plugin, store or protocol registration error, returned on build.
+  error: Option<crate::Error>,
   /// The setup callback, invoked when the webview is ready.
   setup: Option<Box<Setup<A::Dispatcher>>>,
+  /// The exit callback, invoked before the app exits.
+  on_exit: Option<Box<OnExit<A::Dispatcher>>>,
+  /// The window event callback, invoked with the events of every window.
+  on_window_event: Option<Box<OnWindowEvent<A::Dispatcher>>>,
+  /// Whether only one instance of the app can run at a time, overriding the `tauri > singleInstance` config.
+  single_instance: Option<bool>,
+  /// Whether closing the last window exits the app.
+  exit_on_last_window_closed: bool,
+  /// The custom URI scheme protocols registered on the webviews, keyed by scheme.
+  uri_scheme_protocols: HashMap<String, UriSchemeProtocol>,
+  /// The menu bar of the windows that don't define their own menu.
+  menu: Option<Menu>,
+  /// The system tray icon, with its icon path resolved.
+  system_tray: Option<SystemTray>,
+  /// The plugins of the app.
+  plugins: PluginCollection<A::Dispatcher>,
+  /// The Rust event listeners of the app.
+  events: EventBus,
+  /// The runtime running the app tasks, if the app provides one.
+  runtime: Option<Handle>,
+  /// The persistent key-value stores of the app.
+  stores: StoreRegistry,
+  /// The values managed by the app.
+  state: StateManager,
   /// The configuration used
   config: PhantomData<C>,
 }
@@ -87,12 +231,35 @@ impl<A: ApplicationExt + 'static, C: AsTauriContext> AppBuilder<A, C> {
   pub fn new() -> Self {
     Self {
       invoke_handler: None,
+      commands: Vec::new(),
+      error: None,
       setup: None,
+      on_exit: None,
+      on_window_event: None,
+      single_instance: None,
+      exit_on_last_window_closed: false,
+      uri_scheme_protocols: Default::default(),
+      menu: None,
+      system_tray: None,
+      plugins: Default::default(),
+      events: Default::default(),
+      runtime: None,
+      stores: Default::default(),
+      state: Default::default(),
       config: Default::default(),
     }
   }
 
+  /// Adds commands that JS can invoke by name, usually generated with [`generate_handler!`](crate::generate_handler).
+  ///
+  /// Building the app fails if a command name is registered twice.
+  pub fn commands(mut self, commands: Vec<Command<A::Dispatcher>>) -> Self {
+    self.commands.extend(commands);
+    self
+  }
+
   /// Defines the JS message handler callback.
+  /// It receives the messages that don't name a registered command.
   pub fn invoke_handler<
     T: futures::Future<Output = crate::Result<()>> + Send + Sync + 'static,
     F: Fn(WebviewManager<A::Dispatcher>, String) -> T + Send + Sync + 'static,
@@ -120,21 +287,184 @@ impl<A: ApplicationExt + 'static, C: AsTauriContext> AppBuilder<A, C> {
     self
   }
 
+  /// Defines the exit callback, invoked before the plugins exit and the event loop stops.
+  pub fn on_exit<
+    T: futures::Future<Output = ()> + Send + Sync + 'static,
+    F: Fn(WebviewManager<A::Dispatcher>) -> T + Send + Sync + 'static,
+  >(
+    mut self,
+    on_exit: F,
+  ) -> Self {
+    self.on_exit = Some(Box::new(move |webview_manager| {
+      Box::pin(on_exit(webview_manager))
+    }));
+    self
+  }
+
+  /// Defines the window event callback, invoked with the events of every window before the window listeners.
+  ///
+  /// Unlike the listeners added once the app runs, it receives the events of the windows created at startup.
+  /// Call [`CloseRequest::prevent_close`](crate::CloseRequest::prevent_close) on a `CloseRequested` event
+  /// to keep the window open.
+  pub fn on_window_event<
+    F: Fn(&WebviewManager<A::Dispatcher>, &WindowEvent) + Send + Sync + 'static,
+  >(
+    mut self,
+    on_window_event: F,
+  ) -> Self {
+    self.on_window_event = Some(Box::new(on_window_event));
+    self
+  }
+
+  /// Listens to the events emitted by all windows and the app, from the moment the app runs.
+  ///
+  /// Unlike [`WebviewManager::listen`], it receives the events emitted before the setup callback runs.
+  pub fn listen<F: FnMut(Event) + Send + 'static>(
+    self,
+    event: impl AsRef<str>,
+    handler: F,
+  ) -> Self {
+    self.events.listen(event, None, false, handler);
+    self
+  }
+
+  /// Sets whether only one instance of the app can run at a time, overriding the `tauri > singleInstance` config.
+  ///
+  /// Launching the app again focuses the running instance and emits the `second-instance` event with the new args.
+  pub fn single_instance(mut self, single_instance: bool) -> Self {
+    self.single_instance = Some(single_instance);
+    self
+  }
+
+  /// Sets whether closing the last window exits the app, running the exit hooks first.
+  ///
+  /// Disabled by default, so the app keeps running until it calls [`WebviewManager::exit`].
+  pub fn exit_on_last_window_closed(mut self, exit_on_last_window_closed: bool) -> Self {
+    self.exit_on_last_window_closed = exit_on_last_window_closed;
+    self
+  }
+
+  /// Registers a custom URI scheme protocol, answering the webview requests to `<uri_scheme>://` URIs without a network socket.
+  ///
+  /// Building the app fails if the scheme is registered twice. The `tauri` scheme is reserved for the app assets.
+  pub fn register_uri_scheme_protocol<
+    F: Fn(&str) -> crate::Result<UriSchemeResponse> + Send + Sync + 'static,
+  >(
+    mut self,
+    uri_scheme: impl Into<String>,
+    handler: F,
+  ) -> Self {
+    let uri_scheme = uri_scheme.into();
+    if uri_scheme == protocol::ASSET_URI_SCHEME
+      || self.uri_scheme_protocols.contains_key(&uri_scheme)
+    {
+      self
+        .error
+        .get_or_insert(crate::Error::UriSchemeAlreadyRegistered(uri_scheme));
+    } else {
+      self
+        .uri_scheme_protocols
+        .insert(uri_scheme, Arc::new(handler));
+    }
+    self
+  }
+
+  /// Runs the app tasks on the given tokio runtime instead of starting a new one.
+  ///
+  /// The app must still be run on a thread that isn't a worker of a current thread runtime,
+  /// since it waits for the plugins to initialize on the given runtime.
+  ///
+  /// The [`async_runtime`](crate::async_runtime) functions use the first runtime given to an app.
+  pub fn runtime(mut self, handle: Handle) -> Self {
+    crate::async_runtime::set_app_handle(handle.clone());
+    self.runtime = Some(handle);
+    self
+  }
+
+  /// Sets the menu bar of the windows that don't define a `menu` in their config.
+  ///
+  /// Clicks on its items emit the `menu-event` event with the item id.
+  pub fn menu(mut self, menu: Menu) -> Self {
+    self.menu = Some(menu);
+    self
+  }
+
+  /// Adds a system tray icon to the app.
+  /// Relative icon paths are resolved from the resource directory.
+  ///
+  /// Its clicks emit the `system-tray-event` event.
+  /// Apps that only live in the system tray can define no windows in the config.
+  pub fn system_tray(mut self, mut system_tray: SystemTray) -> Self {
+    match crate::webview::resolve_icon_path(system_tray.icon) {
+      Ok(icon) => {
+        system_tray.icon = icon;
+        self.system_tray = Some(system_tray);
+      }
+      Err(e) => {
+        self.error.get_or_insert(e);
+      }
+    }
+    self
+  }
+
+  /// Adds a value to the app state, replacing the value of the same type if it was already managed.
+  ///
+  /// The invoke handler, the setup callback and the plugins get it with [`WebviewManager::state`].
+  pub fn manage<T: Send + Sync + 'static>(self, state: T) -> Self {
+    self.state.set(state);
+    self
+  }
+
+  /// Registers a persistent key-value store, opened on first use with [`WebviewManager::store`] or the JS store API.
+  /// The stores that aren't registered are opened with the default options.
+  ///
+  /// Building the app fails if the store name is invalid or registered twice.
+  pub fn store(mut self, store: StoreBuilder) -> Self {
+    if let Err(e) = self.stores.register(store) {
+      self.error.get_or_insert(e);
+    }
+    self
+  }
+
   /// Adds a plugin to the runtime.
+  /// Its commands are invoked with the `plugin:<name>|<command>` name.
+  ///
+  /// Building the app fails if a plugin name is registered twice.
   pub fn plugin(
-    self,
+    mut self,
     plugin: impl crate::plugin::Plugin<A::Dispatcher> + Send + Sync + Sync + 'static,
   ) -> Self {
-    crate::async_runtime::block_on(crate::plugin::register(A::plugin_store(), plugin));
+    if let Err(e) = self.plugins.add(plugin) {
+      self.error.get_or_insert(e);
+    }
     self
   }
 
   /// Builds the App.
   pub fn build(self) -> crate::Result<App<A>> {
+    if let Some(e) = self.error {
+      return Err(e);
+    }
+    let context = Context::new::<C>()?;
     Ok(App {
       invoke_handler: self.invoke_handler,
+      router: Router::new(self.commands)?,
       setup: self.setup,
-      context: Context::new::<C>()?,
+      on_exit: self.on_exit,
+      on_window_event: self.on_window_event,
+      single_instance: self.single_instance,
+      exit_on_last_window_closed: self.exit_on_last_window_closed,
+      uri_scheme_protocols: self.uri_scheme_protocols,
+      menu: self.menu,
+      system_tray: self.system_tray,
+      plugins: Arc::new(crate::async_runtime::Mutex::new(self.plugins)),
+      runtime: self
+        .runtime
+        .unwrap_or_else(crate::async_runtime::default_handle),
+      events: self.events,
+      stores: Arc::new(self.stores),
+      context,
+      state: Arc::new(self.state),
     })
   }
 }
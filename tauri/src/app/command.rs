@@ -0,0 +1,97 @@
+use super::{InvokeMessage, State, WebviewManager};
+use crate::ApplicationDispatcherExt;
+
+use serde::de::DeserializeOwned;
+
+/// A type that can be an argument of a [`#[command]`](crate::command) function.
+///
+/// The `WebviewManager` and `State` arguments are injected and any other argument is deserialized
+/// from the camelCase key of the invoke message.
+pub trait CommandArg<D: ApplicationDispatcherExt>: Sized {
+  /// Resolves the argument with the given key from the invoke message.
+  fn from_command(
+    key: &str,
+    message: &InvokeMessage,
+    webview_manager: &WebviewManager<D>,
+  ) -> crate::Result<Self>;
+}
+
+impl<D: ApplicationDispatcherExt, T: DeserializeOwned> CommandArg<D> for T {
+  fn from_command(
+    key: &str,
+    message: &InvokeMessage,
+    _webview_manager: &WebviewManager<D>,
+  ) -> crate::Result<Self> {
+    message.arg(key)
+  }
+}
+
+impl<D: ApplicationDispatcherExt, T: Send + Sync + 'static> CommandArg<D> for State<T> {
+  fn from_command(
+    _key: &str,
+    _message: &InvokeMessage,
+    webview_manager: &WebviewManager<D>,
+  ) -> crate::Result<Self> {
+    webview_manager.state()
+  }
+}
+
+impl<D: ApplicationDispatcherExt> CommandArg<D> for WebviewManager<D> {
+  fn from_command(
+    _key: &str,
+    _message: &InvokeMessage,
+    webview_manager: &WebviewManager<D>,
+  ) -> crate::Result<Self> {
+    Ok(webview_manager.clone())
+  }
+}
+
+/// Turns the value returned by a command into the result of its promise.
+///
+/// The generated code calls `(&value).command_kind().into_result(value)` with both traits in scope:
+/// method resolution picks [`ResultKind`] for a `Result` and falls back to [`ValueKind`]
+/// for any other serializable value, without looking at the name of the return type.
+#[doc(hidden)]
+pub mod private {
+  use serde::Serialize;
+
+  /// The kind of the commands returning a `Result`.
+  pub struct ResultTag;
+
+  /// Selects [`ResultTag`] for the `Result` values.
+  pub trait ResultKind {
+    /// The kind of the command.
+    fn command_kind(&self) -> ResultTag {
+      ResultTag
+    }
+  }
+
+  impl<T: Serialize, E: Serialize> ResultKind for Result<T, E> {}
+
+  impl ResultTag {
+    /// The `Result` resolves or rejects the promise.
+    pub fn into_result<T, E>(self, value: Result<T, E>) -> Result<T, E> {
+      value
+    }
+  }
+
+  /// The kind of the commands returning any other serializable value.
+  pub struct ValueTag;
+
+  /// Selects [`ValueTag`] for the values that aren't a `Result`.
+  pub trait ValueKind {
+    /// The kind of the command.
+    fn command_kind(&self) -> ValueTag {
+      ValueTag
+    }
+  }
+
+  impl<T: Serialize> ValueKind for &T {}
+
+  impl ValueTag {
+    /// The value resolves the promise.
+    pub fn into_result<T>(self, value: T) -> Result<T, ()> {
+      Ok(value)
+    }
+  }
+}
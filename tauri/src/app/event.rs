@@ -4,37 +4,77 @@ use std::{
   sync::{Arc, Mutex},
 };
 
-use crate::ApplicationDispatcherExt;
+use crate::{salt::SaltStore, ApplicationDispatcherExt, SystemTrayEvent, WindowEvent};
 use lazy_static::lazy_static;
-use once_cell::sync::Lazy;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
+use tauri_api::rpc::format_ipc_message;
 
-/// An event handler.
-struct EventHandler {
+/// The event emitted with the item id when a menu item is clicked.
+pub(crate) const MENU_EVENT: &str = "menu-event";
+
+/// The event emitted when the system tray is clicked or one of its menu items is clicked.
+pub(crate) const SYSTEM_TRAY_EVENT: &str = "system-tray-event";
+
+/// An event received by a Rust listener.
+#[derive(Debug, Clone)]
+pub struct Event {
+  payload: Option<String>,
+  window_label: Option<String>,
+}
+
+impl Event {
+  /// The event payload, serialized as JSON.
+  pub fn payload(&self) -> Option<&str> {
+    self.payload.as_deref()
+  }
+
+  /// The label of the window that emitted the event, `None` for the events emitted by the app,
+  /// e.g. the system tray events.
+  pub fn window_label(&self) -> Option<&str> {
+    self.window_label.as_deref()
+  }
+}
+
+/// The identifier of an event listener, used to remove it with `unlisten`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventHandler(uuid::Uuid);
+
+/// An event listener.
+struct Listener {
+  id: EventHandler,
+  /// The window the listener is scoped to, `None` to listen to the events of all windows and the app.
+  window_label: Option<String>,
+  /// Whether the listener is removed after its first event.
+  once: bool,
   /// The on event callback.
-  on_event: Box<dyn FnMut(Option<String>) + Send>,
+  on_event: Arc<Mutex<dyn FnMut(Event) + Send>>,
 }
 
-type Listeners = Arc<Mutex<HashMap<String, Vec<EventHandler>>>>;
+impl Listener {
+  /// Whether the listener receives the events emitted by the given window.
+  fn receives(&self, window_label: Option<&str>) -> bool {
+    self.window_label.is_none() || self.window_label.as_deref() == window_label
+  }
+}
 
 lazy_static! {
   static ref EMIT_FUNCTION_NAME: String = uuid::Uuid::new_v4().to_string();
+  static ref LISTEN_FUNCTION_NAME: String = uuid::Uuid::new_v4().to_string();
   static ref EVENT_LISTENERS_OBJECT_NAME: String = uuid::Uuid::new_v4().to_string();
   static ref EVENT_QUEUE_OBJECT_NAME: String = uuid::Uuid::new_v4().to_string();
 }
 
-/// Gets the listeners map.
-fn listeners() -> &'static Listeners {
-  static LISTENERS: Lazy<Listeners> = Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
-  &LISTENERS
-}
-
 /// the emit JS function name
 pub fn emit_function_name() -> String {
   EMIT_FUNCTION_NAME.to_string()
 }
 
+/// the listen JS function name
+pub fn listen_function_name() -> String {
+  LISTEN_FUNCTION_NAME.to_string()
+}
+
 /// the event listeners JS object name
 pub fn event_listeners_object_name() -> String {
   EVENT_LISTENERS_OBJECT_NAME.to_string()
@@ -45,18 +85,95 @@ pub fn event_queue_object_name() -> String {
   EVENT_QUEUE_OBJECT_NAME.to_string()
 }
 
-/// Adds an event listener for JS events.
-pub fn listen<F: FnMut(Option<String>) + Send + 'static>(id: impl AsRef<str>, handler: F) {
-  let mut l = listeners()
-    .lock()
-    .expect("Failed to lock listeners: listen()");
-  let handler = EventHandler {
-    on_event: Box::new(handler),
-  };
-  if let Some(listeners) = l.get_mut(id.as_ref()) {
-    listeners.push(handler);
-  } else {
-    l.insert(id.as_ref().to_string(), vec![handler]);
+/// The Rust event listeners of an app and the salts of the events it emits to JS.
+#[derive(Clone, Default)]
+pub(crate) struct EventBus {
+  listeners: Arc<Mutex<HashMap<String, Vec<Listener>>>>,
+  salts: SaltStore,
+}
+
+impl EventBus {
+  /// The salts of the events emitted to JS.
+  pub(crate) fn salts(&self) -> &SaltStore {
+    &self.salts
+  }
+
+  /// Adds an event listener, scoped to the given window if any.
+  pub(crate) fn listen<F: FnMut(Event) + Send + 'static>(
+    &self,
+    event: impl AsRef<str>,
+    window_label: Option<String>,
+    once: bool,
+    handler: F,
+  ) -> EventHandler {
+    let id = EventHandler(uuid::Uuid::new_v4());
+    let listener = Listener {
+      id,
+      window_label,
+      once,
+      on_event: Arc::new(Mutex::new(handler)),
+    };
+    self
+      .listeners
+      .lock()
+      .expect("Failed to lock listeners: listen()")
+      .entry(event.as_ref().to_string())
+      .or_insert_with(Vec::new)
+      .push(listener);
+    id
+  }
+
+  /// Removes an event listener.
+  pub(crate) fn unlisten(&self, handler: EventHandler) {
+    let mut l = self
+      .listeners
+      .lock()
+      .expect("Failed to lock listeners: unlisten()");
+    for listeners in l.values_mut() {
+      listeners.retain(|listener| listener.id != handler);
+    }
+  }
+
+  /// Removes the event listeners scoped to the given window.
+  pub(crate) fn unlisten_window(&self, window_label: &str) {
+    let mut l = self
+      .listeners
+      .lock()
+      .expect("Failed to lock listeners: unlisten_window()");
+    for listeners in l.values_mut() {
+      listeners.retain(|listener| listener.window_label.as_deref() != Some(window_label));
+    }
+  }
+
+  /// Triggers the given event with its payload on the Rust listeners.
+  ///
+  /// The listeners scoped to a window only receive the events emitted by that window.
+  pub(crate) fn trigger(&self, event: &str, window_label: Option<&str>, data: Option<String>) {
+    let handlers: Vec<_> = {
+      let mut l = self
+        .listeners
+        .lock()
+        .expect("Failed to lock listeners: trigger()");
+      let listeners = match l.get_mut(event) {
+        Some(listeners) => listeners,
+        None => return,
+      };
+      let handlers = listeners
+        .iter()
+        .filter(|listener| listener.receives(window_label))
+        .map(|listener| listener.on_event.clone())
+        .collect();
+      listeners.retain(|listener| !(listener.once && listener.receives(window_label)));
+      handlers
+    };
+
+    // the handlers run without the lock, so they can add and remove listeners
+    for handler in handlers {
+      (handler.lock().expect("Failed to lock event handler"))(Event {
+        payload: data.clone(),
+        window_label: window_label.map(ToString::to_string),
+      });
+    }
   }
 }
 
@@ -66,7 +183,7 @@ pub fn emit<D: ApplicationDispatcherExt, S: Serialize>(
   event: impl AsRef<str>,
   payload: Option<S>,
 ) -> crate::Result<()> {
-  let salt = crate::salt::generate();
+  let salt = webview_dispatcher.events().salts().generate();
 
   let js_payload = if let Some(payload_value) = payload {
     serde_json::to_value(payload_value)?
@@ -74,29 +191,77 @@ pub fn emit<D: ApplicationDispatcherExt, S: Serialize>(
     JsonValue::Null
   };
 
-  webview_dispatcher.eval(&format!(
-    "window['{}']({{type: '{}', payload: {}}}, '{}')",
-    emit_function_name(),
-    event.as_ref(),
-    js_payload,
-    salt
-  ));
+  webview_dispatcher.eval(&emit_script(event.as_ref(), js_payload, &salt));
 
   Ok(())
 }
 
-/// Triggers the given event with its payload.
-pub fn on_event(event: String, data: Option<String>) {
-  let mut l = listeners()
-    .lock()
-    .expect("Failed to lock listeners: on_event()");
+/// The script that sends the event to the JS emit function, through the IPC dispatcher.
+fn emit_script(event: &str, payload: JsonValue, salt: &str) -> String {
+  format_ipc_message(
+    Some(&emit_function_name()),
+    true,
+    serde_json::json!({
+      "payload": { "type": event, "payload": payload },
+      "salt": salt,
+    }),
+  )
+}
 
-  if l.contains_key(&event) {
-    let listeners = l.get_mut(&event).expect("Failed to get mutable handler");
-    for handler in listeners {
-      (handler.on_event)(data.clone());
-    }
+/// Forwards a window event to the JS listeners of the window and to the Rust event listeners of the window,
+/// as a `tauri://` event, or a `menu-event` event for menu clicks.
+pub(crate) fn emit_window_event<D: ApplicationDispatcherExt>(
+  webview_dispatcher: &crate::WebviewDispatcher<D>,
+  event: &WindowEvent,
+) {
+  let (name, payload) = match event {
+    WindowEvent::CloseRequested(_) => ("tauri://close-requested", JsonValue::Null),
+    WindowEvent::Destroyed => ("tauri://destroyed", JsonValue::Null),
+    WindowEvent::Resized { width, height } => (
+      "tauri://resize",
+      serde_json::json!({ "width": width, "height": height }),
+    ),
+    WindowEvent::Moved { x, y } => ("tauri://move", serde_json::json!({ "x": x, "y": y })),
+    WindowEvent::Focused(true) => ("tauri://focus", JsonValue::Null),
+    WindowEvent::Focused(false) => ("tauri://blur", JsonValue::Null),
+    WindowEvent::ScaleFactorChanged {
+      scale_factor,
+      width,
+      height,
+    } => (
+      "tauri://scale-change",
+      serde_json::json!({ "scaleFactor": scale_factor, "width": width, "height": height }),
+    ),
+    WindowEvent::MenuItemClicked(id) => (MENU_EVENT, JsonValue::String(id.clone())),
+  };
+
+  // the webview of a destroyed window can't be reached anymore
+  let webview_alive = !matches!(event, WindowEvent::Destroyed);
+  if cfg!(event) && webview_alive {
+    let _ = emit(webview_dispatcher, name, Some(payload.clone()));
   }
+  webview_dispatcher.events().trigger(
+    name,
+    Some(webview_dispatcher.label()),
+    if payload.is_null() {
+      None
+    } else {
+      Some(payload.to_string())
+    },
+  );
+}
+
+/// Forwards a system tray event to the JS listeners of all windows and to the Rust event listeners.
+pub(crate) fn emit_system_tray_event<D: ApplicationDispatcherExt>(
+  webview_manager: &crate::WebviewManager<D>,
+  event: &SystemTrayEvent,
+) {
+  if cfg!(event) {
+    let _ = webview_manager.emit_all(SYSTEM_TRAY_EVENT, Some(event));
+  }
+  webview_manager
+    .events()
+    .trigger(SYSTEM_TRAY_EVENT, None, serde_json::to_string(event).ok());
 }
 
 #[cfg(test)]
@@ -104,38 +269,151 @@ mod test {
   use super::*;
   use proptest::prelude::*;
 
+  #[test]
+  fn escapes_emitted_events() {
+    let event = r#"event'); alert("name\</script>"#;
+    let payload = serde_json::json!({ "message": "payload'); alert(\"</script>\\" });
+    let js = emit_script(event, payload.clone(), "salt");
+    assert!(!js.contains("</script>"));
+
+    let literal = js
+      .strip_prefix(&format!("{}(", tauri_api::rpc::IPC_DISPATCHER))
+      .and_then(|js| js.strip_suffix(')'))
+      .expect("not an IPC dispatcher call");
+    let message: JsonValue =
+      serde_json::from_str(&serde_json::from_str::<String>(literal).unwrap()).unwrap();
+    assert_eq!(message["id"], emit_function_name());
+    assert_eq!(message["payload"]["payload"]["type"], event);
+    assert_eq!(message["payload"]["payload"]["payload"], payload);
+    assert_eq!(message["payload"]["salt"], "salt");
+  }
+
+  #[test]
+  fn scopes_listeners_to_windows() {
+    let bus = EventBus::default();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_ = events.clone();
+    bus.listen("scoped-event", None, false, move |event| {
+      events_
+        .lock()
+        .unwrap()
+        .push(("app", event.window_label().map(ToString::to_string)))
+    });
+    let events_ = events.clone();
+    bus.listen(
+      "scoped-event",
+      Some("main".to_string()),
+      false,
+      move |event| {
+        events_
+          .lock()
+          .unwrap()
+          .push(("main", event.window_label().map(ToString::to_string)))
+      },
+    );
+
+    bus.trigger("scoped-event", Some("settings"), None);
+    bus.trigger("scoped-event", Some("main"), None);
+    bus.unlisten_window("main");
+    bus.trigger("scoped-event", None, None);
+
+    assert_eq!(
+      *events.lock().unwrap(),
+      vec![
+        ("app", Some("settings".to_string())),
+        ("app", Some("main".to_string())),
+        ("main", Some("main".to_string())),
+        ("app", None),
+      ]
+    );
+  }
+
+  #[test]
+  fn removes_listeners() {
+    let bus = EventBus::default();
+    let payloads = Arc::new(Mutex::new(Vec::new()));
+    let payloads_ = payloads.clone();
+    bus.listen("once-event", None, true, move |event| {
+      payloads_
+        .lock()
+        .unwrap()
+        .push(event.payload().map(ToString::to_string))
+    });
+    let handler = Arc::new(Mutex::new(None));
+    let handler_ = handler.clone();
+    let payloads_ = payloads.clone();
+    let bus_ = bus.clone();
+    let id = bus.listen("once-event", None, false, move |event| {
+      payloads_
+        .lock()
+        .unwrap()
+        .push(event.payload().map(ToString::to_string));
+      // the handlers can remove listeners
+      if let Some(id) = *handler_.lock().unwrap() {
+        bus_.unlisten(id);
+      }
+    });
+    handler.lock().unwrap().replace(id);
+
+    bus.trigger("once-event", None, Some("1".to_string()));
+    bus.trigger("once-event", None, Some("2".to_string()));
+
+    assert_eq!(
+      *payloads.lock().unwrap(),
+      vec![Some("1".to_string()), Some("1".to_string())]
+    );
+  }
+
+  #[test]
+  fn isolates_event_buses() {
+    let bus = EventBus::default();
+    let other_bus = EventBus::default();
+    bus.listen("isolated-event", None, false, |_| {
+      panic!("the listener received the event of another bus")
+    });
+    other_bus.trigger("isolated-event", None, None);
+
+    let salt = bus.salts().generate();
+    assert!(!other_bus.salts().is_valid(salt.clone()));
+    assert!(bus.salts().is_valid(salt.clone()));
+    // the salt is only valid once
+    assert!(!bus.salts().is_valid(salt));
+  }
+
   // dummy event handler function
-  fn event_fn(s: Option<String>) {
+  fn event_fn(s: Event) {
     println!("{:?}", s);
   }
 
   proptest! {
     #![proptest_config(ProptestConfig::with_cases(10000))]
     #[test]
-    // check to see if listen() is properly passing keys into the LISTENERS map
+    // check to see if listen() is properly passing keys into the listeners map
     fn listeners_check_key(e in "[a-z]+") {
+      let bus = EventBus::default();
       // clone e as the key
       let key = e.clone();
       // pass e and an dummy func into listen
-      listen(e, event_fn);
+      bus.listen(e, None, false, event_fn);
 
       // lock mutex
-      let l = listeners().lock().unwrap();
+      let l = bus.listeners.lock().unwrap();
 
       // check if the generated key is in the map
       assert_eq!(l.contains_key(&key), true);
     }
 
     #[test]
-    // check to see if listen inputs a handler function properly into the LISTENERS map.
+    // check to see if listen inputs a handler function properly into the listeners map.
     fn listeners_check_fn(e in "[a-z]+") {
+       let bus = EventBus::default();
        // clone e as the key
        let key = e.clone();
        // pass e and an dummy func into listen
-       listen(e, event_fn);
+       bus.listen(e, None, false, event_fn);
 
        // lock mutex
-       let mut l = listeners().lock().unwrap();
+       let mut l = bus.listeners.lock().unwrap();
 
        // check if l contains key
        if l.contains_key(&key) {
@@ -152,17 +430,18 @@ mod test {
     }
 
     #[test]
-    // check to see if on_event properly grabs the stored function from listen.
-    fn check_on_event(e in "[a-z]+", d in "[a-z]+") {
+    // check to see if trigger properly grabs the stored function from listen.
+    fn check_trigger(e in "[a-z]+", d in "[a-z]+") {
+      let bus = EventBus::default();
       // clone e as the key
       let key = e.clone();
       // call listen with e and the event_fn dummy func
-      listen(e.clone(), event_fn);
-      // call on event with e and d.
-      on_event(e, Some(d));
+      bus.listen(e.clone(), None, false, event_fn);
+      // call trigger with e and d.
+      bus.trigger(&e, None, Some(d));
 
       // lock the mutex
-      let l = listeners().lock().unwrap();
+      let l = bus.listeners.lock().unwrap();
 
       // assert that the key is contained in the listeners map
       assert!(l.contains_key(&key));
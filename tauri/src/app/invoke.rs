@@ -0,0 +1,99 @@
+use crate::{ApplicationDispatcherExt, WebviewManager};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use tauri_api::rpc::{format_callback, format_callback_result};
+
+/// A command invoked by JS, e.g. with `promisified({ cmd: 'my_command', firstArg: 'value' })`.
+///
+/// Routed to the [`Command`](crate::Command) registered with its name.
+#[derive(Debug, Deserialize)]
+pub struct InvokeMessage {
+  cmd: String,
+  callback: Option<String>,
+  error: Option<String>,
+  #[serde(flatten)]
+  args: Map<String, JsonValue>,
+}
+
+impl InvokeMessage {
+  /// Parses the invoke payload.
+  pub fn parse(payload: &str) -> crate::Result<Self> {
+    serde_json::from_str(payload).map_err(Into::into)
+  }
+
+  /// The command name.
+  pub fn command(&self) -> &str {
+    &self.cmd
+  }
+
+  /// Deserializes the argument with the given name.
+  /// A missing argument is deserialized from `null`, so it can be an `Option`.
+  pub fn arg<T: DeserializeOwned>(&self, name: &str) -> crate::Result<T> {
+    let value = self.args.get(name).cloned().unwrap_or(JsonValue::Null);
+    serde_json::from_value(value)
+      .map_err(|e| crate::Error::InvalidArgs(self.cmd.clone(), Some(name.to_string()), e))
+  }
+
+  /// Awaits the task and resolves or rejects the JS promise with its result.
+  /// The result is discarded if the command was called without the `promisified` helper.
+  pub async fn respond<
+    D: ApplicationDispatcherExt,
+    T: Serialize,
+    E: Serialize,
+    F: futures::Future<Output = Result<T, E>>,
+  >(
+    self,
+    webview_manager: &WebviewManager<D>,
+    task: F,
+  ) {
+    let result = task.await;
+    if let (Some(callback), Some(error)) = (self.callback, self.error) {
+      let callback_string = match format_callback_result(result, callback, &error) {
+        Ok(callback_string) => callback_string,
+        Err(e) => format_callback(error, e.to_string()),
+      };
+      if let Ok(dispatcher) = webview_manager.current_webview() {
+        dispatcher.eval(&callback_string);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::InvokeMessage;
+  use serde::Deserialize;
+
+  #[derive(Debug, Deserialize, PartialEq)]
+  struct Body {
+    id: u32,
+  }
+
+  #[test]
+  fn parses_command_arguments() {
+    let message = InvokeMessage::parse(
+      r#"{"cmd":"perform_request","endpoint":"/items","body":{"id":3},"callback":"_1","error":"_2"}"#,
+    )
+    .unwrap();
+    assert_eq!(message.command(), "perform_request");
+    assert_eq!(message.arg::<String>("endpoint").unwrap(), "/items");
+    assert_eq!(message.arg::<Body>("body").unwrap(), Body { id: 3 });
+    assert_eq!(message.arg::<Option<String>>("missing").unwrap(), None);
+    match message.arg::<u32>("endpoint") {
+      Err(crate::Error::InvalidArgs(command, arg, _)) => {
+        assert_eq!(command, "perform_request");
+        assert_eq!(arg.as_deref(), Some("endpoint"));
+      }
+      _ => panic!("expected an InvalidArgs error"),
+    }
+  }
+
+  #[test]
+  fn fails_without_command_name() {
+    assert!(matches!(
+      InvokeMessage::parse(r#"{"module":"Window"}"#),
+      Err(crate::Error::Json(_))
+    ));
+  }
+}
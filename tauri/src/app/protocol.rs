@@ -0,0 +1,304 @@
+use crate::{UriSchemeProtocol, UriSchemeResponse};
+
+use tauri_api::{
+  assets::{AssetFetch, Assets},
+  html::{csp_hash, csp_with_hashes, inject_csp, inline_scripts, inline_styles},
+};
+
+use std::{
+  io::Read,
+  path::{Component, Path, PathBuf},
+  sync::Arc,
+};
+
+/// The URI scheme of the built-in protocol serving the app assets.
+pub(crate) const ASSET_URI_SCHEME: &str = "tauri";
+
+/// The URL of the app entry, served by the built-in protocol.
+pub(crate) fn app_url() -> String {
+  format!("{}://localhost", ASSET_URI_SCHEME)
+}
+
+/// The response of an asset, with the CSP injected as a `<meta>` tag if it's an HTML document.
+fn asset_response(body: Vec<u8>, path: &str, csp: Option<&str>) -> UriSchemeResponse {
+  let mime_type = mime_type(path);
+  let body = match csp {
+    Some(csp) if mime_type == "text/html" => {
+      inject_csp(&String::from_utf8_lossy(&body), csp).into_bytes()
+    }
+    _ => body,
+  };
+  UriSchemeResponse::new(body, mime_type)
+}
+
+/// The built-in protocol, answering the requests straight from the embedded assets.
+pub(crate) fn asset_protocol(assets: &'static Assets, csp: Option<String>) -> UriSchemeProtocol {
+  Arc::new(move |uri| {
+    let path = asset_path(uri);
+    let (mut asset, _) = assets
+      .get(&Assets::format_key(&path), AssetFetch::Decompress)
+      .ok_or_else(|| crate::Error::AssetNotFound(path.clone()))?;
+    let mut body = Vec::new();
+    asset.read_to_end(&mut body)?;
+    Ok(asset_response(body, &path, csp.as_deref()))
+  })
+}
+
+/// The CSP allowing the inline scripts and styles of the HTML document by their hashes.
+fn document_csp(csp: &str, html: &[u8]) -> String {
+  let html = String::from_utf8_lossy(html);
+  let script_hashes: Vec<String> = inline_scripts(&html)
+    .iter()
+    .map(|script| csp_hash(script))
+    .collect();
+  let style_hashes: Vec<String> = inline_styles(&html)
+    .iter()
+    .map(|style| csp_hash(style))
+    .collect();
+  csp_with_hashes(csp, &script_hashes, &style_hashes)
+}
+
+/// The built-in protocol in dev mode, answering the requests from the files of the given directory, read on each request.
+///
+/// The inline scripts and styles of the HTML documents are hashed when they're served, since they can change.
+pub(crate) fn dir_protocol(dir: PathBuf, csp: Option<String>) -> UriSchemeProtocol {
+  Arc::new(move |uri| {
+    let path = asset_path(uri);
+    // the requests can't reach files outside of the directory
+    let is_relative = Path::new(&path)
+      .components()
+      .all(|component| matches!(component, Component::Normal(_)));
+    let body = if is_relative {
+      std::fs::read(dir.join(&path)).ok()
+    } else {
+      None
+    };
+    body
+      .map(|body| {
+        let csp = match &csp {
+          Some(csp) if mime_type(&path) == "text/html" => Some(document_csp(csp, &body)),
+          _ => None,
+        };
+        asset_response(body, &path, csp.as_deref())
+      })
+      .ok_or(crate::Error::AssetNotFound(path))
+  })
+}
+
+/// The decoded asset path of the URI, without its scheme, host, query and fragment.
+/// The root path is mapped to `index.html`.
+fn asset_path(uri: &str) -> String {
+  let path = uri.splitn(2, "://").nth(1).unwrap_or(uri);
+  let path = path.split(|c| c == '?' || c == '#').next().unwrap_or("");
+  let path = path.strip_prefix("localhost").unwrap_or(path);
+  let path = percent_decode(path.trim_start_matches('/'));
+  if path.is_empty() {
+    "index.html".to_string()
+  } else {
+    path
+  }
+}
+
+/// Decodes the percent-encoded bytes of the URI path, e.g. `%20` to a space.
+/// Invalid escapes are kept as is.
+fn percent_decode(path: &str) -> String {
+  let bytes = path.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let byte = match bytes[i] {
+      b'%' => path
+        .get(i + 1..i + 3)
+        .filter(|hex| hex.bytes().all(|c| c.is_ascii_hexdigit()))
+        .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+      _ => None,
+    };
+    match byte {
+      Some(byte) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      None => {
+        decoded.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The MIME type of the file, guessed from its extension.
+pub(crate) fn mime_type(path: &str) -> &'static str {
+  let extension = Path::new(path)
+    .extension()
+    .map(|extension| extension.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+  match extension.as_str() {
+    "html" | "htm" => "text/html",
+    "js" | "mjs" => "text/javascript",
+    "css" => "text/css",
+    "json" | "map" => "application/json",
+    "txt" => "text/plain",
+    "csv" => "text/csv",
+    "xml" => "application/xml",
+    "wasm" => "application/wasm",
+    "svg" => "image/svg+xml",
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "webp" => "image/webp",
+    "ico" => "image/vnd.microsoft.icon",
+    "woff" => "font/woff",
+    "woff2" => "font/woff2",
+    "ttf" => "font/ttf",
+    "otf" => "font/otf",
+    "mp3" => "audio/mpeg",
+    "wav" => "audio/wav",
+    "mp4" => "video/mp4",
+    "webm" => "video/webm",
+    "pdf" => "application/pdf",
+    _ => "application/octet-stream",
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{asset_path, dir_protocol, mime_type};
+  use crate::{
+    flavors::Mock,
+    test::{run, TauriContext},
+    AppBuilder, UriSchemeResponse,
+  };
+  use tauri_api::html::{csp_hash, csp_with_hashes, inject_csp};
+
+  #[test]
+  fn resolves_asset_paths() {
+    assert_eq!(asset_path("tauri://localhost"), "index.html");
+    assert_eq!(asset_path("tauri://localhost/"), "index.html");
+    assert_eq!(
+      asset_path("tauri://localhost/js/app.js?v=1#top"),
+      "js/app.js"
+    );
+    assert_eq!(asset_path("tauri://style.css"), "style.css");
+    assert_eq!(
+      asset_path("tauri://localhost/img/my%20photo%C3%A9.png"),
+      "img/my photoé.png"
+    );
+    assert_eq!(asset_path("tauri://localhost/100%25%zz%+1"), "100%%zz%+1");
+    assert_eq!(mime_type("js/app.js"), "text/javascript");
+    assert_eq!(mime_type("index.HTML"), "text/html");
+    assert_eq!(mime_type("font.woff2"), "font/woff2");
+    assert_eq!(mime_type("LICENSE"), "application/octet-stream");
+  }
+
+  #[test]
+  fn serves_dev_dir() {
+    let dir = std::env::temp_dir().join(format!("tauri-dev-dir-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join("js")).unwrap();
+    std::fs::write(
+      dir.join("index.html"),
+      "<script src=\"js/app.js\"></script>",
+    )
+    .unwrap();
+    std::fs::write(dir.join("js/app.js"), "console.log(1)").unwrap();
+    let protocol = dir_protocol(dir.clone(), None);
+
+    let response = protocol("tauri://localhost/js/app.js").unwrap();
+    assert_eq!(response.body, b"console.log(1)");
+    assert_eq!(response.mime_type, "text/javascript");
+    // the files are read on each request
+    std::fs::write(dir.join("js/app.js"), "console.log(2)").unwrap();
+    assert_eq!(
+      protocol("tauri://localhost/js/app.js").unwrap().body,
+      b"console.log(2)"
+    );
+    assert_eq!(
+      protocol("tauri://localhost").unwrap().mime_type,
+      "text/html"
+    );
+
+    assert!(matches!(
+      protocol("tauri://localhost/js/../../secret.txt"),
+      Err(crate::Error::AssetNotFound(_))
+    ));
+    // the path is decoded before its components are checked
+    assert!(matches!(
+      protocol("tauri://localhost/js/%2e%2e/%2E%2E/secret.txt"),
+      Err(crate::Error::AssetNotFound(_))
+    ));
+    std::fs::write(dir.join("js/my app.js"), "console.log(3)").unwrap();
+    assert_eq!(
+      protocol("tauri://localhost/js/my%20app.js").unwrap().body,
+      b"console.log(3)"
+    );
+    assert!(matches!(
+      protocol("tauri://localhost/missing.css"),
+      Err(crate::Error::AssetNotFound(_))
+    ));
+
+    // the CSP is only injected in the HTML documents
+    let protocol = dir_protocol(dir.clone(), Some("default-src 'self'".to_string()));
+    assert_eq!(
+      protocol("tauri://localhost").unwrap().body,
+      br#"<meta http-equiv="Content-Security-Policy" content="default-src 'self'"><script src="js/app.js"></script>"#.to_vec()
+    );
+    assert_eq!(
+      protocol("tauri://localhost/js/app.js").unwrap().body,
+      b"console.log(2)"
+    );
+    // the inline scripts are hashed when the document is served
+    std::fs::write(dir.join("index.html"), "<script>console.log(0)</script>").unwrap();
+    let csp = csp_with_hashes("default-src 'self'", &[csp_hash("console.log(0)")], &[]);
+    assert_eq!(
+      String::from_utf8(protocol("tauri://localhost").unwrap().body).unwrap(),
+      inject_csp("<script>console.log(0)</script>", &csp)
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn serves_uri_scheme_protocols() {
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().register_uri_scheme_protocol("greeting", |uri| {
+        Ok(UriSchemeResponse::new(
+          uri.replace("greeting://", "hello ").into_bytes(),
+          "text/plain",
+        ))
+      }),
+    );
+
+    let response = handle.request(0, "greeting://tauri").unwrap();
+    assert_eq!(response.body, b"hello tauri");
+    assert_eq!(response.mime_type, "text/plain");
+
+    let response = handle.request(0, "tauri://localhost/").unwrap();
+    assert_eq!(response.mime_type, "text/html");
+    // the fixture CSP is injected in the index.html
+    let html = String::from_utf8(response.body).unwrap();
+    assert_eq!(
+      html,
+      tauri_api::html::inject_csp(
+        include_str!("../../test/fixture/dist/index.html"),
+        "default-src blob: data: filesystem: ws: http: https: 'unsafe-eval' 'unsafe-inline'"
+      )
+    );
+
+    assert!(matches!(
+      handle.request(0, "tauri://localhost/missing.js"),
+      Err(crate::Error::AssetNotFound(_))
+    ));
+  }
+
+  #[test]
+  fn fails_on_duplicated_uri_schemes() {
+    let result = AppBuilder::<Mock, TauriContext>::new()
+      .register_uri_scheme_protocol("tauri", |_| {
+        Ok(UriSchemeResponse::new(Vec::new(), "text/plain"))
+      })
+      .build();
+    match result {
+      Err(crate::Error::UriSchemeAlreadyRegistered(uri_scheme)) => assert_eq!(uri_scheme, "tauri"),
+      _ => panic!("expected a UriSchemeAlreadyRegistered error"),
+    }
+  }
+}
@@ -0,0 +1,341 @@
+use super::{ipc_scope::INVOKE_HANDLER_API, App, InvokeMessage, WebviewManager};
+use crate::{api::rpc::format_ipc_message, ApplicationDispatcherExt, ApplicationExt};
+
+use futures::future::BoxFuture;
+use serde_json::Value as JsonValue;
+
+use std::collections::HashMap;
+
+/// The prefix of the commands handled by plugins, e.g. `plugin:sql|execute`.
+const PLUGIN_COMMAND_PREFIX: &str = "plugin:";
+
+type CommandHandler<D> =
+  dyn Fn(WebviewManager<D>, InvokeMessage) -> BoxFuture<'static, crate::Result<()>> + Send + Sync;
+
+/// A command that JS can invoke by name, usually generated with [`generate_handler!`](crate::generate_handler).
+pub struct Command<D: ApplicationDispatcherExt> {
+  name: String,
+  handler: Box<CommandHandler<D>>,
+}
+
+impl<D: ApplicationDispatcherExt> Command<D> {
+  /// Creates a command with the given name and handler.
+  pub fn new<
+    T: futures::Future<Output = crate::Result<()>> + Send + 'static,
+    F: Fn(WebviewManager<D>, InvokeMessage) -> T + Send + Sync + 'static,
+  >(
+    name: impl Into<String>,
+    handler: F,
+  ) -> Self {
+    Self {
+      name: name.into(),
+      handler: Box::new(move |webview_manager, message| {
+        Box::pin(handler(webview_manager, message))
+      }),
+    }
+  }
+}
+
+/// The commands of the app, keyed by name.
+pub(crate) struct Router<D: ApplicationDispatcherExt>(HashMap<String, Box<CommandHandler<D>>>);
+
+impl<D: ApplicationDispatcherExt> Router<D> {
+  /// Indexes the commands by name, failing if a name is registered twice.
+  pub(crate) fn new(commands: Vec<Command<D>>) -> crate::Result<Self> {
+    let mut router = HashMap::new();
+    for command in commands {
+      if router.contains_key(&command.name) {
+        return Err(crate::Error::CommandAlreadyRegistered(command.name));
+      }
+      router.insert(command.name, command.handler);
+    }
+    Ok(Self(router))
+  }
+}
+
+/// The target of an invoke message.
+#[derive(Debug, PartialEq)]
+enum Route<'a> {
+  /// A tauri API module, e.g. `{ module: 'Window', message: { cmd: 'setTitle' } }`.
+  Endpoint(&'a str),
+  /// A plugin command, e.g. `{ cmd: 'plugin:sql|execute' }`.
+  Plugin { plugin: &'a str, command: &'a str },
+  /// An app command, e.g. `{ cmd: 'my_command' }`.
+  Command(&'a str),
+  /// A payload without a command name, left to the app invoke handler.
+  Unnamed,
+}
+
+/// Routes on the command name, so an app command taking a `module` argument isn't mistaken for an API call.
+/// Only a payload without a command name is an API endpoint call.
+fn route(payload: &JsonValue) -> Route<'_> {
+  match payload.get("cmd").and_then(|cmd| cmd.as_str()) {
+    Some(cmd) if cmd.starts_with(PLUGIN_COMMAND_PREFIX) => {
+      let mut parts = cmd[PLUGIN_COMMAND_PREFIX.len()..].splitn(2, '|');
+      let plugin = parts.next().unwrap_or_default();
+      let command = parts.next().unwrap_or_default();
+      Route::Plugin { plugin, command }
+    }
+    Some(cmd) => Route::Command(cmd),
+    None => match payload.get("module") {
+      Some(module) => Route::Endpoint(module.as_str().unwrap_or_default()),
+      None => Route::Unnamed,
+    },
+  }
+}
+
+impl<A: ApplicationExt + 'static> App<A> {
+  /// Routes the invoke message to the API endpoints, the plugin or the command it names.
+  ///
+  /// Messages that don't name a registered command are passed to the invoke handler.
+  ///
+  /// The message is rejected if the calling page, loaded from the given URL, can't invoke its target.
+  /// The API modules, the plugins and the invoke handler are remote API, the app commands aren't.
+  pub(crate) async fn route(
+    &self,
+    webview_manager: &WebviewManager<A::Dispatcher>,
+    url: Option<&str>,
+    arg: &str,
+  ) -> crate::Result<()> {
+    let mut payload: JsonValue = serde_json::from_str(arg)?;
+    let route = route(&payload);
+    let remote_api = match route {
+      Route::Endpoint(module) => Some(module.to_string()),
+      Route::Plugin { plugin, .. } => Some(format!("{}{}", PLUGIN_COMMAND_PREFIX, plugin)),
+      Route::Command(command) if self.router.0.contains_key(command) => None,
+      // the other messages are passed to the invoke handler, or fail
+      _ if self.invoke_handler.is_some() => Some(INVOKE_HANDLER_API.to_string()),
+      _ => None,
+    };
+    let webview = webview_manager.current_webview()?;
+    if let Err(e) = webview.check_ipc_access(url, remote_api.as_deref()) {
+      // reject the promise of the blocked call
+      return match payload.get("error").and_then(JsonValue::as_str) {
+        Some(error) => {
+          webview.eval(&format_ipc_message(
+            Some(error),
+            false,
+            e.to_string().into(),
+          ));
+          Ok(())
+        }
+        None => Err(e),
+      };
+    }
+    match route {
+      Route::Endpoint(_) => crate::endpoints::handle(webview_manager, arg, &self.context).await,
+      Route::Plugin { plugin, command } => {
+        let plugin = plugin.to_string();
+        let command = command.to_string();
+        // the plugin receives the command name without its namespace
+        payload["cmd"] = JsonValue::String(command.clone());
+        crate::plugin::extend_api(
+          &self.plugins,
+          webview_manager,
+          &plugin,
+          &payload.to_string(),
+        )
+        .await
+        .map_err(|e| match e {
+          crate::Error::UnknownApi(_) => {
+            crate::Error::UnknownCommand(format!("{}{}|{}", PLUGIN_COMMAND_PREFIX, plugin, command))
+          }
+          e => e,
+        })
+      }
+      Route::Command(command) if self.router.0.contains_key(command) => {
+        let handler = &self.router.0[command];
+        let message = InvokeMessage::parse(arg)?;
+        handler(webview_manager.clone(), message).await
+      }
+      route => {
+        if let Some(ref invoke_handler) = self.invoke_handler {
+          invoke_handler(webview_manager.clone(), arg.to_string()).await
+        } else if let Route::Command(command) = route {
+          Err(crate::Error::UnknownCommand(command.to_string()))
+        } else {
+          Err(crate::Error::UnknownApi(None))
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{route, Route};
+  use crate::{
+    api::config::{WindowConfig, WindowUrl},
+    flavors::Mock,
+    test::{run, wait_for_script, TauriContext},
+    AppBuilder, Command, InvokeMessage,
+  };
+
+  #[test]
+  fn routes_by_command_name() {
+    let payload = serde_json::json!({ "module": "Window", "message": { "cmd": "setTitle" } });
+    assert_eq!(route(&payload), Route::Endpoint("Window"));
+    let payload = serde_json::json!({ "cmd": "plugin:sql|execute", "query": "" });
+    assert_eq!(
+      route(&payload),
+      Route::Plugin {
+        plugin: "sql",
+        command: "execute"
+      }
+    );
+    let payload = serde_json::json!({ "cmd": "plugin:sql" });
+    assert_eq!(
+      route(&payload),
+      Route::Plugin {
+        plugin: "sql",
+        command: ""
+      }
+    );
+    let payload = serde_json::json!({ "cmd": "greet", "name": "tauri" });
+    assert_eq!(route(&payload), Route::Command("greet"));
+    let payload = serde_json::json!({ "cmd": "install", "module": "Window" });
+    assert_eq!(route(&payload), Route::Command("install"));
+    assert_eq!(route(&serde_json::json!({ "title": "" })), Route::Unnamed);
+  }
+
+  #[test]
+  fn fails_on_duplicated_commands() {
+    let result = AppBuilder::<Mock, TauriContext>::new()
+      .commands(vec![
+        Command::new("greet", |_, _| async { Ok(()) }),
+        Command::new("greet", |_, _| async { Ok(()) }),
+      ])
+      .build();
+    match result {
+      Err(crate::Error::CommandAlreadyRegistered(name)) => assert_eq!(name, "greet"),
+      _ => panic!("expected a CommandAlreadyRegistered error"),
+    }
+  }
+
+  #[test]
+  fn reports_command_errors() {
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().commands(vec![Command::new(
+        "greet",
+        |webview_manager, message: InvokeMessage| async move {
+          let name: String = message.arg("name")?;
+          webview_manager
+            .current_webview()?
+            .eval(&format!("greeted {}", name));
+          Ok(())
+        },
+      )]),
+    );
+
+    handle
+      .invoke(0, r#"{"cmd":"greet","name":"tauri"}"#)
+      .unwrap();
+    assert!(wait_for_script(&handle, 0, "greeted tauri"));
+
+    handle.invoke(0, r#"{"cmd":"greet","name":1}"#).unwrap();
+    assert!(wait_for_script(
+      &handle,
+      0,
+      "invalid argument `name` for command `greet`"
+    ));
+
+    handle.invoke(0, r#"{"cmd":"farewell"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "unknown command `farewell`"));
+
+    handle.invoke(0, r#"{"cmd":"plugin:missing|run"}"#).unwrap();
+    assert!(wait_for_script(
+      &handle,
+      0,
+      "unknown command `plugin:missing|run`"
+    ));
+  }
+
+  #[test]
+  fn checks_the_origin_of_invokes() {
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new()
+        .commands(vec![Command::new(
+          "greet",
+          |webview_manager, _| async move {
+            webview_manager.current_webview()?.eval("greeted");
+            Ok(())
+          },
+        )])
+        .invoke_handler(|webview_manager, _| async move {
+          webview_manager.current_webview()?.eval("handled");
+          Ok(())
+        })
+        .setup(|webview_manager| async move {
+          webview_manager
+            .create_window(WindowConfig {
+              label: "remote".to_string(),
+              url: WindowUrl::Custom("https://tauri.studio/docs".to_string()),
+              trusted_origins: vec!["https://tauri.studio".to_string()],
+              remote_api: vec!["Window".to_string()],
+              ..Default::default()
+            })
+            .unwrap();
+          webview_manager.current_webview().unwrap().eval("created");
+        }),
+    );
+    handle.invoke(0, r#"{"cmd":"__initialized"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "created"));
+
+    // the trusted remote origin can call the commands and the allowed API modules
+    handle.invoke(1, r#"{"cmd":"greet"}"#).unwrap();
+    assert!(wait_for_script(&handle, 1, "greeted"));
+    handle
+      .invoke(
+        1,
+        r#"{"module":"Fs","message":{"cmd":"readDir","path":""},"callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      1,
+      "the `Fs` API can't be called from the remote origin `https://tauri.studio`"
+    ));
+
+    // the plugins and the invoke handler must be allowed too
+    handle
+      .invoke(
+        1,
+        r#"{"cmd":"plugin:sql|execute","callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      1,
+      "the `plugin:sql` API can't be called from the remote origin `https://tauri.studio`"
+    ));
+    handle
+      .invoke(
+        1,
+        r#"{"cmd":"other","callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      1,
+      "the `InvokeHandler` API can't be called from the remote origin `https://tauri.studio`"
+    ));
+    handle.invoke(0, r#"{"cmd":"other"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "handled"));
+
+    // the app content is trusted until the webview navigates away from it
+    handle.invoke(0, r#"{"cmd":"greet"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "greeted"));
+    handle.navigate(0, "https://example.com/");
+    handle
+      .invoke(
+        0,
+        r#"{"cmd":"greet","callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      0,
+      "IPC calls from the `https://example.com` origin are not allowed"
+    ));
+  }
+}
@@ -1,13 +1,23 @@
-#[cfg(dev)]
-use std::io::Read;
-use std::{collections::HashMap, sync::Arc};
-
-#[cfg(dev)]
-use crate::api::assets::{AssetFetch, Assets};
-
-use crate::{api::config::WindowUrl, ApplicationExt, WebviewBuilderExt};
-
-use super::{App, WebviewDispatcher, WebviewManager};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+
+use crate::{
+  api::{
+    config::{WindowConfig, WindowUrl},
+    rpc::{format_ipc_message, format_js_string},
+  },
+  webview::WindowBuilder,
+  ApplicationExt, WebviewBuilderExt, WindowBuilderExt, WindowEvent,
+};
+
+use super::{
+  ipc_scope::IpcScope,
+  protocol,
+  single_instance::{self, InstanceListener, InstanceLock, SecondInstance, SECOND_INSTANCE_EVENT},
+  webview_manager::{window_labels_script, WebviewFactory, WindowRegistry},
+  App, WebviewDispatcher, WebviewManager,
+};
 #[cfg(embedded_server)]
 use crate::api::tcp::{get_available_port, port_is_available};
 use crate::app::Context;
@@ -19,12 +29,46 @@ enum Content<T> {
 }
 
 /// Main entry point for running the Webview
-pub(crate) fn run<A: ApplicationExt + 'static>(application: App<A>) -> crate::Result<()> {
+pub(crate) fn run<A: ApplicationExt + 'static>(mut application: App<A>) -> crate::Result<()> {
+  let config = &application.context.config;
+  let instance_listener = if application
+    .single_instance
+    .unwrap_or(config.tauri.single_instance)
+  {
+    match single_instance::acquire(&config.tauri.bundle.identifier)? {
+      Some(instance_listener) => Some(instance_listener),
+      // the args were sent to the running instance
+      None => return Ok(()),
+    }
+  } else {
+    None
+  };
+
+  let plugins = application.plugins.clone();
   let plugin_config = application.context.config.plugins.clone();
-  crate::async_runtime::block_on(async move {
-    crate::plugin::initialize(A::plugin_store(), plugin_config).await
+  crate::async_runtime::wait(&application.runtime, async move {
+    crate::plugin::initialize(&plugins, plugin_config).await
   })?;
 
+  let plugins = application.plugins.clone();
+  let plugin_initialization_script = crate::async_runtime::wait(&application.runtime, async move {
+    crate::plugin::initialization_script(&plugins).await
+  });
+  let csp = application.context.csp();
+  #[cfg(embedded_server)]
+  let server_csp = csp.clone();
+  // in dev mode, the assets are read from the devPath directory so edits show up without rebuilding
+  let asset_protocol = match application.context.dev_dir() {
+    Some(dev_dir) => protocol::dir_protocol(
+      dev_dir,
+      application.context.config.tauri.security.csp.clone(),
+    ),
+    None => protocol::asset_protocol(application.context.assets, csp),
+  };
+  application
+    .uri_scheme_protocols
+    .insert(protocol::ASSET_URI_SCHEME.to_string(), asset_protocol);
+
   // setup the content using the config struct depending on the compile target
   let main_content = setup_content(&application.context)?;
 
@@ -39,11 +83,16 @@ pub(crate) fn run<A: ApplicationExt + 'static>(application: App<A>) -> crate::Re
 
     // spawn the embedded server on our server url
     #[cfg(embedded_server)]
-    spawn_server(server_url, &application.context);
+    spawn_server(server_url, &application.context, server_csp);
   }
 
   // build the webview
-  let webview_application = build_webview(application, main_content)?;
+  let webview_application = build_webview(
+    application,
+    main_content,
+    plugin_initialization_script,
+    instance_listener,
+  )?;
 
   // spin up the updater process
   #[cfg(feature = "updater")]
@@ -85,22 +134,7 @@ fn setup_content(context: &Context) -> crate::Result<Content<String>> {
     }
     Ok(Content::Url(config.build.dev_path.clone()))
   } else {
-    Ok(Content::Html(format!(
-      "data:text/html;base64,{}",
-      base64::encode(
-        context
-          .assets
-          .get(&Assets::format_key("index.html"), AssetFetch::Decompress)
-          .ok_or_else(|| crate::Error::AssetNotFound("index.html".to_string()))
-          .and_then(|(read, _)| {
-            read
-              .bytes()
-              .collect::<Result<Vec<u8>, _>>()
-              .map_err(Into::into)
-          })
-          .expect("Unable to find `index.html` under your devPath folder")
-      )
-    )))
+    Ok(Content::Url(super::protocol::app_url()))
   }
 }
 
@@ -147,7 +181,7 @@ fn setup_server_url(port: String, context: &Context) -> String {
 
 // spawn the embedded server
 #[cfg(embedded_server)]
-fn spawn_server(server_url: String, context: &Context) {
+fn spawn_server(server_url: String, context: &Context, csp: Option<String>) {
   let assets = context.assets;
   let public_path = context.config.tauri.embedded_server.public_path.clone();
   std::thread::spawn(move || {
@@ -171,7 +205,7 @@ fn spawn_server(server_url: String, context: &Context) {
       }
       .to_string();
       request
-        .respond(crate::server::asset_response(&url, assets))
+        .respond(crate::server::asset_response(&url, assets, csp.as_deref()))
         .expect("unable to setup response");
     }
   });
@@ -197,13 +231,12 @@ pub fn event_initialization_script() -> String {
   return format!(
     "
       window['{queue}'] = [];
-      window['{fn}'] = function (payload, salt, ignoreQueue) {{
+      window['{fn}'] = function (event, ignoreQueue) {{
+      const payload = event.payload
+      const salt = event.salt
       const listeners = (window['{listeners}'] && window['{listeners}'][payload.type]) || []
       if (!ignoreQueue && listeners.length === 0) {{
-        window['{queue}'].push({{
-          payload: payload,
-          salt: salt
-        }})
+        window['{queue}'].push(event)
       }}
 
       if (listeners.length > 0) {{
@@ -223,8 +256,26 @@ pub fn event_initialization_script() -> String {
         }})
       }}
     }}
+
+      window['{listen}'] = function (listener) {{
+      if (window['{listeners}'] === void 0) {{
+        window['{listeners}'] = {{}}
+      }}
+      if (window['{listeners}'][listener.event] === void 0) {{
+        window['{listeners}'][listener.event] = []
+      }}
+      window['{listeners}'][listener.event].push({{
+        handler: window[listener.handler],
+        once: listener.once
+      }})
+
+      for (let i = 0; i < window['{queue}'].length; i++) {{
+        window['{fn}'](window['{queue}'][i], true)
+      }}
+    }}
     ",
     fn = crate::event::emit_function_name(),
+    listen = crate::event::listen_function_name(),
     queue = crate::event::event_queue_object_name(),
     listeners = crate::event::event_listeners_object_name()
   );
@@ -234,6 +285,8 @@ pub fn event_initialization_script() -> String {
 fn build_webview<A: ApplicationExt + 'static>(
   application: App<A>,
   content: Content<String>,
+  plugin_initialization_script: String,
+  instance_listener: Option<InstanceListener>,
 ) -> crate::Result<A> {
   // TODO let debug = cfg!(debug_assertions);
   let content_url = match content {
@@ -243,6 +296,7 @@ fn build_webview<A: ApplicationExt + 'static>(
 
   let initialization_script = format!(
     r#"
+      {ipc_initialization_script}
       {tauri_initialization_script}
       {event_initialization_script}
       if (window.__TAURI_INVOKE_HANDLER__) {{
@@ -254,72 +308,205 @@ fn build_webview<A: ApplicationExt + 'static>(
       }}
       {plugin_initialization_script}
     "#,
+    ipc_initialization_script = tauri_api::rpc::IPC_INITIALIZATION_SCRIPT,
     tauri_initialization_script = application.context.tauri_script,
     event_initialization_script = event_initialization_script(),
-    plugin_initialization_script =
-      crate::async_runtime::block_on(crate::plugin::initialization_script(A::plugin_store()))
+    plugin_initialization_script = plugin_initialization_script
   );
 
+  let window_configs = application.context.config.tauri.windows.clone();
+  let main_window_label = window_configs
+    .first()
+    .map(|window_config| window_config.label.clone())
+    .unwrap_or_default();
+  let state = application.state.clone();
+  let system_tray = application.system_tray.clone();
   let application = Arc::new(application);
 
   let mut webview_application = A::new()?;
+  let windows = Arc::new(WindowRegistry::new(
+    Box::new(AppWebviewFactory {
+      application: application.clone(),
+      content_url,
+      initialization_script,
+      instance_lock: instance_listener.as_ref().map(InstanceListener::lock),
+    }),
+    webview_application.app_dispatcher(),
+    application.exit_on_last_window_closed,
+    application.runtime.clone(),
+    application.events.clone(),
+    application.stores.clone(),
+  ));
 
   let mut window_refs = Vec::new();
-  let mut dispatchers = HashMap::new();
 
-  for window_config in application.context.config.tauri.windows.clone() {
-    let window = crate::webview::WindowBuilder::from(&window_config);
-    let window = webview_application.create_window(window.get())?;
+  for window_config in window_configs {
+    let window = windows.factory().build_window(&window_config);
+    let window = webview_application.create_window(window)?;
     let dispatcher = webview_application.dispatcher(&window);
-    dispatchers.insert(
+    windows.insert(
       window_config.label.to_string(),
-      WebviewDispatcher::new(dispatcher),
+      WebviewDispatcher::new(
+        dispatcher,
+        window_config.label.to_string(),
+        windows.events().clone(),
+        windows.factory().ipc_scope(&window_config),
+      ),
     );
     window_refs.push((window_config, window));
   }
 
   for (window_config, window) in window_refs {
-    let webview_manager = WebviewManager::new(dispatchers.clone(), window_config.label.to_string());
+    let webview_manager = WebviewManager::new(
+      windows.clone(),
+      state.clone(),
+      window_config.label.to_string(),
+    );
+    let (webview_builder, callbacks) = windows
+      .factory()
+      .build_webview(&webview_manager, &window_config);
+    webview_application.create_webview(
+      webview_builder,
+      window,
+      callbacks,
+      webview_manager.window_event_handler(),
+    )?;
+    windows.factory().created(webview_manager);
+  }
+
+  let app_webview_manager = WebviewManager::new(windows, state, main_window_label);
+
+  if let Some(system_tray) = system_tray {
+    let webview_manager = app_webview_manager.clone();
+    webview_application.create_system_tray(
+      system_tray,
+      Box::new(move |event| super::event::emit_system_tray_event(&webview_manager, event)),
+    )?;
+  }
+
+  webview_application.set_global_shortcut_handler(app_webview_manager.global_shortcut_handler());
 
+  // without a webview to report it, the app is ready once it's built
+  if app_webview_manager.windows().is_empty() {
     let application = application.clone();
-    let content_url = content_url.to_string();
+    let webview_manager = app_webview_manager.clone();
+    application.runtime.spawn(async move {
+      application.run_setup(&webview_manager).await;
+      crate::plugin::ready(&application.plugins, &webview_manager).await;
+    });
+  }
+
+  if let Some(instance_listener) = instance_listener {
+    instance_listener
+      .listen(move |instance| on_second_instance(&application, &app_webview_manager, instance));
+  }
 
+  Ok(webview_application)
+}
+
+/// Focuses the app and emits the `second-instance` event with the args of the launched instance.
+#[allow(unused_variables)]
+fn on_second_instance<A: ApplicationExt + 'static>(
+  application: &App<A>,
+  webview_manager: &WebviewManager<A::Dispatcher>,
+  instance: SecondInstance,
+) {
+  #[cfg(cli)]
+  let matches =
+    tauri_api::cli::get_matches_from(&application.context.config, instance.args.iter()).ok();
+  #[cfg(not(cli))]
+  let matches: Option<()> = None;
+  let payload = serde_json::json!({
+    "args": instance.args,
+    "cwd": instance.cwd,
+    "matches": matches,
+  });
+
+  let windows = webview_manager.windows();
+  let focused_window = webview_manager
+    .current_webview()
+    .ok()
+    .or_else(|| windows.values().next().cloned());
+  if let Some(dispatcher) = focused_window {
+    let _ = dispatcher.unminimize();
+    let _ = dispatcher.show();
+  }
+  let _ = webview_manager.emit_all(SECOND_INSTANCE_EVENT, Some(&payload));
+  webview_manager
+    .events()
+    .trigger(SECOND_INSTANCE_EVENT, None, Some(payload.to_string()));
+}
+
+/// Builds the webviews of the app windows, on startup and at runtime.
+struct AppWebviewFactory<A: ApplicationExt> {
+  application: Arc<App<A>>,
+  content_url: String,
+  initialization_script: String,
+  /// The single instance lock, released on exit.
+  instance_lock: Option<InstanceLock>,
+}
+
+impl<A: ApplicationExt> AppWebviewFactory<A> {
+  /// The URL the window loads when it's created.
+  fn webview_url(&self, window_config: &WindowConfig) -> String {
+    match &window_config.url {
+      WindowUrl::App(None) => self.content_url.to_string(),
+      WindowUrl::App(Some(path)) => app_page_url(&self.content_url, path),
+      WindowUrl::Custom(url) => url.to_string(),
+    }
+  }
+}
+
+impl<A: ApplicationExt + 'static> WebviewFactory<A::Dispatcher> for AppWebviewFactory<A> {
+  fn build_window(&self, window_config: &WindowConfig) -> A::WindowBuilder {
+    let mut window_builder = WindowBuilder::<A::WindowBuilder>::from(window_config).get();
+    if window_config.menu.is_none() {
+      if let Some(menu) = &self.application.menu {
+        window_builder = window_builder.menu(menu.clone());
+      }
+    }
+    window_builder
+  }
+
+  fn ipc_scope(&self, window_config: &WindowConfig) -> IpcScope {
+    IpcScope::new(
+      &self.content_url,
+      &self.webview_url(window_config),
+      self
+        .application
+        .uri_scheme_protocols
+        .keys()
+        .cloned()
+        .collect(),
+      window_config,
+    )
+  }
+
+  fn build_webview(
+    &self,
+    webview_manager: &WebviewManager<A::Dispatcher>,
+    window_config: &WindowConfig,
+  ) -> (A::WebviewBuilder, Vec<crate::Callback<A::Dispatcher>>) {
+    let application = self.application.clone();
     let webview_manager_ = webview_manager.clone();
     let tauri_invoke_handler = crate::Callback::<A::Dispatcher> {
       name: "__TAURI_INVOKE_HANDLER__".to_string(),
-      function: Box::new(move |_, _, arg| {
+      function: Box::new(move |_, url, _, arg| {
+        let url = url.map(ToString::to_string);
         let arg = arg.into_iter().next().unwrap_or_else(String::new);
         let application = application.clone();
         let webview_manager = webview_manager_.clone();
 
-        crate::async_runtime::spawn(async move {
+        application.runtime.clone().spawn(async move {
           if arg == r#"{"cmd":"__initialized"}"# {
             application.run_setup(&webview_manager).await;
-            crate::plugin::ready(A::plugin_store(), &webview_manager).await;
+            crate::plugin::ready(&application.plugins, &webview_manager).await;
           } else {
-            let mut endpoint_handle =
-              crate::endpoints::handle(&webview_manager, &arg, &application.context).await;
-            if let Err(crate::Error::UnknownApi(_)) = endpoint_handle {
-              let response = match application.run_invoke_handler(&webview_manager, &arg).await {
-                Ok(handled) => {
-                  if handled {
-                    Ok(())
-                  } else {
-                    endpoint_handle
-                  }
-                }
-                Err(e) => Err(e),
-              };
-              endpoint_handle = response;
-            }
-            if let Err(crate::Error::UnknownApi(_)) = endpoint_handle {
-              endpoint_handle =
-                crate::plugin::extend_api(A::plugin_store(), &webview_manager, &arg)
-                  .await
-                  .map(|_| ());
-            }
-            if let Err(handler_error_message) = endpoint_handle {
-              let handler_error_message = handler_error_message.to_string().replace("'", "\\'");
+            let response = application
+              .route(&webview_manager, url.as_deref(), &arg)
+              .await;
+            if let Err(handler_error_message) = response {
+              let handler_error_message = handler_error_message.to_string();
               if !handler_error_message.is_empty() {
                 if let Ok(dispatcher) = webview_manager.current_webview() {
                   dispatcher.eval(&get_api_error_message(&arg, handler_error_message));
@@ -332,56 +519,104 @@ fn build_webview<A: ApplicationExt + 'static>(
       }),
     };
 
-    let webview_url = match &window_config.url {
-      WindowUrl::App => content_url.to_string(),
-      WindowUrl::Custom(url) => url.to_string(),
-    };
+    let webview_url = self.webview_url(window_config);
 
-    webview_application.create_webview(
-      A::WebviewBuilder::new()
-        .url(webview_url)
-        .initialization_script(&initialization_script)
-        .initialization_script(&format!(
-          r#"
-              window.__TAURI__.windows = {window_labels_array}.map(function (label) {{ return {{ label: label }} }});
-              window.__TAURI__.currentWindow = {{ label: "{current_window_label}" }}
+    let mut window_labels: Vec<String> = webview_manager.windows().keys().cloned().collect();
+    if !window_labels.contains(&window_config.label) {
+      window_labels.push(window_config.label.to_string());
+    }
+    window_labels.sort();
+
+    let mut webview_builder = A::WebviewBuilder::new()
+      .url(webview_url)
+      .initialization_script(&self.initialization_script)
+      .initialization_script(&format!(
+        r#"
+              {window_labels_script}
+              window.__TAURI__.currentWindow = {{ label: {current_window_label} }}
             "#,
-          window_labels_array =
-            serde_json::to_string(&dispatchers.keys().collect::<Vec<&String>>()).unwrap(),
-          current_window_label = window_config.label,
-        )),
-      window,
-      vec![tauri_invoke_handler],
-    )?;
+        window_labels_script = window_labels_script(&window_labels),
+        current_window_label = format_js_string(&window_config.label),
+      ));
+    for (uri_scheme, protocol) in &self.application.uri_scheme_protocols {
+      webview_builder =
+        webview_builder.register_uri_scheme_protocol(uri_scheme.clone(), protocol.clone());
+    }
 
-    crate::async_runtime::spawn(async move {
-      crate::plugin::created(A::plugin_store(), &webview_manager).await
-    });
+    (webview_builder, vec![tauri_invoke_handler])
   }
 
-  Ok(webview_application)
+  fn created(&self, webview_manager: WebviewManager<A::Dispatcher>) {
+    let application = self.application.clone();
+    self
+      .application
+      .runtime
+      .spawn(async move { crate::plugin::created(&application.plugins, &webview_manager).await });
+  }
+
+  fn window_event(&self, webview_manager: &WebviewManager<A::Dispatcher>, event: &WindowEvent) {
+    self.application.run_on_window_event(webview_manager, event);
+  }
+
+  fn exit(
+    &self,
+    webview_manager: WebviewManager<A::Dispatcher>,
+  ) -> BoxFuture<'static, crate::Result<()>> {
+    let application = self.application.clone();
+    let instance_lock = self.instance_lock.clone();
+    Box::pin(async move {
+      application.run_on_exit(&webview_manager).await;
+      crate::plugin::exit(&application.plugins).await;
+      let result = application.stores.flush();
+      if let Some(instance_lock) = instance_lock {
+        instance_lock.release();
+      }
+      result
+    })
+  }
+}
+
+/// The URL of a page of the app, resolved like a link of the content page:
+/// a relative path against the directory of the content URL and an absolute path against its origin.
+fn app_page_url(content_url: &str, path: &str) -> String {
+  let base = content_url
+    .split(|c| c == '?' || c == '#')
+    .next()
+    .unwrap_or(content_url);
+  let authority_start = base.find("://").map(|i| i + 3).unwrap_or(0);
+  let (origin, base_path) = match base[authority_start..].find('/') {
+    Some(i) => base.split_at(authority_start + i),
+    None => (base, ""),
+  };
+  if path.starts_with('/') {
+    format!("{}{}", origin, path)
+  } else {
+    let dir = base_path
+      .rfind('/')
+      .map(|i| &base_path[..=i])
+      .unwrap_or("/");
+    format!("{}{}{}", origin, dir, path)
+  }
 }
 
 // Formats an invoke handler error message to print to console.error
 fn get_api_error_message(arg: &str, handler_error_message: String) -> String {
-  format!(
-    r#"console.error('failed to match a command for {}, {}')"#,
-    arg.replace("'", "\\'"),
-    handler_error_message
+  format_ipc_message(
+    None,
+    false,
+    format!(
+      "failed to match a command for {}, {}",
+      arg, handler_error_message
+    )
+    .into(),
   )
 }
 
 #[cfg(test)]
 mod test {
   use super::Content;
-  use crate::{Context, FromTauriContext};
+  use crate::{test::TauriContext, Context};
   use proptest::prelude::*;
-  #[cfg(dev)]
-  use std::io::Read;
-
-  #[derive(FromTauriContext)]
-  #[config_path = "test/fixture/src-tauri/tauri.conf.json"]
-  struct TauriContext;
 
   #[test]
   fn check_setup_content() {
@@ -398,36 +633,45 @@ mod test {
     {
       let config = &context.config;
       match res {
-        Ok(Content::Url(dp)) => assert_eq!(dp, config.build.dev_path),
-        Ok(Content::Html(s)) => {
-          assert_eq!(
-            s,
-            format!(
-              "data:text/html;base64,{}",
-              base64::encode(
-                context
-                  .assets
-                  .get(
-                    &crate::api::assets::Assets::format_key("index.html"),
-                    crate::api::assets::AssetFetch::Decompress
-                  )
-                  .ok_or_else(|| crate::Error::AssetNotFound("index.html".to_string()))
-                  .and_then(|(read, _)| {
-                    read
-                      .bytes()
-                      .collect::<Result<Vec<u8>, _>>()
-                      .map_err(Into::into)
-                  })
-                  .expect("Unable to find `index.html` under your dist folder")
-              )
-            )
-          );
+        Ok(Content::Url(url)) => {
+          if config.build.dev_path.starts_with("http") {
+            assert_eq!(url, config.build.dev_path)
+          } else {
+            assert_eq!(url, crate::app::protocol::app_url())
+          }
         }
         _ => panic!("setup content failed"),
       }
     }
   }
 
+  #[test]
+  fn resolves_app_page_urls() {
+    assert_eq!(
+      super::app_page_url("tauri://localhost", "settings.html"),
+      "tauri://localhost/settings.html"
+    );
+    assert_eq!(
+      super::app_page_url("http://localhost:4000/", "settings/index.html"),
+      "http://localhost:4000/settings/index.html"
+    );
+    assert_eq!(
+      super::app_page_url(
+        "http://localhost:4000/app/index.html?v=1#top",
+        "settings.html"
+      ),
+      "http://localhost:4000/app/settings.html"
+    );
+    assert_eq!(
+      super::app_page_url("http://localhost:4000/app/", "settings.html"),
+      "http://localhost:4000/app/settings.html"
+    );
+    assert_eq!(
+      super::app_page_url("http://localhost:4000/app/index.html", "/settings.html"),
+      "http://localhost:4000/settings.html"
+    );
+  }
+
   proptest! {
     #![proptest_config(ProptestConfig::with_cases(10000))]
     #[cfg(embedded_server)]
@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+
+use std::{
+  fs::File,
+  io::{Read, Write},
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+#[cfg(windows)]
+use std::net::{TcpListener as Listener, TcpStream as Stream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener as Listener, UnixStream as Stream};
+
+/// The event emitted when the app is launched while it's already running.
+pub(crate) const SECOND_INSTANCE_EVENT: &str = "second-instance";
+
+/// How long the running instance waits for the args of a launched instance,
+/// and how long a launched instance waits for the running instance to listen.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum size of the message sent by a launched instance.
+const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
+
+/// The args of an app instance launched while another instance was running.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SecondInstance {
+  /// The args of the second instance, starting with the binary path.
+  pub(crate) args: Vec<String>,
+  /// The working directory of the second instance.
+  pub(crate) cwd: String,
+}
+
+impl SecondInstance {
+  fn current() -> crate::Result<Self> {
+    Ok(Self {
+      args: std::env::args_os()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect(),
+      cwd: std::env::current_dir()?.display().to_string(),
+    })
+  }
+}
+
+/// The message sent by a launched instance, authenticated with the token of the running instance.
+#[derive(Serialize, Deserialize)]
+struct InstanceMessage {
+  token: String,
+  #[serde(flatten)]
+  instance: SecondInstance,
+}
+
+/// Listens to the instances launched while the app is running.
+pub(crate) struct InstanceListener {
+  listener: Listener,
+  token: String,
+  lock: InstanceLock,
+}
+
+impl InstanceListener {
+  /// The lock that makes the current process the running instance.
+  pub(crate) fn lock(&self) -> InstanceLock {
+    self.lock.clone()
+  }
+
+  /// Calls the handler with the args of each instance launched later, on a background thread.
+  /// Each connection is read on its own thread, so a client that doesn't send its args can't block the others.
+  /// Stops once the lock is released.
+  pub(crate) fn listen<F: Fn(SecondInstance) + Send + 'static>(self, handler: F) {
+    let handler = Arc::new(Mutex::new(handler));
+    std::thread::spawn(move || {
+      for stream in self.listener.incoming() {
+        if self.lock.is_released() {
+          break;
+        }
+        if let Ok(stream) = stream {
+          let handler = handler.clone();
+          let token = self.token.clone();
+          std::thread::spawn(move || {
+            if let Some(instance) = receive(stream, &token) {
+              (handler.lock().unwrap())(instance);
+            }
+          });
+        }
+      }
+    });
+  }
+}
+
+/// Reads the args sent by a launched instance, ignoring the messages without the token of the running instance.
+fn receive(stream: Stream, token: &str) -> Option<SecondInstance> {
+  stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+  let mut message = String::new();
+  stream
+    .take(MAX_MESSAGE_SIZE)
+    .read_to_string(&mut message)
+    .ok()?;
+  let message: InstanceMessage = serde_json::from_str(&message).ok()?;
+  if message.token == token {
+    Some(message.instance)
+  } else {
+    None
+  }
+}
+
+/// The lock that makes the current process the running instance of the app.
+#[derive(Clone)]
+pub(crate) struct InstanceLock {
+  path: PathBuf,
+  file: Arc<Mutex<Option<File>>>,
+}
+
+impl InstanceLock {
+  /// Removes the socket of the running instance and unlocks the lock file,
+  /// so the instance launched next becomes the running instance.
+  pub(crate) fn release(&self) {
+    let mut file = self.file.lock().unwrap();
+    if file.is_some() {
+      let _ = std::fs::remove_file(&self.path);
+      file.take();
+    }
+  }
+
+  fn is_released(&self) -> bool {
+    self.file.lock().unwrap().is_none()
+  }
+}
+
+/// Makes the current process the running instance of the app with the given identifier.
+///
+/// The running instance is the process holding the lock file, which the OS unlocks when the process exits.
+/// If another instance is already running, sends it the args and cwd of the current process and returns `None`.
+pub(crate) fn acquire(identifier: &str) -> crate::Result<Option<InstanceListener>> {
+  let address = socket_path(identifier)?;
+  let lock_file = match lock(&address.with_extension("lock"))? {
+    Some(lock_file) => lock_file,
+    None => {
+      send(&address)?;
+      return Ok(None);
+    }
+  };
+  // only the lock holder binds, so removing the socket of an instance that didn't exit cleanly is safe
+  let _ = std::fs::remove_file(&address);
+  let (listener, token) = bind(&address)?;
+  Ok(Some(InstanceListener {
+    listener,
+    token,
+    lock: InstanceLock {
+      path: address,
+      file: Arc::new(Mutex::new(Some(lock_file))),
+    },
+  }))
+}
+
+/// Sends the args and cwd of the current process to the running instance,
+/// waiting for it to listen if it's still starting.
+fn send(address: &Path) -> crate::Result<()> {
+  let message = InstanceMessage {
+    token: String::new(),
+    instance: SecondInstance::current()?,
+  };
+  let started = std::time::Instant::now();
+  loop {
+    match connect(address) {
+      Ok((mut stream, token)) => {
+        let message = InstanceMessage { token, ..message };
+        stream.write_all(serde_json::to_string(&message)?.as_bytes())?;
+        return Ok(());
+      }
+      Err(e) if started.elapsed() > TIMEOUT => return Err(e.into()),
+      Err(_) => std::thread::sleep(Duration::from_millis(50)),
+    }
+  }
+}
+
+/// The path of the socket the running instance listens on.
+/// On Windows, it's the file that stores the loopback port and the token of the running instance.
+fn socket_path(identifier: &str) -> crate::Result<PathBuf> {
+  let name = if identifier.is_empty() {
+    std::env::current_exe()?
+      .file_stem()
+      .map(|stem| stem.to_string_lossy().to_string())
+      .unwrap_or_else(|| "tauri-app".to_string())
+  } else {
+    identifier.to_string()
+  };
+  let dir = tauri_api::path::runtime_dir().unwrap_or_else(std::env::temp_dir);
+  let extension = if cfg!(windows) { "port" } else { "sock" };
+  Ok(dir.join(format!("{}.{}", name, extension)))
+}
+
+/// Locks the file exclusively, returning `None` if another process holds the lock.
+#[cfg(unix)]
+fn lock(path: &Path) -> crate::Result<Option<File>> {
+  use std::os::unix::io::AsRawFd;
+  let file = std::fs::OpenOptions::new()
+    .create(true)
+    .write(true)
+    .open(path)?;
+  // the lock is released when the file is closed, including when the process is killed
+  if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+    Ok(Some(file))
+  } else {
+    let e = std::io::Error::last_os_error();
+    match e.raw_os_error() {
+      Some(libc::EWOULDBLOCK) => Ok(None),
+      _ => Err(e.into()),
+    }
+  }
+}
+
+#[cfg(unix)]
+fn connect(path: &Path) -> std::io::Result<(Stream, String)> {
+  Stream::connect(path).map(|stream| (stream, String::new()))
+}
+
+/// Binds the socket, only accessible to the user running the app, so the instances don't need a token.
+#[cfg(unix)]
+fn bind(path: &Path) -> crate::Result<(Listener, String)> {
+  use std::os::unix::fs::PermissionsExt;
+  let listener = Listener::bind(path)?;
+  std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+  Ok((listener, String::new()))
+}
+
+/// Opens the file without sharing it, returning `None` if another process has it open.
+#[cfg(windows)]
+fn lock(path: &Path) -> crate::Result<Option<File>> {
+  use std::os::windows::fs::OpenOptionsExt;
+  /// The error returned when another process has the file open.
+  const ERROR_SHARING_VIOLATION: i32 = 32;
+  match std::fs::OpenOptions::new()
+    .create(true)
+    .write(true)
+    .share_mode(0)
+    .open(path)
+  {
+    Ok(file) => Ok(Some(file)),
+    Err(e) if e.raw_os_error() == Some(ERROR_SHARING_VIOLATION) => Ok(None),
+    Err(e) => Err(e.into()),
+  }
+}
+
+#[cfg(windows)]
+fn connect(path: &Path) -> std::io::Result<(Stream, String)> {
+  let contents = std::fs::read_to_string(path)?;
+  let mut lines = contents.lines();
+  let port: u16 = lines
+    .next()
+    .and_then(|port| port.trim().parse().ok())
+    .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid port file"))?;
+  let token = lines.next().unwrap_or_default().trim().to_string();
+  Stream::connect(("127.0.0.1", port)).map(|stream| (stream, token))
+}
+
+/// Binds a loopback port, that any local process can connect to,
+/// so the instances authenticate with a token stored in the directory of the user running the app.
+#[cfg(windows)]
+fn bind(path: &Path) -> crate::Result<(Listener, String)> {
+  let listener = Listener::bind(("127.0.0.1", 0))?;
+  let token = uuid::Uuid::new_v4().to_string();
+  std::fs::write(
+    path,
+    format!("{}\n{}", listener.local_addr()?.port(), token),
+  )?;
+  Ok((listener, token))
+}
+
+#[cfg(test)]
+mod test {
+  use super::{acquire, socket_path, SecondInstance};
+  use std::{sync::mpsc::channel, time::Duration};
+
+  #[test]
+  fn forwards_args_to_running_instance() {
+    let identifier = format!("tauri.single-instance.{}", uuid::Uuid::new_v4());
+    let listener = acquire(&identifier)
+      .unwrap()
+      .expect("the first instance should listen");
+    let lock = listener.lock();
+    let (tx, rx) = channel();
+    listener.listen(move |instance| tx.send(instance).unwrap());
+
+    assert!(acquire(&identifier).unwrap().is_none());
+    let instance = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(instance, SecondInstance::current().unwrap());
+    lock.release();
+  }
+
+  #[test]
+  fn releases_the_running_instance() {
+    let identifier = format!("tauri.single-instance.{}", uuid::Uuid::new_v4());
+    let listener = acquire(&identifier)
+      .unwrap()
+      .expect("the first instance should listen");
+    let lock = listener.lock();
+    listener.listen(|_| {});
+
+    lock.release();
+    assert!(!socket_path(&identifier).unwrap().exists());
+    let listener = acquire(&identifier)
+      .unwrap()
+      .expect("the next instance should listen once the lock is released");
+    listener.lock().release();
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn replaces_the_socket_of_a_crashed_instance() {
+    let identifier = format!("tauri.single-instance.{}", uuid::Uuid::new_v4());
+    let path = socket_path(&identifier).unwrap();
+    // the socket file of an instance that didn't exit cleanly refuses connections
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let listener = acquire(&identifier)
+      .unwrap()
+      .expect("the instance should replace the stale socket");
+    listener.lock().release();
+  }
+}
@@ -0,0 +1,117 @@
+use std::{
+  any::{Any, TypeId},
+  collections::HashMap,
+  ops::Deref,
+  sync::{Arc, Mutex},
+};
+
+/// A value managed by the app with [`AppBuilder::manage`](crate::AppBuilder::manage).
+pub struct State<T>(Arc<T>);
+
+impl<T> Clone for State<T> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+impl<T> Deref for State<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.0
+  }
+}
+
+/// The values managed by the app, keyed by their type.
+#[derive(Default)]
+pub(crate) struct StateManager(Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>);
+
+impl StateManager {
+  /// Manages the given value, replacing the value of the same type if it was already managed.
+  pub(crate) fn set<T: Send + Sync + 'static>(&self, state: T) {
+    self
+      .0
+      .lock()
+      .unwrap()
+      .insert(TypeId::of::<T>(), Arc::new(state));
+  }
+
+  /// Gets the value of the given type.
+  pub(crate) fn get<T: Send + Sync + 'static>(&self) -> crate::Result<State<T>> {
+    self
+      .0
+      .lock()
+      .unwrap()
+      .get(&TypeId::of::<T>())
+      .cloned()
+      .and_then(|state| state.downcast::<T>().ok())
+      .map(State)
+      .ok_or_else(|| crate::Error::StateNotManaged(std::any::type_name::<T>()))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::StateManager;
+  use crate::{
+    flavors::Mock,
+    test::{run, wait_for_script, TauriContext},
+    AppBuilder,
+  };
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  #[test]
+  fn gets_managed_state() {
+    let state = StateManager::default();
+    state.set(AtomicUsize::new(1));
+    state.set(String::from("tauri"));
+
+    let counter = state.get::<AtomicUsize>().unwrap();
+    counter.fetch_add(1, Ordering::Relaxed);
+    assert_eq!(
+      state.get::<AtomicUsize>().unwrap().load(Ordering::Relaxed),
+      2
+    );
+    assert_eq!(*state.get::<String>().unwrap(), "tauri");
+
+    state.set(String::from("replaced"));
+    assert_eq!(*state.get::<String>().unwrap(), "replaced");
+  }
+
+  #[test]
+  fn fails_on_unmanaged_state() {
+    let state = StateManager::default();
+    match state.get::<u8>() {
+      Err(crate::Error::StateNotManaged(type_name)) => assert_eq!(type_name, "u8"),
+      _ => panic!("expected a StateNotManaged error"),
+    }
+  }
+
+  #[test]
+  fn injects_state_into_invoke_handler() {
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new()
+        .manage(AtomicUsize::new(0))
+        .invoke_handler(|webview_manager, arg| async move {
+          if arg.contains("counter") {
+            let counter = webview_manager.state::<AtomicUsize>()?;
+            let count = counter.fetch_add(1, Ordering::Relaxed) + 1;
+            webview_manager
+              .current_webview()?
+              .eval(&format!("count = {}", count));
+          } else {
+            webview_manager.state::<String>()?;
+          }
+          Ok(())
+        }),
+    );
+
+    handle.invoke(0, r#"{"state":"counter"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "count = 1"));
+    handle.invoke(0, r#"{"state":"counter"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "count = 2"));
+
+    handle.invoke(0, r#"{"state":"string"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "is not managed"));
+  }
+}
@@ -1,35 +1,287 @@
-use std::collections::HashMap;
+use std::{
+  collections::{HashMap, HashSet},
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+};
 
+use super::{
+  event::{Event, EventBus, EventHandler},
+  ipc_scope::IpcScope,
+  state::{State, StateManager},
+};
 use crate::{
+  api::{config::WindowConfig, menu::Menu, rpc::format_js_string},
+  async_runtime::Handle,
+  global_shortcut::{Accelerator, ShortcutRegistry},
+  settings::{Store, StoreRegistry, STORE_CHANGE_EVENT},
   webview::{Event, Message},
-  ApplicationDispatcherExt,
+  ApplicationDispatcherExt, Callback, GlobalShortcutHandler, GlobalShortcutMessage,
+  SystemTrayMessage, WindowEvent, WindowEventHandler, WindowMessage,
 };
 
+use futures::future::BoxFuture;
 use serde::Serialize;
 
+/// Builds the webviews of the app windows.
+pub(crate) trait WebviewFactory<A: ApplicationDispatcherExt>: Send + Sync {
+  /// Builds the given window.
+  fn build_window(&self, window_config: &WindowConfig) -> A::WindowBuilder;
+
+  /// The IPC access of the pages loaded in the given window.
+  fn ipc_scope(&self, window_config: &WindowConfig) -> IpcScope;
+
+  /// Builds the webview of the given window and the callbacks bound to it.
+  fn build_webview(
+    &self,
+    webview_manager: &WebviewManager<A>,
+    window_config: &WindowConfig,
+  ) -> (A::WebviewBuilder, Vec<Callback<A>>);
+
+  /// Called after the webview of the window was created.
+  fn created(&self, webview_manager: WebviewManager<A>);
+
+  /// Called with the events of the window, before the window listeners.
+  fn window_event(&self, webview_manager: &WebviewManager<A>, event: &WindowEvent);
+
+  /// Runs the exit hooks of the app and its plugins, saves the stores, then releases the single instance lock.
+  /// Fails if the stores couldn't be saved.
+  fn exit(&self, webview_manager: WebviewManager<A>) -> BoxFuture<'static, crate::Result<()>>;
+}
+
+/// A registered global shortcut.
+struct GlobalShortcut {
+  /// The window that registered the shortcut from JS, which unregisters it when it's destroyed.
+  window_label: Option<String>,
+  handler: Arc<dyn Fn(&Accelerator) + Send + Sync>,
+}
+
+/// The windows of the app, shared by all webview managers.
+pub(crate) struct WindowRegistry<A: ApplicationDispatcherExt> {
+  dispatchers: Mutex<HashMap<String, WebviewDispatcher<A>>>,
+  /// The labels of the windows being created, locked after `dispatchers`.
+  reserved_labels: Mutex<HashSet<String>>,
+  factory: Box<dyn WebviewFactory<A>>,
+  /// The dispatcher that isn't bound to a window.
+  app_dispatcher: A,
+  /// Whether closing the last window exits the app.
+  exit_on_last_window_closed: bool,
+  /// The runtime running the app tasks.
+  runtime: Handle,
+  global_shortcuts: Mutex<ShortcutRegistry<GlobalShortcut>>,
+  /// The event listeners of the app.
+  events: EventBus,
+  /// The persistent key-value stores of the app.
+  stores: Arc<StoreRegistry>,
+  exiting: AtomicBool,
+}
+
+impl<A: ApplicationDispatcherExt> WindowRegistry<A> {
+  pub(crate) fn new(
+    factory: Box<dyn WebviewFactory<A>>,
+    app_dispatcher: A,
+    exit_on_last_window_closed: bool,
+    runtime: Handle,
+    events: EventBus,
+    stores: Arc<StoreRegistry>,
+  ) -> Self {
+    Self {
+      dispatchers: Default::default(),
+      reserved_labels: Default::default(),
+      factory,
+      app_dispatcher,
+      exit_on_last_window_closed,
+      runtime,
+      global_shortcuts: Default::default(),
+      events,
+      stores,
+      exiting: AtomicBool::new(false),
+    }
+  }
+
+  pub(crate) fn factory(&self) -> &dyn WebviewFactory<A> {
+    self.factory.as_ref()
+  }
+
+  pub(crate) fn events(&self) -> &EventBus {
+    &self.events
+  }
+
+  pub(crate) fn insert(&self, label: String, dispatcher: WebviewDispatcher<A>) {
+    self.dispatchers.lock().unwrap().insert(label, dispatcher);
+  }
+
+  /// Reserves the label of a window being created.
+  /// Fails if a window with the same label is open or being created.
+  fn reserve_label(&self, label: String) -> crate::Result<LabelReservation<'_, A>> {
+    let dispatchers = self.dispatchers.lock().unwrap();
+    let mut reserved_labels = self.reserved_labels.lock().unwrap();
+    if dispatchers.contains_key(&label) || reserved_labels.contains(&label) {
+      return Err(crate::Error::WindowLabelAlreadyExists(label));
+    }
+    reserved_labels.insert(label.clone());
+    Ok(LabelReservation {
+      windows: self,
+      label,
+    })
+  }
+}
+
+/// A window label reserved until the window is inserted or its creation fails.
+struct LabelReservation<'a, A: ApplicationDispatcherExt> {
+  windows: &'a WindowRegistry<A>,
+  label: String,
+}
+
+impl<A: ApplicationDispatcherExt> LabelReservation<'_, A> {
+  /// Inserts the window, releasing the label reservation.
+  fn insert(self, dispatcher: WebviewDispatcher<A>) {
+    self.windows.insert(self.label.clone(), dispatcher);
+  }
+}
+
+impl<A: ApplicationDispatcherExt> Drop for LabelReservation<'_, A> {
+  fn drop(&mut self) {
+    self
+      .windows
+      .reserved_labels
+      .lock()
+      .unwrap()
+      .remove(&self.label);
+  }
+}
+
+/// The script that sets the `window.__TAURI__.windows` array.
+pub(crate) fn window_labels_script(labels: &[String]) -> String {
+  format!(
+    "window.__TAURI__.windows = JSON.parse({window_labels_array}).map(function (label) {{ return {{ label: label }} }});",
+    window_labels_array = format_js_string(&serde_json::to_string(labels).unwrap()),
+  )
+}
+
+type WindowEventListener = Arc<dyn Fn(&WindowEvent) + Send + Sync>;
+
+/// The state of a window, shared by all its dispatchers.
+struct WindowState {
+  window_event_listeners: Mutex<Vec<WindowEventListener>>,
+  prevent_close: AtomicBool,
+  ipc_scope: IpcScope,
+}
+
 /// The webview dispatcher.
+///
+/// The window updates fail with [`Error::UnsupportedByRuntime`](crate::Error::UnsupportedByRuntime)
+/// if the webview runtime can't apply them.
 #[derive(Clone)]
-pub struct WebviewDispatcher<A: Clone>(A);
+pub struct WebviewDispatcher<A: Clone> {
+  dispatcher: A,
+  label: String,
+  events: EventBus,
+  state: Arc<WindowState>,
+}
 
 impl<A: ApplicationDispatcherExt> WebviewDispatcher<A> {
-  pub(crate) fn new(dispatcher: A) -> Self {
-    Self(dispatcher)
+  pub(crate) fn new(dispatcher: A, label: String, events: EventBus, ipc_scope: IpcScope) -> Self {
+    Self {
+      dispatcher,
+      label,
+      events,
+      state: Arc::new(WindowState {
+        window_event_listeners: Default::default(),
+        prevent_close: AtomicBool::new(false),
+        ipc_scope,
+      }),
+    }
+  }
+
+  /// The label of the window.
+  pub fn label(&self) -> &str {
+    &self.label
+  }
+
+  /// Checks whether the webview page loaded from the given URL can invoke the app,
+  /// calling the given remote API or an app command when it's `None`.
+  pub(crate) fn check_ipc_access(
+    &self,
+    url: Option<&str>,
+    remote_api: Option<&str>,
+  ) -> crate::Result<()> {
+    self.state.ipc_scope.check(url, remote_api)
+  }
+
+  /// Narrows the IPC access of a window created from this webview to the access of this window.
+  pub(crate) fn narrow_ipc_access(&self, window_config: &mut WindowConfig) {
+    self.state.ipc_scope.narrow(window_config)
   }
 
   pub(crate) fn send_event(&self, event: Event) {
-    self.0.send_message(Message::Event(event))
+    // every runtime handles the events
+    let _ = self.dispatcher.send_message(Message::Event(event));
+  }
+
+  /// Listen to the window events.
+  ///
+  /// Call [`CloseRequest::prevent_close`](crate::CloseRequest::prevent_close) on a `CloseRequested` event
+  /// to keep the window open.
+  pub fn on_window_event<F: Fn(&WindowEvent) + Send + Sync + 'static>(&self, handler: F) {
+    self
+      .state
+      .window_event_listeners
+      .lock()
+      .unwrap()
+      .push(Arc::new(handler));
+  }
+
+  /// Sets whether the window stays open when the user requests it to be closed.
+  ///
+  /// The `tauri://close-requested` event is still emitted,
+  /// so the app can ask the user for confirmation and call [`close`](Self::close) itself.
+  pub fn set_prevent_close(&self, prevent_close: bool) {
+    self
+      .state
+      .prevent_close
+      .store(prevent_close, Ordering::Relaxed)
+  }
+
+  fn trigger_window_event(&self, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested(request) = event {
+      if self.state.prevent_close.load(Ordering::Relaxed) {
+        request.prevent_close();
+      }
+    }
+    let listeners = self.state.window_event_listeners.lock().unwrap().clone();
+    for listener in listeners {
+      listener(event);
+    }
+    super::event::emit_window_event(self, event);
+  }
+
+  /// Listens to the events emitted by this window.
+  /// The listener is removed when the window is closed.
+  pub fn listen<F: FnMut(Event) + Send + 'static>(
+    &self,
+    event: impl AsRef<str>,
+    handler: F,
+  ) -> EventHandler {
+    self
+      .events
+      .listen(event, Some(self.label.clone()), false, handler)
   }
 
-  /// Listen to an event.
-  pub fn listen<F: FnMut(Option<String>) + Send + 'static>(
+  /// Listens to the next event emitted by this window.
+  pub fn once<F: FnMut(Event) + Send + 'static>(
     &self,
     event: impl AsRef<str>,
     handler: F,
-  ) {
-    super::event::listen(event, handler)
+  ) -> EventHandler {
+    self
+      .events
+      .listen(event, Some(self.label.clone()), true, handler)
   }
 
-  /// Emits an event.
+  /// Emits an event to the JS listeners of this window.
   pub fn emit<S: Serialize>(
     &self,
     event: impl AsRef<str>,
@@ -38,53 +290,1122 @@ impl<A: ApplicationDispatcherExt> WebviewDispatcher<A> {
     super::event::emit(&self, event, payload)
   }
 
+  /// Triggers the Rust listeners of the event, as if this window emitted it.
   pub(crate) fn on_event(&self, event: String, data: Option<String>) {
-    super::event::on_event(event, data)
+    self.events.trigger(&event, Some(&self.label), data)
+  }
+
+  /// The event listeners of the app.
+  pub(crate) fn events(&self) -> &EventBus {
+    &self.events
   }
 
   /// Evaluates a JS script.
   pub fn eval(&self, js: &str) {
-    self.0.send_message(Message::EvalScript(js.to_string()))
+    // every runtime evaluates scripts
+    let _ = self
+      .dispatcher
+      .send_message(Message::EvalScript(js.to_string()));
   }
 
   /// Updates the window title.
-  pub fn set_title(&self, title: &str) {
-    self
-      .0
-      .send_message(Message::SetWindowTitle(title.to_string()))
+  pub fn set_title(&self, title: &str) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetTitle(title.to_string()))
+  }
+
+  /// Sets whether the window is resizable or not.
+  pub fn set_resizable(&self, resizable: bool) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetResizable(resizable))
+  }
+
+  /// Resizes the window.
+  pub fn set_size(&self, width: f64, height: f64) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetSize { width, height })
+  }
+
+  /// Sets the window min size. `None` removes the constraint.
+  pub fn set_min_size(&self, min_width: Option<f64>, min_height: Option<f64>) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetMinSize {
+      min_width,
+      min_height,
+    })
+  }
+
+  /// Sets the window max size. `None` removes the constraint.
+  pub fn set_max_size(&self, max_width: Option<f64>, max_height: Option<f64>) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetMaxSize {
+      max_width,
+      max_height,
+    })
+  }
+
+  /// Moves the window.
+  pub fn set_position(&self, x: f64, y: f64) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetPosition { x, y })
+  }
+
+  /// Enters or leaves fullscreen.
+  pub fn set_fullscreen(&self, fullscreen: bool) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetFullscreen(fullscreen))
+  }
+
+  /// Sets whether the window has borders and bars.
+  pub fn set_decorations(&self, decorations: bool) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetDecorations(decorations))
+  }
+
+  /// Sets whether the window is always on top of other windows.
+  pub fn set_always_on_top(&self, always_on_top: bool) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetAlwaysOnTop(always_on_top))
+  }
+
+  /// Minimizes the window.
+  pub fn minimize(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Minimize)
+  }
+
+  /// Restores a minimized window.
+  pub fn unminimize(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Unminimize)
+  }
+
+  /// Maximizes the window.
+  pub fn maximize(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Maximize)
+  }
+
+  /// Restores a maximized window.
+  pub fn unmaximize(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Unmaximize)
+  }
+
+  /// Shows the window.
+  pub fn show(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Show)
+  }
+
+  /// Hides the window.
+  pub fn hide(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Hide)
+  }
+
+  /// Centers the window on its monitor.
+  pub fn center(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Center)
+  }
+
+  /// Starts moving the window with the cursor.
+  /// Call it on a mouse down event of a custom title bar.
+  pub fn start_dragging(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::StartDragging)
+  }
+
+  /// Closes the window.
+  /// It's removed from the window list when the runtime reports it as destroyed.
+  pub fn close(&self) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::Close)
+  }
+
+  /// Sets whether the menu item with the given id can be clicked or not.
+  pub fn set_menu_item_enabled(&self, id: impl Into<String>, enabled: bool) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetMenuItemEnabled {
+      id: id.into(),
+      enabled,
+    })
+  }
+
+  /// Checks or unchecks the menu item with the given id.
+  pub fn set_menu_item_checked(&self, id: impl Into<String>, checked: bool) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetMenuItemChecked {
+      id: id.into(),
+      checked,
+    })
+  }
+
+  /// Updates the title of the menu item with the given id.
+  pub fn set_menu_item_title(
+    &self,
+    id: impl Into<String>,
+    title: impl Into<String>,
+  ) -> crate::Result<()> {
+    self.send_window_message(WindowMessage::SetMenuItemTitle {
+      id: id.into(),
+      title: title.into(),
+    })
+  }
+
+  fn send_window_message(&self, message: WindowMessage) -> crate::Result<()> {
+    self.dispatcher.send_message(Message::Window(message))
   }
 }
 
 /// The webview manager.
 #[derive(Clone)]
-pub struct WebviewManager<A: Clone> {
-  dispatchers: HashMap<String, WebviewDispatcher<A>>,
+pub struct WebviewManager<A: ApplicationDispatcherExt> {
+  windows: Arc<WindowRegistry<A>>,
+  state: Arc<StateManager>,
   current_webview_window_label: String,
 }
 
 impl<A: ApplicationDispatcherExt> WebviewManager<A> {
-  pub(crate) fn new(dispatchers: HashMap<String, WebviewDispatcher<A>>, label: String) -> Self {
+  pub(crate) fn new(
+    windows: Arc<WindowRegistry<A>>,
+    state: Arc<StateManager>,
+    label: String,
+  ) -> Self {
     Self {
-      dispatchers,
+      windows,
+      state,
       current_webview_window_label: label,
     }
   }
 
+  /// Gets the value of the given type managed with [`AppBuilder::manage`](crate::AppBuilder::manage).
+  /// Fails with [`Error::StateNotManaged`](crate::Error::StateNotManaged) if it wasn't managed.
+  pub fn state<T: Send + Sync + 'static>(&self) -> crate::Result<State<T>> {
+    self.state.get()
+  }
+
   /// Returns the label of the window associated with the current context.
   pub fn current_window_label(&self) -> &str {
     &self.current_webview_window_label
   }
 
   /// Gets the webview associated with the current context.
-  pub fn current_webview(&self) -> crate::Result<&WebviewDispatcher<A>> {
+  pub fn current_webview(&self) -> crate::Result<WebviewDispatcher<A>> {
     self.get_webview(&self.current_webview_window_label)
   }
 
   /// Gets the webview associated with the given window label.
-  pub fn get_webview(&self, window_label: &str) -> crate::Result<&WebviewDispatcher<A>> {
+  pub fn get_webview(&self, window_label: &str) -> crate::Result<WebviewDispatcher<A>> {
     self
+      .windows
       .dispatchers
+      .lock()
+      .unwrap()
       .get(window_label)
+      .cloned()
       .ok_or(crate::Error::WebviewNotFound)
   }
+
+  /// Gets the webviews of all open windows, keyed by the window label.
+  pub fn windows(&self) -> HashMap<String, WebviewDispatcher<A>> {
+    self.windows.dispatchers.lock().unwrap().clone()
+  }
+
+  /// Creates a new window with its webview.
+  /// Fails if a window with the same label is already open.
+  pub fn create_window(&self, window_config: WindowConfig) -> crate::Result<WebviewDispatcher<A>> {
+    let label = window_config.label.clone();
+    let reservation = self.windows.reserve_label(label.clone())?;
+
+    let webview_manager = Self::new(self.windows.clone(), self.state.clone(), label.clone());
+    let (webview_builder, callbacks) = self
+      .windows
+      .factory()
+      .build_webview(&webview_manager, &window_config);
+    let window_builder = self.windows.factory().build_window(&window_config);
+    let dispatcher = WebviewDispatcher::new(
+      self.windows.app_dispatcher.create_webview(
+        window_builder,
+        webview_builder,
+        callbacks,
+        webview_manager.window_event_handler(),
+      )?,
+      label.clone(),
+      self.windows.events.clone(),
+      self.windows.factory().ipc_scope(&window_config),
+    );
+
+    reservation.insert(dispatcher.clone());
+    self.windows.factory().created(webview_manager);
+    self.update_window_labels();
+
+    Ok(dispatcher)
+  }
+
+  /// Listens to the events emitted by all windows and the app.
+  pub fn listen<F: FnMut(Event) + Send + 'static>(
+    &self,
+    event: impl AsRef<str>,
+    handler: F,
+  ) -> EventHandler {
+    self.windows.events.listen(event, None, false, handler)
+  }
+
+  /// Listens to the next event emitted by any window or the app.
+  pub fn once<F: FnMut(Event) + Send + 'static>(
+    &self,
+    event: impl AsRef<str>,
+    handler: F,
+  ) -> EventHandler {
+    self.windows.events.listen(event, None, true, handler)
+  }
+
+  /// Removes an event listener added with `listen` or `once`.
+  pub fn unlisten(&self, handler: EventHandler) {
+    self.windows.events.unlisten(handler)
+  }
+
+  /// The event listeners of the app.
+  pub(crate) fn events(&self) -> &EventBus {
+    &self.windows.events
+  }
+
+  /// Emits an event to the JS listeners of all windows.
+  pub fn emit_all<S: Serialize>(
+    &self,
+    event: impl AsRef<str>,
+    payload: Option<S>,
+  ) -> crate::Result<()> {
+    for dispatcher in self.windows().values() {
+      dispatcher.emit(event.as_ref(), payload.as_ref())?;
+    }
+    Ok(())
+  }
+
+  /// Emits an event to the JS listeners of the window with the given label.
+  pub fn emit_to<S: Serialize>(
+    &self,
+    window_label: &str,
+    event: impl AsRef<str>,
+    payload: Option<S>,
+  ) -> crate::Result<()> {
+    self.get_webview(window_label)?.emit(event, payload)
+  }
+
+  /// Gets the persistent key-value store with the given name,
+  /// opening it with the options registered with [`AppBuilder::store`](crate::AppBuilder::store) or the default ones.
+  ///
+  /// Its changes emit the [`STORE_CHANGE_EVENT`] event to the Rust listeners and the JS listeners of all windows.
+  pub fn store(&self, name: &str) -> crate::Result<Store>
+  where
+    A: 'static,
+  {
+    // the stores outlive the windows, so they don't keep them alive
+    let windows = Arc::downgrade(&self.windows);
+    self.windows.stores.open(name, move |store| {
+      store.on_change(move |change| {
+        if let Some(windows) = windows.upgrade() {
+          windows
+            .events
+            .trigger(STORE_CHANGE_EVENT, None, serde_json::to_string(change).ok());
+          let dispatchers: Vec<_> = windows
+            .dispatchers
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+          for dispatcher in dispatchers {
+            let _ = dispatcher.emit(STORE_CHANGE_EVENT, Some(change));
+          }
+        }
+      })
+    })
+  }
+
+  /// Closes the window with the given label.
+  pub fn close_window(&self, window_label: &str) -> crate::Result<()> {
+    self.get_webview(window_label)?.close()
+  }
+
+  /// Runs the exit hooks of the app and its plugins, then stops the event loop and exits the process with the given code.
+  ///
+  /// The hooks run on a separate task, so the plugins can exit the app from their own hooks.
+  /// If the stores can't be saved, the process exits with the code 1 instead of a successful exit code.
+  /// Does nothing if the app is already exiting.
+  pub fn exit(&self, exit_code: i32) {
+    let dispatcher = self.current_webview().map(|webview| webview.dispatcher);
+    self.shutdown(dispatcher.ok(), exit_code);
+  }
+
+  /// Runs the exit hooks of the app and its plugins, then relaunches the app with the same arguments and exits.
+  ///
+  /// The new instance is launched once the exit hooks released the single instance lock, so it doesn't forward its args to this one.
+  pub fn restart(&self) -> crate::Result<()> {
+    let executable = std::env::current_exe()?;
+    let dispatcher = self.current_webview().map(|webview| webview.dispatcher);
+    if self.windows.exiting.swap(true, Ordering::SeqCst) {
+      return Ok(());
+    }
+    let webview_manager = self.clone();
+    self.windows.runtime.spawn(async move {
+      let exit_code = webview_manager.run_exit_hooks(0).await;
+      if let Err(e) = std::process::Command::new(executable)
+        .args(std::env::args_os().skip(1))
+        .spawn()
+      {
+        eprintln!("failed to restart the app: {}", e);
+      }
+      webview_manager.stop(dispatcher.ok(), exit_code);
+    });
+    Ok(())
+  }
+
+  /// Runs the exit hooks on a separate task, then stops the event loop with the given dispatcher.
+  fn shutdown(&self, dispatcher: Option<A>, exit_code: i32) {
+    if self.windows.exiting.swap(true, Ordering::SeqCst) {
+      return;
+    }
+    let webview_manager = self.clone();
+    self.windows.runtime.spawn(async move {
+      let exit_code = webview_manager.run_exit_hooks(exit_code).await;
+      webview_manager.stop(dispatcher, exit_code);
+    });
+  }
+
+  /// Runs the exit hooks, returning the exit code of the process: 1 if they failed with a successful exit code.
+  async fn run_exit_hooks(&self, exit_code: i32) -> i32 {
+    match self.windows.factory().exit(self.clone()).await {
+      Err(_) if exit_code == 0 => 1,
+      _ => exit_code,
+    }
+  }
+
+  fn stop(&self, dispatcher: Option<A>, exit_code: i32) {
+    // every runtime handles the exit requests
+    let _ = dispatcher
+      .unwrap_or_else(|| self.windows.app_dispatcher.clone())
+      .send_message(Message::Exit(exit_code));
+  }
+
+  /// Updates the system tray icon.
+  /// Relative paths are resolved from the resource directory.
+  pub fn set_system_tray_icon(&self, icon: impl Into<PathBuf>) -> crate::Result<()> {
+    let icon = crate::webview::resolve_icon_path(icon.into())?;
+    self.send_system_tray_message(SystemTrayMessage::SetIcon(icon));
+    Ok(())
+  }
+
+  /// Updates the tooltip shown when hovering the system tray icon.
+  pub fn set_system_tray_tooltip(&self, tooltip: impl Into<String>) {
+    self.send_system_tray_message(SystemTrayMessage::SetTooltip(tooltip.into()))
+  }
+
+  /// Replaces the system tray menu.
+  pub fn set_system_tray_menu(&self, menu: Menu) {
+    self.send_system_tray_message(SystemTrayMessage::SetMenu(menu))
+  }
+
+  fn send_system_tray_message(&self, message: SystemTrayMessage) {
+    let _ = self
+      .windows
+      .app_dispatcher
+      .send_message(Message::SystemTray(message));
+  }
+
+  /// Registers a global shortcut, calling the handler whenever the user presses it,
+  /// even if the app isn't focused.
+  ///
+  /// Fails if the accelerator is invalid or already registered, or if the OS didn't register it.
+  pub fn register_global_shortcut<F: Fn(&Accelerator) + Send + Sync + 'static>(
+    &self,
+    accelerator: &str,
+    handler: F,
+  ) -> crate::Result<()> {
+    self.add_global_shortcut(accelerator, None, Arc::new(handler))
+  }
+
+  /// Registers a global shortcut on behalf of the current window, unregistered when the window is destroyed.
+  pub(crate) fn register_window_global_shortcut<F: Fn(&Accelerator) + Send + Sync + 'static>(
+    &self,
+    accelerator: &str,
+    handler: F,
+  ) -> crate::Result<()> {
+    self.add_global_shortcut(
+      accelerator,
+      Some(self.current_webview_window_label.clone()),
+      Arc::new(handler),
+    )
+  }
+
+  fn add_global_shortcut(
+    &self,
+    accelerator: &str,
+    window_label: Option<String>,
+    handler: Arc<dyn Fn(&Accelerator) + Send + Sync>,
+  ) -> crate::Result<()> {
+    let accelerator: Accelerator = accelerator.parse()?;
+    self.windows.global_shortcuts.lock().unwrap().register(
+      accelerator,
+      GlobalShortcut {
+        window_label,
+        handler,
+      },
+    )?;
+    if let Err(e) = self.send_global_shortcut_message(GlobalShortcutMessage::Register(accelerator))
+    {
+      let _ = self
+        .windows
+        .global_shortcuts
+        .lock()
+        .unwrap()
+        .unregister(&accelerator);
+      return Err(e);
+    }
+    Ok(())
+  }
+
+  /// Unregisters a global shortcut.
+  ///
+  /// Fails if the accelerator is invalid or isn't registered.
+  pub fn unregister_global_shortcut(&self, accelerator: &str) -> crate::Result<()> {
+    let accelerator: Accelerator = accelerator.parse()?;
+    self
+      .windows
+      .global_shortcuts
+      .lock()
+      .unwrap()
+      .unregister(&accelerator)?;
+    self.send_global_shortcut_message(GlobalShortcutMessage::Unregister(accelerator))
+  }
+
+  /// Whether the global shortcut is registered by the app.
+  ///
+  /// Fails if the accelerator is invalid.
+  pub fn is_global_shortcut_registered(&self, accelerator: &str) -> crate::Result<bool> {
+    let accelerator: Accelerator = accelerator.parse()?;
+    Ok(
+      self
+        .windows
+        .global_shortcuts
+        .lock()
+        .unwrap()
+        .is_registered(&accelerator),
+    )
+  }
+
+  fn send_global_shortcut_message(&self, message: GlobalShortcutMessage) -> crate::Result<()> {
+    self
+      .windows
+      .app_dispatcher
+      .send_message(Message::GlobalShortcut(message))
+  }
+
+  /// Unregisters the global shortcuts the current window registered from JS.
+  fn unregister_window_global_shortcuts(&self) {
+    let accelerators = self
+      .windows
+      .global_shortcuts
+      .lock()
+      .unwrap()
+      .unregister_matching(|shortcut| {
+        shortcut.window_label.as_ref() == Some(&self.current_webview_window_label)
+      });
+    for accelerator in accelerators {
+      let _ = self.send_global_shortcut_message(GlobalShortcutMessage::Unregister(accelerator));
+    }
+  }
+
+  /// The handler of the global shortcuts pressed by the user.
+  pub(crate) fn global_shortcut_handler(&self) -> GlobalShortcutHandler {
+    let windows = self.windows.clone();
+    Box::new(move |accelerator| {
+      let handler = windows
+        .global_shortcuts
+        .lock()
+        .unwrap()
+        .get(accelerator)
+        .map(|shortcut| shortcut.handler.clone());
+      if let Some(handler) = handler {
+        handler(accelerator);
+      }
+    })
+  }
+
+  /// The handler of the runtime events of the current window.
+  pub(crate) fn window_event_handler(&self) -> WindowEventHandler {
+    let webview_manager = self.clone();
+    Box::new(move |event| webview_manager.on_window_event(event))
+  }
+
+  fn on_window_event(&self, event: &WindowEvent) {
+    self.windows.factory().window_event(self, event);
+    if let Ok(dispatcher) = self.current_webview() {
+      dispatcher.trigger_window_event(event);
+    }
+    if let WindowEvent::Destroyed = event {
+      let (dispatcher, is_last_window) = {
+        let mut dispatchers = self.windows.dispatchers.lock().unwrap();
+        let dispatcher = dispatchers.remove(&self.current_webview_window_label);
+        (dispatcher, dispatchers.is_empty())
+      };
+      self
+        .windows
+        .events
+        .unlisten_window(&self.current_webview_window_label);
+      self.unregister_window_global_shortcuts();
+      if is_last_window && self.windows.exit_on_last_window_closed {
+        self.shutdown(dispatcher.map(|webview| webview.dispatcher), 0);
+      } else {
+        self.update_window_labels();
+      }
+    }
+  }
+
+  /// Updates the `window.__TAURI__.windows` array on all windows.
+  fn update_window_labels(&self) {
+    let windows = self.windows();
+    let mut labels: Vec<String> = windows.keys().cloned().collect();
+    labels.sort();
+    let script = window_labels_script(&labels);
+    for dispatcher in windows.values() {
+      dispatcher.eval(&script);
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{window_labels_script, WebviewManager};
+  use crate::{
+    api::config::WindowConfig,
+    flavors::{
+      mock::{MockDispatcher, MockMessage},
+      Mock,
+    },
+    global_shortcut::Accelerator,
+    settings::StoreBuilder,
+    test::{run, wait_for, wait_for_script, TauriContext, TrayContext, TIMEOUT},
+    AppBuilder, ApplicationDispatcherExt, GlobalShortcutMessage, Menu, MenuEntry, MenuItem,
+    Submenu, SystemTray, SystemTrayEvent, WindowEvent, WindowMessage,
+  };
+  use serde::Deserialize;
+  use std::{
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      Arc, Mutex,
+    },
+    time::Duration,
+  };
+
+  #[derive(Deserialize)]
+  struct WindowCmd {
+    create: Option<String>,
+    close: Option<String>,
+  }
+
+  #[test]
+  fn creates_and_closes_windows() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new().invoke_handler(
+      |webview_manager, arg| async move {
+        let cmd: WindowCmd = serde_json::from_str(&arg)?;
+        if let Some(label) = cmd.create {
+          webview_manager.create_window(WindowConfig {
+            label,
+            title: "Settings".to_string(),
+            ..Default::default()
+          })?;
+        }
+        if let Some(label) = cmd.close {
+          webview_manager.close_window(&label)?;
+        }
+        Ok(())
+      },
+    ));
+
+    handle.invoke(0, r#"{"create":"settings"}"#).unwrap();
+    let labels_script = window_labels_script(&["main".to_string(), "settings".to_string()]);
+    assert!(wait_for_script(&handle, 0, &labels_script));
+    let windows = handle.windows();
+    assert_eq!(windows.len(), 2);
+    assert_eq!(windows[1].attributes.title, "Settings");
+    assert!(windows[1]
+      .webview
+      .as_ref()
+      .expect("webview not created")
+      .initialization_scripts
+      .iter()
+      .any(|script| script.contains(&labels_script)));
+
+    handle.invoke(0, r#"{"create":"settings"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "already exists"));
+
+    handle.invoke(1, r#"{"close":"settings"}"#).unwrap();
+    assert!(wait_for(&handle, 1, |message| {
+      *message == MockMessage::Window(WindowMessage::Close)
+    }));
+    assert!(wait_for_script(
+      &handle,
+      0,
+      &window_labels_script(&["main".to_string()])
+    ));
+    assert!(handle.invoke(1, "{}").is_err());
+  }
+
+  #[test]
+  fn reserves_the_labels_of_windows_being_created() {
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().setup(|webview_manager| async move {
+        let settings = || WindowConfig {
+          label: "settings".to_string(),
+          ..Default::default()
+        };
+        let reservation = webview_manager
+          .windows
+          .reserve_label("settings".to_string())
+          .unwrap();
+        if let Err(crate::Error::WindowLabelAlreadyExists(_)) =
+          webview_manager.create_window(settings())
+        {
+          drop(reservation);
+          webview_manager.create_window(settings()).unwrap();
+          webview_manager.current_webview().unwrap().eval("created");
+        }
+      }),
+    );
+
+    handle.invoke(0, r#"{"cmd":"__initialized"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "created"));
+    assert_eq!(handle.windows().len(), 2);
+  }
+
+  #[test]
+  fn forwards_window_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_ = events.clone();
+    let webview_manager = Arc::new(Mutex::new(None));
+    let webview_manager_ = webview_manager.clone();
+    // the app callback receives the events of the windows created at startup
+    let handle = run(AppBuilder::<Mock, TauriContext>::new().on_window_event(
+      move |webview_manager, event| {
+        if let WindowEvent::CloseRequested(request) = event {
+          request.prevent_close();
+        }
+        events_.lock().unwrap().push(event.clone());
+        webview_manager_
+          .lock()
+          .unwrap()
+          .replace(webview_manager.clone());
+      },
+    ));
+
+    assert!(handle.send_window_event(
+      0,
+      WindowEvent::Resized {
+        width: 800.0,
+        height: 600.0
+      }
+    ));
+    assert!(!handle.send_window_event(0, WindowEvent::CloseRequested(Default::default())));
+    let webview_manager = webview_manager.lock().unwrap().clone().unwrap();
+    assert_eq!(webview_manager.windows().len(), 1);
+
+    assert!(handle.send_window_event(0, WindowEvent::Destroyed));
+    assert!(handle.invoke(0, "{}").is_err());
+    assert!(webview_manager.windows().is_empty());
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(events[0], WindowEvent::Resized { width, .. } if width == 800.0));
+    assert!(matches!(events[1], WindowEvent::CloseRequested(_)));
+    assert!(matches!(events[2], WindowEvent::Destroyed));
+  }
+
+  #[test]
+  fn runs_exit_hooks() {
+    let exits = Arc::new(AtomicUsize::new(0));
+    let exits_ = exits.clone();
+    let webview_manager = Arc::new(Mutex::new(None));
+    let webview_manager_ = webview_manager.clone();
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().on_exit(move |webview_manager| {
+        let exits = exits_.clone();
+        webview_manager_.lock().unwrap().replace(webview_manager);
+        async move {
+          exits.fetch_add(1, Ordering::SeqCst);
+        }
+      }),
+    );
+
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Process","message":{"cmd":"exit","exitCode":3}}"#,
+      )
+      .unwrap();
+    assert!(wait_for(&handle, 0, |message| {
+      *message == MockMessage::Exit(3)
+    }));
+    assert_eq!(exits.load(Ordering::SeqCst), 1);
+
+    // the app is already exiting, so the exit request returns without spawning the hooks
+    let webview_manager: WebviewManager<MockDispatcher> =
+      webview_manager.lock().unwrap().clone().unwrap();
+    assert!(webview_manager.windows.exiting.load(Ordering::SeqCst));
+    webview_manager.exit(4);
+    assert_eq!(exits.load(Ordering::SeqCst), 1);
+    assert!(!handle
+      .messages()
+      .iter()
+      .any(|(_, message)| *message == MockMessage::Exit(4)));
+  }
+
+  #[test]
+  fn exits_with_a_failure_code_if_the_stores_cant_be_saved() {
+    // the stores directory can't be created under a file
+    let file = std::env::temp_dir().join("tauri-store-exit");
+    std::fs::write(&file, "").unwrap();
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new()
+        .store(
+          StoreBuilder::new("preferences")
+            .dir(file.join("stores"))
+            .autosave(Duration::from_secs(3600)),
+        )
+        .setup(|webview_manager| async move {
+          let store = webview_manager.store("preferences").unwrap();
+          store.set("theme", "dark").unwrap();
+          webview_manager.current_webview().unwrap().eval("stored");
+        }),
+    );
+    handle.invoke(0, r#"{"cmd":"__initialized"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "stored"));
+
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Process","message":{"cmd":"exit","exitCode":0}}"#,
+      )
+      .unwrap();
+    assert!(wait_for(&handle, 0, |message| {
+      *message == MockMessage::Exit(1)
+    }));
+
+    std::fs::remove_file(file).unwrap();
+  }
+
+  /// Records the threads its hooks run on.
+  struct ThreadPlugin(Arc<Mutex<Vec<Option<String>>>>);
+
+  #[async_trait::async_trait]
+  impl<D: ApplicationDispatcherExt + 'static> crate::plugin::Plugin<D> for ThreadPlugin {
+    fn name(&self) -> &'static str {
+      "thread"
+    }
+
+    async fn initialize(&mut self, _config: String) -> crate::Result<()> {
+      self.0.lock().unwrap().push(thread_name());
+      Ok(())
+    }
+  }
+
+  fn thread_name() -> Option<String> {
+    std::thread::current().name().map(ToString::to_string)
+  }
+
+  #[test]
+  fn runs_tasks_on_the_app_runtime() {
+    // the async runtime functions keep using the app runtime after the test
+    let runtime: &'static _ = Box::leak(Box::new(
+      tokio::runtime::Builder::new_multi_thread()
+        .thread_name("app-runtime")
+        .build()
+        .unwrap(),
+    ));
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let threads_ = threads.clone();
+    // the runtime can be set after the plugins are registered
+    let builder = AppBuilder::<Mock, TauriContext>::new()
+      .plugin(ThreadPlugin(threads.clone()))
+      .runtime(runtime.handle().clone())
+      .setup(move |_| {
+        let threads = threads_.clone();
+        async move {
+          threads.lock().unwrap().push(thread_name());
+        }
+      });
+    // running the app in an async context doesn't panic
+    let handle = runtime.block_on(async move { run(builder) });
+
+    handle.invoke(0, r#"{"cmd":"__initialized"}"#).unwrap();
+    let started = std::time::Instant::now();
+    while threads.lock().unwrap().len() < 2 && started.elapsed() < TIMEOUT {
+      std::thread::yield_now();
+    }
+    assert_eq!(
+      *threads.lock().unwrap(),
+      vec![Some("app-runtime".to_string()); 2]
+    );
+  }
+
+  #[test]
+  fn exits_when_the_last_window_is_closed_if_enabled() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new().exit_on_last_window_closed(true));
+    assert!(handle.send_window_event(0, WindowEvent::Destroyed));
+    assert!(wait_for(&handle, 0, |message| {
+      *message == MockMessage::Exit(0)
+    }));
+  }
+
+  #[test]
+  fn routes_menu_events() {
+    let clicks = Arc::new(Mutex::new(Vec::new()));
+    let clicks_ = clicks.clone();
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new()
+        .menu(Menu::new(vec![Submenu::new(
+          "File",
+          vec![
+            MenuItem::new("save", "Save")
+              .accelerator("CmdOrCtrl+S")
+              .into(),
+            MenuEntry::separator(),
+            MenuItem::new("autosave", "Auto Save").checked(false).into(),
+          ],
+        )
+        .into()]))
+        // the app listeners receive the events emitted before the setup callback runs
+        .listen("menu-event", move |event| {
+          clicks_
+            .lock()
+            .unwrap()
+            .push(event.payload().map(ToString::to_string));
+        })
+        .invoke_handler(|webview_manager, _| async move {
+          let dispatcher = webview_manager.current_webview()?;
+          dispatcher.set_menu_item_checked("autosave", true).unwrap();
+          dispatcher.set_menu_item_enabled("save", false).unwrap();
+          dispatcher.set_menu_item_title("save", "Saved").unwrap();
+          Ok(())
+        }),
+    );
+    let menu = handle.windows()[0].attributes.menu.clone();
+    assert_eq!(
+      menu.and_then(|menu| menu.item("save").cloned()),
+      Some(MenuItem::new("save", "Save").accelerator("CmdOrCtrl+S"))
+    );
+
+    assert!(handle.send_window_event(0, WindowEvent::MenuItemClicked("save".to_string())));
+    assert_eq!(*clicks.lock().unwrap(), vec![Some(r#""save""#.to_string())]);
+
+    handle.invoke(0, "{}").unwrap();
+    assert!(wait_for(&handle, 0, |message| {
+      matches!(
+        message,
+        MockMessage::Window(WindowMessage::SetMenuItemTitle { .. })
+      )
+    }));
+    let menu = handle.windows()[0].attributes.menu.clone().unwrap();
+    assert_eq!(menu.item("autosave").unwrap().checked, Some(true));
+    let save = menu.item("save").unwrap();
+    assert!(!save.enabled);
+    assert_eq!(save.title, "Saved");
+  }
+
+  #[test]
+  fn runs_system_tray_apps() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_ = events.clone();
+    let icon = std::env::temp_dir().join("tray.png");
+    let webview_manager = Arc::new(Mutex::new(None));
+    let webview_manager_ = webview_manager.clone();
+    let handle = run(
+      AppBuilder::<Mock, TrayContext>::new()
+        .system_tray(
+          SystemTray::new(icon.clone())
+            .tooltip("Sync")
+            .menu(Menu::new(vec![MenuItem::new("quit", "Quit").into()])),
+        )
+        .setup(move |webview_manager| {
+          let events = events_.clone();
+          webview_manager_
+            .lock()
+            .unwrap()
+            .replace(webview_manager.clone());
+          async move {
+            webview_manager.listen("system-tray-event", move |event| {
+              events
+                .lock()
+                .unwrap()
+                .push(event.payload().map(ToString::to_string));
+            });
+            webview_manager
+              .create_window(WindowConfig::default())
+              .unwrap();
+            webview_manager.set_system_tray_tooltip("Synced");
+          }
+        }),
+    );
+
+    // the setup runs without a webview
+    assert!(handle
+      .wait_for_app_message(TIMEOUT, |message| {
+        matches!(message, MockMessage::SystemTray(_))
+      })
+      .is_some());
+    let system_tray = handle.system_tray().expect("system tray not created");
+    assert_eq!(system_tray.icon, icon);
+    assert_eq!(system_tray.tooltip, Some("Synced".to_string()));
+
+    handle.send_system_tray_event(SystemTrayEvent::MenuItemClick {
+      id: "quit".to_string(),
+    });
+    assert_eq!(
+      *events.lock().unwrap(),
+      vec![Some(r#"{"type":"menuItemClick","id":"quit"}"#.to_string())]
+    );
+
+    // closing the last window doesn't exit the app by default, so the app can still exit with its own code
+    assert_eq!(handle.windows().len(), 1);
+    assert!(handle.send_window_event(0, WindowEvent::Destroyed));
+    let webview_manager = webview_manager.lock().unwrap().clone().unwrap();
+    webview_manager.exit(4);
+    assert!(handle
+      .wait_for_app_message(TIMEOUT, |message| *message == MockMessage::Exit(4))
+      .is_some());
+  }
+
+  #[test]
+  fn emits_to_windows() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_ = events.clone();
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().setup(move |webview_manager| {
+        let events = events_.clone();
+        async move {
+          let settings = webview_manager
+            .create_window(WindowConfig {
+              label: "settings".to_string(),
+              ..Default::default()
+            })
+            .unwrap();
+          settings.listen("settings-event", move |event| {
+            events
+              .lock()
+              .unwrap()
+              .push(event.window_label().map(ToString::to_string))
+          });
+          // the events of the main window don't reach the listeners of the settings window
+          webview_manager
+            .current_webview()
+            .unwrap()
+            .on_event("settings-event".to_string(), None);
+          settings.on_event("settings-event".to_string(), None);
+
+          webview_manager
+            .emit_to("settings", "greet", Some("hi"))
+            .unwrap();
+          assert!(webview_manager
+            .emit_to("missing", "greet", Some("hi"))
+            .is_err());
+          webview_manager
+            .emit_all("broadcast", Option::<()>::None)
+            .unwrap();
+        }
+      }),
+    );
+    handle.invoke(0, r#"{"cmd":"__initialized"}"#).unwrap();
+
+    assert!(wait_for_script(&handle, 0, "broadcast"));
+    assert!(wait_for_script(&handle, 1, "broadcast"));
+    assert!(wait_for_script(&handle, 1, "greet"));
+    assert!(!handle.messages().iter().any(|(window, message)| {
+      *window == Some(0) && matches!(message, MockMessage::EvalScript(js) if js.contains("greet"))
+    }));
+    assert_eq!(*events.lock().unwrap(), vec![Some("settings".to_string())]);
+
+    // JS emits to the given window, or to all windows
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Event","message":{"cmd":"emit","event":"js-greet","windowLabel":"settings","payload":"hi"}}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(&handle, 1, "js-greet"));
+    handle
+      .invoke(
+        1,
+        r#"{"module":"Event","message":{"cmd":"emit","event":"js-broadcast"}}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(&handle, 0, "js-broadcast"));
+    assert!(wait_for_script(&handle, 1, "js-broadcast"));
+    assert!(!handle.messages().iter().any(|(window, message)| {
+      *window == Some(0)
+        && matches!(message, MockMessage::EvalScript(js) if js.contains("js-greet"))
+    }));
+  }
+
+  #[test]
+  fn runs_global_shortcuts() {
+    let pressed = Arc::new(Mutex::new(Vec::new()));
+    let pressed_ = pressed.clone();
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().setup(move |webview_manager| {
+        let pressed = pressed_.clone();
+        async move {
+          webview_manager
+            .register_global_shortcut("CmdOrCtrl+Shift+K", move |accelerator| {
+              pressed.lock().unwrap().push(accelerator.to_string())
+            })
+            .unwrap();
+          assert!(matches!(
+            webview_manager.register_global_shortcut("CmdOrCtrl+Shift+K", |_| {}),
+            Err(crate::Error::GlobalShortcutAlreadyRegistered(_))
+          ));
+          assert!(matches!(
+            webview_manager.register_global_shortcut("Ctrl+Shift", |_| {}),
+            Err(crate::Error::InvalidAccelerator(_))
+          ));
+          webview_manager
+            .register_global_shortcut("Alt+F4", |_| {})
+            .unwrap();
+          webview_manager
+            .unregister_global_shortcut("alt+f4")
+            .unwrap();
+          // the OS doesn't register the shortcuts used by other apps
+          assert!(matches!(
+            webview_manager.register_global_shortcut("Ctrl+Alt+P", |_| {}),
+            Err(crate::Error::RegisterGlobalShortcut(_))
+          ));
+          assert!(!webview_manager
+            .is_global_shortcut_registered("Ctrl+Alt+P")
+            .unwrap());
+        }
+      }),
+    );
+    handle.use_global_shortcut_elsewhere("Ctrl+Alt+P".parse().unwrap());
+    handle.invoke(0, r#"{"cmd":"__initialized"}"#).unwrap();
+
+    let accelerator: Accelerator = "CmdOrCtrl+Shift+K".parse().unwrap();
+    let alt_f4: Accelerator = "Alt+F4".parse().unwrap();
+    assert!(handle
+      .wait_for_app_message(TIMEOUT, |message| {
+        *message == MockMessage::GlobalShortcut(GlobalShortcutMessage::Unregister(alt_f4))
+      })
+      .is_some());
+    assert!(handle.press_global_shortcut(&accelerator));
+    assert!(!handle.press_global_shortcut(&alt_f4));
+    assert_eq!(*pressed.lock().unwrap(), vec![accelerator.to_string()]);
+  }
+
+  #[test]
+  fn unregisters_the_global_shortcuts_of_destroyed_windows() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new());
+    let accelerator: Accelerator = "Ctrl+Shift+J".parse().unwrap();
+
+    handle
+      .invoke(
+        0,
+        r#"{"module":"GlobalShortcut","message":{"cmd":"register","shortcut":"Ctrl+Shift+J","handler":"onShortcut","callback":"ok","error":"err"}}"#,
+      )
+      .unwrap();
+    assert!(handle
+      .wait_for_app_message(TIMEOUT, |message| {
+        *message == MockMessage::GlobalShortcut(GlobalShortcutMessage::Register(accelerator))
+      })
+      .is_some());
+    assert!(handle.press_global_shortcut(&accelerator));
+    assert!(wait_for_script(&handle, 0, "onShortcut"));
+
+    assert!(handle.send_window_event(0, WindowEvent::Destroyed));
+    assert!(handle
+      .wait_for_app_message(TIMEOUT, |message| {
+        *message == MockMessage::GlobalShortcut(GlobalShortcutMessage::Unregister(accelerator))
+      })
+      .is_some());
+    assert!(!handle.press_global_shortcut(&accelerator));
+  }
 }
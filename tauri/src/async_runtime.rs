@@ -1,22 +1,139 @@
 use once_cell::sync::OnceCell;
 use tokio::runtime::Runtime;
 
-use std::sync::Mutex as StdMutex;
+pub use tokio::{
+  runtime::Handle,
+  sync::Mutex,
+  task::{JoinError, JoinHandle},
+};
 
-pub use tokio::sync::Mutex;
+use std::future::Future;
 
-static RUNTIME: OnceCell<StdMutex<Runtime>> = OnceCell::new();
+/// The runtime started by Tauri when the app doesn't provide one.
+static RUNTIME: OnceCell<Runtime> = OnceCell::new();
 
-pub fn block_on<F: futures::Future>(future: F) -> F::Output {
-  let runtime = RUNTIME.get_or_init(|| StdMutex::new(Runtime::new().unwrap()));
-  runtime.lock().unwrap().block_on(future)
+/// The runtime given to [`AppBuilder::runtime`](crate::AppBuilder::runtime), if any.
+static APP_RUNTIME: OnceCell<Handle> = OnceCell::new();
+
+/// Uses the given runtime instead of the runtime started by Tauri.
+///
+/// Only the first runtime is kept, since the functions of this module can already have used it.
+pub(crate) fn set_app_handle(handle: Handle) {
+  let _ = APP_RUNTIME.set(handle);
+}
+
+/// The handle of the runtime given to the app, or the runtime started by Tauri, started on first use.
+pub(crate) fn default_handle() -> Handle {
+  APP_RUNTIME.get().cloned().unwrap_or_else(|| {
+    RUNTIME
+      .get_or_init(|| Runtime::new().unwrap())
+      .handle()
+      .clone()
+  })
+}
+
+/// The handle of the runtime given to [`AppBuilder::runtime`](crate::AppBuilder::runtime).
+///
+/// Without one, it's the runtime running the current task, or the runtime started by Tauri
+/// outside of a task.
+pub fn handle() -> Handle {
+  APP_RUNTIME
+    .get()
+    .cloned()
+    .or_else(|| Handle::try_current().ok())
+    .unwrap_or_else(default_handle)
+}
+
+/// Runs a future to completion on the app runtime or the runtime started by Tauri, blocking the current thread.
+///
+/// Panics if called from an async context.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+  default_handle().block_on(future)
+}
+
+/// Runs a future to completion on the given runtime, blocking the current thread until it's done.
+///
+/// Unlike [`Handle::block_on`], it doesn't enter the runtime, so it doesn't panic in an async context.
+pub(crate) fn wait<F>(handle: &Handle, future: F) -> F::Output
+where
+  F: Future + Send + 'static,
+  F::Output: Send + 'static,
+{
+  match futures::executor::block_on(handle.spawn(future)) {
+    Ok(output) => output,
+    Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+    Err(e) => panic!("{}", e),
+  }
 }
 
-pub fn spawn<F>(future: F)
+/// Spawns a future onto the runtime returned by [`handle`].
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
 where
-  F: futures::Future + Send + 'static,
+  F: Future + Send + 'static,
   F::Output: Send + 'static,
 {
-  let runtime = RUNTIME.get_or_init(|| StdMutex::new(Runtime::new().unwrap()));
-  runtime.lock().unwrap().spawn(future);
+  handle().spawn(future)
+}
+
+/// Runs a blocking function on the thread pool of the runtime returned by [`handle`].
+pub fn spawn_blocking<F, R>(function: F) -> JoinHandle<R>
+where
+  F: FnOnce() -> R + Send + 'static,
+  R: Send + 'static,
+{
+  handle().spawn_blocking(function)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{block_on, set_app_handle, spawn, spawn_blocking, wait};
+  use tokio::runtime::{Builder, Runtime};
+
+  // the app runtime is global, so every test setting it uses a runtime named `app-runtime`
+  fn app_runtime() -> &'static Runtime {
+    Box::leak(Box::new(
+      Builder::new_multi_thread()
+        .thread_name("app-runtime")
+        .build()
+        .unwrap(),
+    ))
+  }
+
+  fn thread_name() -> Option<String> {
+    std::thread::current().name().map(ToString::to_string)
+  }
+
+  #[test]
+  fn joins_spawned_tasks() {
+    let tasks: Vec<_> = (0..10).map(|i| spawn(async move { i * 2 })).collect();
+    let blocking = spawn_blocking(|| std::thread::sleep(std::time::Duration::from_millis(10)));
+    let results = block_on(async move {
+      blocking.await.unwrap();
+      futures::future::join_all(tasks).await
+    });
+    let results: Vec<i32> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn runs_tasks_on_the_app_runtime() {
+    let runtime = app_runtime();
+    let handle = runtime.handle().clone();
+    runtime.block_on(async move {
+      // waiting in an async context doesn't panic
+      let name = wait(&handle, async move { thread_name() });
+      assert_eq!(name.as_deref(), Some("app-runtime"));
+      let name = spawn_blocking(thread_name).await.unwrap();
+      assert_eq!(name.as_deref(), Some("app-runtime"));
+    });
+  }
+
+  #[test]
+  fn uses_the_app_runtime_outside_of_it() {
+    set_app_handle(app_runtime().handle().clone());
+    let name = block_on(spawn(async { thread_name() })).unwrap();
+    assert_eq!(name.as_deref(), Some("app-runtime"));
+    let name = block_on(spawn_blocking(thread_name)).unwrap();
+    assert_eq!(name.as_deref(), Some("app-runtime"));
+  }
 }
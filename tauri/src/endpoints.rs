@@ -4,12 +4,15 @@ mod dialog;
 mod event;
 #[allow(unused_imports)]
 mod file_system;
+mod global_shortcut;
 #[cfg(http_request)]
 mod http;
 mod internal;
 #[cfg(notification)]
 mod notification;
+mod process;
 mod shell;
+mod store;
 mod window;
 
 use crate::{app::Context, ApplicationDispatcherExt};
@@ -37,6 +40,9 @@ enum Module {
   Cli(cli::Cmd),
   Notification(notification::Cmd),
   Http(http::Cmd),
+  Process(process::Cmd),
+  GlobalShortcut(global_shortcut::Cmd),
+  Store(store::Cmd),
 }
 
 impl Module {
@@ -46,15 +52,18 @@ impl Module {
     context: &Context,
   ) -> crate::Result<()> {
     match self {
-      Self::Fs(cmd) => cmd.run(webview_manager).await,
+      Self::Fs(cmd) => cmd.run(webview_manager, context).await,
       Self::Window(cmd) => cmd.run(webview_manager).await?,
-      Self::Shell(cmd) => cmd.run(webview_manager).await,
+      Self::Shell(cmd) => cmd.run(webview_manager, context).await,
       Self::Event(cmd) => cmd.run(webview_manager).await?,
       Self::Internal(cmd) => cmd.run(webview_manager).await?,
       Self::Dialog(cmd) => cmd.run(webview_manager).await?,
       Self::Cli(cmd) => cmd.run(webview_manager, context).await,
       Self::Notification(cmd) => cmd.run(webview_manager, context).await?,
-      Self::Http(cmd) => cmd.run(webview_manager).await,
+      Self::Http(cmd) => cmd.run(webview_manager, context).await,
+      Self::Process(cmd) => cmd.run(webview_manager).await?,
+      Self::GlobalShortcut(cmd) => cmd.run(webview_manager).await,
+      Self::Store(cmd) => cmd.run(webview_manager).await,
     }
     Ok(())
   }
@@ -65,29 +74,21 @@ pub(crate) async fn handle<D: ApplicationDispatcherExt + 'static>(
   arg: &str,
   context: &Context,
 ) -> crate::Result<()> {
-  match serde_json::from_str::<ModuleDto>(arg) {
-    Err(e) => {
-      if e.to_string().contains("missing field `module`") {
-        Err(crate::Error::UnknownApi(Some(e)))
-      } else {
-        Err(e.into())
-      }
+  let mut module_dto: ModuleDto = serde_json::from_str(arg)?;
+  let module_name = module_dto.module.clone();
+  if let Value::Object(ref mut obj) = module_dto.message {
+    obj.insert("module".to_string(), Value::String(module_dto.module));
+    if let Some(callback) = module_dto.callback {
+      obj.insert("callback".to_string(), Value::String(callback));
     }
-    Ok(mut module_dto) => {
-      if let Value::Object(ref mut obj) = module_dto.message {
-        obj.insert("module".to_string(), Value::String(module_dto.module));
-        if let Some(callback) = module_dto.callback {
-          obj.insert("callback".to_string(), Value::String(callback));
-        }
-        if let Some(error) = module_dto.error {
-          obj.insert("error".to_string(), Value::String(error));
-        }
-      }
-      let module: Module = serde_json::from_str(&module_dto.message.to_string())?;
-      module.run(webview_manager, context).await?;
-      Ok(())
+    if let Some(error) = module_dto.error {
+      obj.insert("error".to_string(), Value::String(error));
     }
   }
+  let module: Module = serde_json::from_value(module_dto.message)
+    .map_err(|e| crate::Error::InvalidArgs(module_name, None, e))?;
+  module.run(webview_manager, context).await?;
+  Ok(())
 }
 
 #[allow(dead_code)]
@@ -96,7 +97,7 @@ fn api_error<D: ApplicationDispatcherExt>(
   error_fn: String,
   message: &str,
 ) {
-  let reject_code = tauri_api::rpc::format_callback(error_fn, message);
+  let reject_code = tauri_api::rpc::format_ipc_message(Some(&error_fn), false, message.into());
   if let Ok(dispatcher) = webview_manager.current_webview() {
     dispatcher.eval(&reject_code);
   }
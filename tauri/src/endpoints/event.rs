@@ -1,4 +1,6 @@
 use serde::Deserialize;
+#[cfg(event)]
+use tauri_api::rpc::format_ipc_message;
 
 /// The API descriptor.
 #[derive(Deserialize)]
@@ -11,8 +13,11 @@ pub enum Cmd {
     once: bool,
   },
   /// The event emit API.
+  /// Emits the event to the JS listeners of the given window, or of all windows if it's not defined.
+  #[serde(rename_all = "camelCase")]
   Emit {
     event: String,
+    window_label: Option<String>,
     payload: Option<String>,
   },
 }
@@ -30,16 +35,28 @@ impl Cmd {
       } => {
         #[cfg(event)]
         {
-          let js_string = listen_fn(event, handler, once)?;
+          let js_string = listen_fn(event, handler, once);
           webview_manager.current_webview()?.eval(&js_string);
         }
         #[cfg(not(event))]
         throw_allowlist_error(webview_manager, "event");
       }
-      Self::Emit { event, payload } => {
-        // TODO emit to optional window
+      Self::Emit {
+        event,
+        window_label,
+        payload,
+      } => {
         #[cfg(event)]
-        webview_manager.current_webview()?.on_event(event, payload);
+        {
+          match &window_label {
+            Some(window_label) => {
+              webview_manager.emit_to(window_label, &event, payload.as_ref())?
+            }
+            None => webview_manager.emit_all(&event, payload.as_ref())?,
+          }
+          // the Rust listeners scoped to other windows don't receive it
+          webview_manager.current_webview()?.on_event(event, payload);
+        }
         #[cfg(not(event))]
         throw_allowlist_error(webview_manager, "event");
       }
@@ -48,32 +65,20 @@ impl Cmd {
   }
 }
 
+/// The script that registers the JS listener, through the IPC dispatcher.
+///
+/// The event and handler names are sent as the message payload, so they are never evaluated as code.
 #[cfg(event)]
-pub fn listen_fn(event: String, handler: String, once: bool) -> crate::Result<String> {
-  Ok(format!(
-    "if (window['{listeners}'] === void 0) {{
-      window['{listeners}'] = {{}}
-      }}
-    if (window['{listeners}']['{evt}'] === void 0) {{
-      window['{listeners}']['{evt}'] = []
-    }}
-    window['{listeners}']['{evt}'].push({{
-      handler: window['{handler}'],
-      once: {once_flag}
-    }});
-
-    for (let i = 0; i < (window['{queue}'] || []).length; i++) {{
-      const e = window['{queue}'][i];
-      window['{emit}'](e.payload, e.salt, true)
-    }}
-  ",
-    listeners = crate::app::event::event_listeners_object_name(),
-    queue = crate::app::event::event_queue_object_name(),
-    emit = crate::app::event::emit_function_name(),
-    evt = event,
-    handler = handler,
-    once_flag = if once { "true" } else { "false" }
-  ))
+pub fn listen_fn(event: String, handler: String, once: bool) -> String {
+  format_ipc_message(
+    Some(&crate::app::event::listen_function_name()),
+    true,
+    serde_json::json!({
+      "event": event,
+      "handler": handler,
+      "once": once,
+    }),
+  )
 }
 
 #[cfg(test)]
@@ -85,7 +90,28 @@ mod test {
     #[cfg(event)]
     #[test]
     fn check_listen_fn(event in "", handler in "", once in proptest::bool::ANY) {
-      super::listen_fn(event, handler, once).expect("listen_fn failed");
+      let js = super::listen_fn(event, handler, once);
+      prop_assert!(js.starts_with(tauri_api::rpc::IPC_DISPATCHER));
     }
   }
+
+  #[cfg(event)]
+  #[test]
+  fn escapes_listened_events() {
+    let event = r#"event']); alert("</script>\"#;
+    let handler = "handler'); alert('";
+    let js = super::listen_fn(event.to_string(), handler.to_string(), false);
+    assert!(!js.contains("</script>"));
+
+    let literal = js
+      .strip_prefix(&format!("{}(", tauri_api::rpc::IPC_DISPATCHER))
+      .and_then(|js| js.strip_suffix(')'))
+      .expect("not an IPC dispatcher call");
+    let message: serde_json::Value =
+      serde_json::from_str(&serde_json::from_str::<String>(literal).unwrap()).unwrap();
+    assert_eq!(message["id"], crate::app::event::listen_function_name());
+    assert_eq!(message["payload"]["event"], event);
+    assert_eq!(message["payload"]["handler"], handler);
+    assert_eq!(message["payload"]["once"], false);
+  }
 }
@@ -1,5 +1,5 @@
 use super::allowlist_error;
-use crate::{api::path::BaseDirectory, ApplicationDispatcherExt};
+use crate::{api::path::BaseDirectory, scope::FsScope, ApplicationDispatcherExt};
 
 use serde::Deserialize;
 use tauri_api::{dir, file, path::resolve_path};
@@ -114,10 +114,13 @@ pub enum Cmd {
 }
 
 impl Cmd {
+  #[allow(unused_variables)]
   pub async fn run<D: ApplicationDispatcherExt + 'static>(
     self,
     webview_manager: &crate::WebviewManager<D>,
+    context: &crate::app::Context,
   ) {
+    let scope = context.fs_scope.clone();
     match self {
       Self::ReadTextFile {
         path,
@@ -126,7 +129,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(read_text_file)]
-        read_text_file(webview_manager, path, options, callback, error).await;
+        read_text_file(webview_manager, scope, path, options, callback, error).await;
         #[cfg(not(read_text_file))]
         allowlist_error(webview_manager, error, "readTextFile");
       }
@@ -137,7 +140,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(read_binary_file)]
-        read_binary_file(webview_manager, path, options, callback, error).await;
+        read_binary_file(webview_manager, scope, path, options, callback, error).await;
         #[cfg(not(read_binary_file))]
         allowlist_error(webview_manager, error, "readBinaryFile");
       }
@@ -149,7 +152,16 @@ impl Cmd {
         error,
       } => {
         #[cfg(write_file)]
-        write_file(webview_manager, path, contents, options, callback, error).await;
+        write_file(
+          webview_manager,
+          scope,
+          path,
+          contents,
+          options,
+          callback,
+          error,
+        )
+        .await;
         #[cfg(not(write_file))]
         allowlist_error(webview_manager, error, "writeFile");
       }
@@ -161,7 +173,16 @@ impl Cmd {
         error,
       } => {
         #[cfg(write_binary_file)]
-        write_binary_file(webview_manager, path, contents, options, callback, error).await;
+        write_binary_file(
+          webview_manager,
+          scope,
+          path,
+          contents,
+          options,
+          callback,
+          error,
+        )
+        .await;
         #[cfg(not(write_binary_file))]
         allowlist_error(webview_manager, error, "writeBinaryFile");
       }
@@ -172,7 +193,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(read_dir)]
-        read_dir(webview_manager, path, options, callback, error).await;
+        read_dir(webview_manager, scope, path, options, callback, error).await;
         #[cfg(not(read_dir))]
         allowlist_error(webview_manager, error, "readDir");
       }
@@ -186,6 +207,7 @@ impl Cmd {
         #[cfg(copy_file)]
         copy_file(
           webview_manager,
+          scope,
           source,
           destination,
           options,
@@ -203,7 +225,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(create_dir)]
-        create_dir(webview_manager, path, options, callback, error).await;
+        create_dir(webview_manager, scope, path, options, callback, error).await;
         #[cfg(not(create_dir))]
         allowlist_error(webview_manager, error, "createDir");
       }
@@ -214,7 +236,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(remove_dir)]
-        remove_dir(webview_manager, path, options, callback, error).await;
+        remove_dir(webview_manager, scope, path, options, callback, error).await;
         #[cfg(not(remove_dir))]
         allowlist_error(webview_manager, error, "removeDir");
       }
@@ -225,7 +247,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(remove_file)]
-        remove_file(webview_manager, path, options, callback, error).await;
+        remove_file(webview_manager, scope, path, options, callback, error).await;
         #[cfg(not(remove_file))]
         allowlist_error(webview_manager, error, "removeFile");
       }
@@ -239,6 +261,7 @@ impl Cmd {
         #[cfg(rename_file)]
         rename_file(
           webview_manager,
+          scope,
           old_path,
           new_path,
           options,
@@ -268,6 +291,7 @@ impl Cmd {
 #[cfg(read_dir)]
 pub async fn read_dir<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   options: Option<DirOperationOptions>,
   callback: String,
@@ -281,7 +305,7 @@ pub async fn read_dir<D: ApplicationDispatcherExt>(
       } else {
         (false, None)
       };
-      dir::read_dir(resolve_path(path, dir)?, recursive).map_err(crate::Error::FailedToExecuteApi)
+      read_scoped_dir(&scope, &scope.check(resolve_path(path, dir)?)?, recursive)
     },
     callback,
     error,
@@ -289,10 +313,33 @@ pub async fn read_dir<D: ApplicationDispatcherExt>(
   .await;
 }
 
+/// Reads a directory, skipping the entries outside of the scope, e.g. the links to other directories.
+#[cfg(read_dir)]
+fn read_scoped_dir(
+  scope: &FsScope,
+  path: &std::path::Path,
+  recursive: bool,
+) -> crate::Result<Vec<dir::DiskEntry>> {
+  let mut entries = Vec::new();
+  for entry in dir::read_dir(path, false)? {
+    let path = match scope.check(&entry.path) {
+      Ok(path) => path,
+      Err(_) => continue,
+    };
+    let children = match entry.children {
+      Some(_) if recursive => Some(read_scoped_dir(scope, &path, true)?),
+      children => children,
+    };
+    entries.push(dir::DiskEntry { children, ..entry });
+  }
+  Ok(entries)
+}
+
 /// Copies a file.
 #[cfg(copy_file)]
 pub async fn copy_file<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   source: PathBuf,
   destination: PathBuf,
   options: Option<FileOperationOptions>,
@@ -309,7 +356,7 @@ pub async fn copy_file<D: ApplicationDispatcherExt>(
         ),
         None => (source, destination),
       };
-      fs::copy(src, dest)?;
+      fs::copy(scope.check(src)?, scope.check(dest)?)?;
       crate::Result::Ok(())
     },
     callback,
@@ -322,6 +369,7 @@ pub async fn copy_file<D: ApplicationDispatcherExt>(
 #[cfg(create_dir)]
 pub async fn create_dir<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   options: Option<DirOperationOptions>,
   callback: String,
@@ -335,7 +383,7 @@ pub async fn create_dir<D: ApplicationDispatcherExt>(
       } else {
         (false, None)
       };
-      let resolved_path = resolve_path(path, dir)?;
+      let resolved_path = scope.check(resolve_path(path, dir)?)?;
       if recursive {
         fs::create_dir_all(resolved_path)?;
       } else {
@@ -354,6 +402,7 @@ pub async fn create_dir<D: ApplicationDispatcherExt>(
 #[cfg(remove_dir)]
 pub async fn remove_dir<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   options: Option<DirOperationOptions>,
   callback: String,
@@ -367,7 +416,7 @@ pub async fn remove_dir<D: ApplicationDispatcherExt>(
       } else {
         (false, None)
       };
-      let resolved_path = resolve_path(path, dir)?;
+      let resolved_path = scope.check(resolve_path(path, dir)?)?;
       if recursive {
         fs::remove_dir_all(resolved_path)?;
       } else {
@@ -386,6 +435,7 @@ pub async fn remove_dir<D: ApplicationDispatcherExt>(
 #[cfg(remove_file)]
 pub async fn remove_file<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   options: Option<FileOperationOptions>,
   callback: String,
@@ -394,7 +444,7 @@ pub async fn remove_file<D: ApplicationDispatcherExt>(
   crate::execute_promise(
     webview_manager,
     async move {
-      let resolved_path = resolve_path(path, options.and_then(|o| o.dir))?;
+      let resolved_path = scope.check(resolve_path(path, options.and_then(|o| o.dir))?)?;
       fs::remove_file(resolved_path)?;
       crate::Result::Ok(())
     },
@@ -408,6 +458,7 @@ pub async fn remove_file<D: ApplicationDispatcherExt>(
 #[cfg(rename_file)]
 pub async fn rename_file<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   old_path: PathBuf,
   new_path: PathBuf,
   options: Option<FileOperationOptions>,
@@ -424,7 +475,7 @@ pub async fn rename_file<D: ApplicationDispatcherExt>(
         ),
         None => (old_path, new_path),
       };
-      fs::rename(old, new).map_err(crate::Error::Io)
+      fs::rename(scope.check(old)?, scope.check(new)?).map_err(crate::Error::Io)
     },
     callback,
     error,
@@ -436,6 +487,7 @@ pub async fn rename_file<D: ApplicationDispatcherExt>(
 #[cfg(write_file)]
 pub async fn write_file<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   contents: String,
   options: Option<FileOperationOptions>,
@@ -445,7 +497,7 @@ pub async fn write_file<D: ApplicationDispatcherExt>(
   crate::execute_promise(
     webview_manager,
     async move {
-      File::create(resolve_path(path, options.and_then(|o| o.dir))?)
+      File::create(scope.check(resolve_path(path, options.and_then(|o| o.dir))?)?)
         .map_err(crate::Error::Io)
         .and_then(|mut f| f.write_all(contents.as_bytes()).map_err(|err| err.into()))?;
       crate::Result::Ok(())
@@ -460,6 +512,7 @@ pub async fn write_file<D: ApplicationDispatcherExt>(
 #[cfg(write_binary_file)]
 pub async fn write_binary_file<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   contents: String,
   options: Option<FileOperationOptions>,
@@ -472,7 +525,7 @@ pub async fn write_binary_file<D: ApplicationDispatcherExt>(
       base64::decode(contents)
         .map_err(crate::Error::Base64Decode)
         .and_then(|c| {
-          File::create(resolve_path(path, options.and_then(|o| o.dir))?)
+          File::create(scope.check(resolve_path(path, options.and_then(|o| o.dir))?)?)
             .map_err(|e| e.into())
             .and_then(|mut f| f.write_all(&c).map_err(|err| err.into()))
         })?;
@@ -488,6 +541,7 @@ pub async fn write_binary_file<D: ApplicationDispatcherExt>(
 #[cfg(read_text_file)]
 pub async fn read_text_file<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   options: Option<FileOperationOptions>,
   callback: String,
@@ -496,7 +550,7 @@ pub async fn read_text_file<D: ApplicationDispatcherExt>(
   crate::execute_promise(
     webview_manager,
     async move {
-      file::read_string(resolve_path(path, options.and_then(|o| o.dir))?)
+      file::read_string(scope.check(resolve_path(path, options.and_then(|o| o.dir))?)?)
         .map_err(crate::Error::FailedToExecuteApi)
     },
     callback,
@@ -509,6 +563,7 @@ pub async fn read_text_file<D: ApplicationDispatcherExt>(
 #[cfg(read_binary_file)]
 pub async fn read_binary_file<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  scope: FsScope,
   path: PathBuf,
   options: Option<FileOperationOptions>,
   callback: String,
@@ -517,7 +572,7 @@ pub async fn read_binary_file<D: ApplicationDispatcherExt>(
   crate::execute_promise(
     webview_manager,
     async move {
-      file::read_binary(resolve_path(path, options.and_then(|o| o.dir))?)
+      file::read_binary(scope.check(resolve_path(path, options.and_then(|o| o.dir))?)?)
         .map_err(crate::Error::FailedToExecuteApi)
     },
     callback,
@@ -545,6 +600,61 @@ pub async fn resolve_path_handler<D: ApplicationDispatcherExt>(
 // test webview functionality.
 #[cfg(test)]
 mod test {
+  use crate::{
+    flavors::Mock,
+    test::{run, wait_for_script, TauriContext},
+    AppBuilder,
+  };
+
+  #[test]
+  fn rejects_paths_outside_of_the_scope() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new());
+
+    // the fixture doesn't define a file system scope
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Fs","message":{"cmd":"readTextFile","path":"Cargo.toml"},"callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      0,
+      "is not allowed on the file system scope"
+    ));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn skips_the_entries_outside_of_the_scope() {
+    use super::read_scoped_dir;
+    use crate::scope::FsScope;
+    use std::{fs, os::unix::fs::symlink};
+
+    let dir = std::env::temp_dir().join("tauri-read-scoped-dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("allowed/nested")).unwrap();
+    fs::create_dir_all(dir.join("denied")).unwrap();
+    fs::write(dir.join("allowed/nested/data.csv"), "").unwrap();
+    fs::write(dir.join("allowed/nested/data.json"), "").unwrap();
+    fs::write(dir.join("denied/secret.txt"), "").unwrap();
+    symlink(dir.join("denied"), dir.join("allowed/link")).unwrap();
+    let scope = FsScope::new(&[
+      format!("{}/allowed", dir.display()),
+      format!("{}/allowed/*", dir.display()),
+      format!("{}/allowed/**/*.csv", dir.display()),
+    ]);
+
+    let entries = read_scoped_dir(&scope, &dir.join("allowed"), true).unwrap();
+    assert_eq!(entries.len(), 1);
+    let children = entries[0].children.as_ref().unwrap();
+    // the JSON file and the link to the denied directory aren't listed
+    let names: Vec<_> = children.iter().map(|entry| entry.name.as_deref()).collect();
+    assert_eq!(names, vec![Some("data.csv")]);
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
   // use super::*;
   // use web_view::*;
 
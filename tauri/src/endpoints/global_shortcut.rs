@@ -0,0 +1,106 @@
+use serde::Deserialize;
+
+/// The API descriptor.
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum Cmd {
+  /// Registers a global shortcut, calling the `handler` function when it's pressed.
+  Register {
+    shortcut: String,
+    handler: String,
+    callback: String,
+    error: String,
+  },
+  /// Unregisters a global shortcut.
+  Unregister {
+    shortcut: String,
+    callback: String,
+    error: String,
+  },
+  /// Checks whether a global shortcut is registered.
+  IsRegistered {
+    shortcut: String,
+    callback: String,
+    error: String,
+  },
+}
+
+impl Cmd {
+  #[allow(unused_variables)]
+  pub async fn run<D: crate::ApplicationDispatcherExt + 'static>(
+    self,
+    webview_manager: &crate::WebviewManager<D>,
+  ) {
+    match self {
+      Self::Register {
+        shortcut,
+        handler,
+        callback,
+        error,
+      } => {
+        #[cfg(global_shortcut)]
+        {
+          let webview_manager_ = webview_manager.clone();
+          crate::execute_promise(
+            webview_manager,
+            async move {
+              let webview_manager = webview_manager_.clone();
+              webview_manager_.register_window_global_shortcut(&shortcut, move |accelerator| {
+                // the handler is called on the window that registered the shortcut, until it's destroyed
+                if let Ok(dispatcher) = webview_manager.current_webview() {
+                  dispatcher.eval(&tauri_api::rpc::format_callback(
+                    &handler,
+                    accelerator.to_string(),
+                  ));
+                }
+              })
+            },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(global_shortcut))]
+        super::allowlist_error(webview_manager, error, "globalShortcut");
+      }
+      Self::Unregister {
+        shortcut,
+        callback,
+        error,
+      } => {
+        #[cfg(global_shortcut)]
+        {
+          let webview_manager_ = webview_manager.clone();
+          crate::execute_promise(
+            webview_manager,
+            async move { webview_manager_.unregister_global_shortcut(&shortcut) },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(global_shortcut))]
+        super::allowlist_error(webview_manager, error, "globalShortcut");
+      }
+      Self::IsRegistered {
+        shortcut,
+        callback,
+        error,
+      } => {
+        #[cfg(global_shortcut)]
+        {
+          let webview_manager_ = webview_manager.clone();
+          crate::execute_promise(
+            webview_manager,
+            async move { webview_manager_.is_global_shortcut_registered(&shortcut) },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(global_shortcut))]
+        super::allowlist_error(webview_manager, error, "globalShortcut");
+      }
+    }
+  }
+}
@@ -19,6 +19,7 @@ impl Cmd {
   pub async fn run<D: ApplicationDispatcherExt + 'static>(
     self,
     webview_manager: &crate::WebviewManager<D>,
+    context: &crate::app::Context,
   ) {
     match self {
       Self::HttpRequest {
@@ -27,7 +28,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(http_request)]
-        make_request(webview_manager, *options, callback, error).await;
+        make_request(webview_manager, context, *options, callback, error).await;
         #[cfg(not(http_request))]
         allowlist_error(webview_manager, error, "httpRequest");
       }
@@ -35,16 +36,18 @@ impl Cmd {
   }
 }
 
-/// Makes an HTTP request and resolves the response to the webview
+/// Makes an HTTP request allowed by the HTTP scope and resolves the response to the webview
 pub async fn make_request<D: ApplicationDispatcherExt>(
   webview_manager: &crate::WebviewManager<D>,
+  context: &crate::app::Context,
   options: HttpRequestOptions,
   callback: String,
   error: String,
 ) {
+  let options = context.http_scope.prepare(options);
   crate::execute_promise(
     webview_manager,
-    async move { request(options).map_err(|e| e.into()) },
+    async move { request(options?).map_err(|e| e.into()) },
     callback,
     error,
   )
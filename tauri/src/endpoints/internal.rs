@@ -36,7 +36,7 @@ pub fn validate_salt<D: crate::ApplicationDispatcherExt>(
   callback: String,
   error: String,
 ) -> crate::Result<()> {
-  let response = if crate::salt::is_valid(salt) {
+  let response = if webview_manager.events().salts().is_valid(salt) {
     Ok("Valid")
   } else {
     Err("Invalid salt")
@@ -0,0 +1,36 @@
+use serde::Deserialize;
+
+/// The API descriptor.
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum Cmd {
+  /// Exits the app with the given code.
+  #[serde(rename_all = "camelCase")]
+  Exit { exit_code: i32 },
+  /// Relaunches the app.
+  Restart,
+}
+
+impl Cmd {
+  #[allow(unused_variables)]
+  pub async fn run<D: crate::ApplicationDispatcherExt + 'static>(
+    self,
+    webview_manager: &crate::WebviewManager<D>,
+  ) -> crate::Result<()> {
+    match self {
+      Self::Exit { exit_code } => {
+        #[cfg(process_exit)]
+        webview_manager.exit(exit_code);
+        #[cfg(not(process_exit))]
+        super::throw_allowlist_error(webview_manager, "exit");
+      }
+      Self::Restart => {
+        #[cfg(process_restart)]
+        webview_manager.restart()?;
+        #[cfg(not(process_restart))]
+        super::throw_allowlist_error(webview_manager, "restart");
+      }
+    }
+    Ok(())
+  }
+}
@@ -4,7 +4,7 @@ use serde::Deserialize;
 #[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
-  /// The execute script API.
+  /// The execute API, running a command defined on the shell scope.
   Execute {
     command: String,
     args: Vec<String>,
@@ -16,9 +16,11 @@ pub enum Cmd {
 }
 
 impl Cmd {
+  #[allow(unused_variables)]
   pub async fn run<D: crate::ApplicationDispatcherExt + 'static>(
     self,
     webview_manager: &crate::WebviewManager<D>,
+    context: &crate::app::Context,
   ) {
     match self {
       Self::Execute {
@@ -28,7 +30,7 @@ impl Cmd {
         error,
       } => {
         #[cfg(execute)]
-        crate::call(webview_manager, command, args, callback, error).await;
+        execute(webview_manager, context, command, args, callback, error).await;
         #[cfg(not(execute))]
         super::throw_allowlist_error(webview_manager, "execute");
       }
@@ -42,6 +44,34 @@ impl Cmd {
   }
 }
 
+/// Executes the command with the given name on the shell scope, validating its arguments.
+#[cfg(execute)]
+pub async fn execute<D: crate::ApplicationDispatcherExt>(
+  webview_manager: &crate::WebviewManager<D>,
+  context: &crate::app::Context,
+  command: String,
+  args: Vec<String>,
+  callback: String,
+  error: String,
+) {
+  let command = context.shell_scope.prepare(&command, args);
+  crate::execute_promise(
+    webview_manager,
+    async move {
+      let (cmd, args) = command?;
+      // the command blocks until it exits
+      crate::async_runtime::spawn_blocking(move || {
+        crate::api::command::get_output(cmd, args, std::process::Stdio::piped())
+      })
+      .await?
+      .map_err(|e| e.into())
+    },
+    callback,
+    error,
+  )
+  .await;
+}
+
 #[cfg(open)]
 pub fn open_browser(uri: String) {
   #[cfg(test)]
@@ -53,7 +83,31 @@ pub fn open_browser(uri: String) {
 
 #[cfg(test)]
 mod test {
+  use crate::{
+    flavors::Mock,
+    test::{run, wait_for_script, TauriContext},
+    AppBuilder,
+  };
   use proptest::prelude::*;
+
+  #[test]
+  fn rejects_commands_outside_of_the_scope() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new());
+
+    // the fixture doesn't define a shell scope
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Shell","message":{"cmd":"execute","command":"ls","args":[]},"callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      0,
+      "shell command `ls` is not allowed"
+    ));
+  }
+
   // Test the open func to see if proper uris can be opened by the browser.
   proptest! {
     #[cfg(open)]
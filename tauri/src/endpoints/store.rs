@@ -0,0 +1,234 @@
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+/// The API descriptor.
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum Cmd {
+  /// Gets the value of a key.
+  Get {
+    store: String,
+    key: String,
+    callback: String,
+    error: String,
+  },
+  /// Sets the value of a key.
+  Set {
+    store: String,
+    key: String,
+    value: JsonValue,
+    callback: String,
+    error: String,
+  },
+  /// Deletes a key.
+  Delete {
+    store: String,
+    key: String,
+    callback: String,
+    error: String,
+  },
+  /// Lists the keys of the store.
+  Keys {
+    store: String,
+    callback: String,
+    error: String,
+  },
+  /// Deletes all the keys of the store.
+  Clear {
+    store: String,
+    callback: String,
+    error: String,
+  },
+  /// Saves the store to its file.
+  Save {
+    store: String,
+    callback: String,
+    error: String,
+  },
+}
+
+impl Cmd {
+  #[allow(unused_variables)]
+  pub async fn run<D: crate::ApplicationDispatcherExt + 'static>(
+    self,
+    webview_manager: &crate::WebviewManager<D>,
+  ) {
+    match self {
+      Self::Get {
+        store,
+        key,
+        callback,
+        error,
+      } => {
+        #[cfg(store)]
+        {
+          let store = webview_manager.store(&store);
+          crate::execute_promise(
+            webview_manager,
+            async move { store?.get::<JsonValue>(&key) },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(store))]
+        super::allowlist_error(webview_manager, error, "store");
+      }
+      Self::Set {
+        store,
+        key,
+        value,
+        callback,
+        error,
+      } => {
+        #[cfg(store)]
+        {
+          let store = webview_manager.store(&store);
+          crate::execute_promise(
+            webview_manager,
+            async move { store?.set(key, value) },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(store))]
+        super::allowlist_error(webview_manager, error, "store");
+      }
+      Self::Delete {
+        store,
+        key,
+        callback,
+        error,
+      } => {
+        #[cfg(store)]
+        {
+          let store = webview_manager.store(&store);
+          crate::execute_promise(
+            webview_manager,
+            async move { Ok(store?.delete(&key)) },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(store))]
+        super::allowlist_error(webview_manager, error, "store");
+      }
+      Self::Keys {
+        store,
+        callback,
+        error,
+      } => {
+        #[cfg(store)]
+        {
+          let store = webview_manager.store(&store);
+          crate::execute_promise(
+            webview_manager,
+            async move { Ok(store?.keys()) },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(store))]
+        super::allowlist_error(webview_manager, error, "store");
+      }
+      Self::Clear {
+        store,
+        callback,
+        error,
+      } => {
+        #[cfg(store)]
+        {
+          let store = webview_manager.store(&store);
+          crate::execute_promise(
+            webview_manager,
+            async move {
+              store?.clear();
+              Ok(())
+            },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(store))]
+        super::allowlist_error(webview_manager, error, "store");
+      }
+      Self::Save {
+        store,
+        callback,
+        error,
+      } => {
+        #[cfg(store)]
+        {
+          let store = webview_manager.store(&store);
+          crate::execute_promise(
+            webview_manager,
+            async move { store?.save() },
+            callback,
+            error,
+          )
+          .await;
+        }
+        #[cfg(not(store))]
+        super::allowlist_error(webview_manager, error, "store");
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{
+    flavors::{mock::MockMessage, Mock},
+    settings::StoreBuilder,
+    test::{run, wait_for, wait_for_script, TauriContext},
+    AppBuilder,
+  };
+
+  #[test]
+  fn sets_values_and_emits_changes() {
+    let dir = std::env::temp_dir().join("tauri-store-endpoint");
+    let _ = std::fs::remove_dir_all(&dir);
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().store(StoreBuilder::new("preferences").dir(&dir)),
+    );
+
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Store","message":{"cmd":"set","store":"preferences","key":"theme","value":"dark"},"callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for(&handle, 0, |message| matches!(
+      message,
+      MockMessage::EvalScript(js) if js.contains("store-change") && js.contains("dark")
+    )));
+
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Store","message":{"cmd":"save","store":"preferences"},"callback":"saved","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(&handle, 0, "saved"));
+    let store = StoreBuilder::new("preferences").dir(&dir).build().unwrap();
+    assert_eq!(
+      store.get::<String>("theme").unwrap(),
+      Some("dark".to_string())
+    );
+
+    // store names can't escape the stores directory
+    handle
+      .invoke(
+        0,
+        r#"{"module":"Store","message":{"cmd":"keys","store":"../secrets"},"callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(&handle, 0, "invalid store name"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+}
@@ -1,3 +1,5 @@
+use crate::api::config::WindowConfig;
+
 use serde::Deserialize;
 
 /// The API descriptor.
@@ -6,6 +8,62 @@ use serde::Deserialize;
 pub enum Cmd {
   /// The set webview title API.
   SetTitle { title: String },
+  /// The set resizable API.
+  SetResizable { resizable: bool },
+  /// The set size API.
+  SetSize { width: f64, height: f64 },
+  /// The set min size API.
+  #[serde(rename_all = "camelCase")]
+  SetMinSize {
+    min_width: Option<f64>,
+    min_height: Option<f64>,
+  },
+  /// The set max size API.
+  #[serde(rename_all = "camelCase")]
+  SetMaxSize {
+    max_width: Option<f64>,
+    max_height: Option<f64>,
+  },
+  /// The set position API.
+  SetPosition { x: f64, y: f64 },
+  /// The set fullscreen API.
+  SetFullscreen { fullscreen: bool },
+  /// The set decorations API.
+  SetDecorations { decorations: bool },
+  /// The set always on top API.
+  #[serde(rename_all = "camelCase")]
+  SetAlwaysOnTop { always_on_top: bool },
+  /// The minimize API.
+  Minimize,
+  /// The unminimize API.
+  Unminimize,
+  /// The maximize API.
+  Maximize,
+  /// The unmaximize API.
+  Unmaximize,
+  /// The show API.
+  Show,
+  /// The hide API.
+  Hide,
+  /// The center API.
+  Center,
+  /// The start dragging API.
+  StartDragging,
+  /// The create window API.
+  CreateWindow {
+    options: Box<WindowConfig>,
+    callback: String,
+    error: String,
+  },
+  /// The set prevent close API.
+  SetPreventClose { prevent: bool },
+  /// The close window API.
+  /// Closes the current window if the label isn't set.
+  CloseWindow {
+    label: Option<String>,
+    callback: String,
+    error: String,
+  },
 }
 
 impl Cmd {
@@ -15,11 +73,306 @@ impl Cmd {
   ) -> crate::Result<()> {
     match self {
       Self::SetTitle { title } => {
-        webview_manager.current_webview()?.set_title(&title);
+        webview_manager.current_webview()?.set_title(&title)?;
         #[cfg(not(set_title))]
-        throw_allowlist_error(webview_manager, "title");
+        super::throw_allowlist_error(webview_manager, "title");
+      }
+      Self::SetResizable { resizable } => {
+        #[cfg(set_resizable)]
+        webview_manager
+          .current_webview()?
+          .set_resizable(resizable)?;
+        #[cfg(not(set_resizable))]
+        super::throw_allowlist_error(webview_manager, "setResizable");
+      }
+      Self::SetSize { width, height } => {
+        #[cfg(set_size)]
+        webview_manager.current_webview()?.set_size(width, height)?;
+        #[cfg(not(set_size))]
+        super::throw_allowlist_error(webview_manager, "setSize");
+      }
+      Self::SetMinSize {
+        min_width,
+        min_height,
+      } => {
+        #[cfg(set_min_size)]
+        webview_manager
+          .current_webview()?
+          .set_min_size(min_width, min_height)?;
+        #[cfg(not(set_min_size))]
+        super::throw_allowlist_error(webview_manager, "setMinSize");
+      }
+      Self::SetMaxSize {
+        max_width,
+        max_height,
+      } => {
+        #[cfg(set_max_size)]
+        webview_manager
+          .current_webview()?
+          .set_max_size(max_width, max_height)?;
+        #[cfg(not(set_max_size))]
+        super::throw_allowlist_error(webview_manager, "setMaxSize");
+      }
+      Self::SetPosition { x, y } => {
+        #[cfg(set_position)]
+        webview_manager.current_webview()?.set_position(x, y)?;
+        #[cfg(not(set_position))]
+        super::throw_allowlist_error(webview_manager, "setPosition");
+      }
+      Self::SetFullscreen { fullscreen } => {
+        #[cfg(set_fullscreen)]
+        webview_manager
+          .current_webview()?
+          .set_fullscreen(fullscreen)?;
+        #[cfg(not(set_fullscreen))]
+        super::throw_allowlist_error(webview_manager, "setFullscreen");
+      }
+      Self::SetDecorations { decorations } => {
+        #[cfg(set_decorations)]
+        webview_manager
+          .current_webview()?
+          .set_decorations(decorations)?;
+        #[cfg(not(set_decorations))]
+        super::throw_allowlist_error(webview_manager, "setDecorations");
+      }
+      Self::SetAlwaysOnTop { always_on_top } => {
+        #[cfg(set_always_on_top)]
+        webview_manager
+          .current_webview()?
+          .set_always_on_top(always_on_top)?;
+        #[cfg(not(set_always_on_top))]
+        super::throw_allowlist_error(webview_manager, "setAlwaysOnTop");
+      }
+      Self::Minimize => {
+        #[cfg(minimize_window)]
+        webview_manager.current_webview()?.minimize()?;
+        #[cfg(not(minimize_window))]
+        super::throw_allowlist_error(webview_manager, "minimizeWindow");
+      }
+      Self::Unminimize => {
+        #[cfg(unminimize_window)]
+        webview_manager.current_webview()?.unminimize()?;
+        #[cfg(not(unminimize_window))]
+        super::throw_allowlist_error(webview_manager, "unminimizeWindow");
+      }
+      Self::Maximize => {
+        #[cfg(maximize_window)]
+        webview_manager.current_webview()?.maximize()?;
+        #[cfg(not(maximize_window))]
+        super::throw_allowlist_error(webview_manager, "maximizeWindow");
+      }
+      Self::Unmaximize => {
+        #[cfg(unmaximize_window)]
+        webview_manager.current_webview()?.unmaximize()?;
+        #[cfg(not(unmaximize_window))]
+        super::throw_allowlist_error(webview_manager, "unmaximizeWindow");
+      }
+      Self::Show => {
+        #[cfg(show_window)]
+        webview_manager.current_webview()?.show()?;
+        #[cfg(not(show_window))]
+        super::throw_allowlist_error(webview_manager, "showWindow");
+      }
+      Self::Hide => {
+        #[cfg(hide_window)]
+        webview_manager.current_webview()?.hide()?;
+        #[cfg(not(hide_window))]
+        super::throw_allowlist_error(webview_manager, "hideWindow");
+      }
+      Self::Center => {
+        #[cfg(center_window)]
+        webview_manager.current_webview()?.center()?;
+        #[cfg(not(center_window))]
+        super::throw_allowlist_error(webview_manager, "centerWindow");
+      }
+      Self::StartDragging => {
+        #[cfg(start_dragging)]
+        webview_manager.current_webview()?.start_dragging()?;
+        #[cfg(not(start_dragging))]
+        super::throw_allowlist_error(webview_manager, "startDragging");
+      }
+      Self::CreateWindow {
+        options,
+        callback,
+        error,
+      } => {
+        #[cfg(create_window)]
+        create_window(webview_manager, *options, callback, error).await;
+        #[cfg(not(create_window))]
+        super::allowlist_error(webview_manager, error, "createWindow");
+      }
+      Self::SetPreventClose { prevent } => {
+        #[cfg(set_prevent_close)]
+        webview_manager
+          .current_webview()?
+          .set_prevent_close(prevent);
+        #[cfg(not(set_prevent_close))]
+        super::throw_allowlist_error(webview_manager, "setPreventClose");
+      }
+      Self::CloseWindow {
+        label,
+        callback,
+        error,
+      } => {
+        #[cfg(close_window)]
+        close_window(webview_manager, label, callback, error).await;
+        #[cfg(not(close_window))]
+        super::allowlist_error(webview_manager, error, "closeWindow");
       }
     }
     Ok(())
   }
 }
+
+/// Creates a new window.
+///
+/// The new window can't have more IPC access than the calling window,
+/// and gets the app menu since its menu events are routed to the app.
+#[cfg(create_window)]
+pub async fn create_window<D: crate::ApplicationDispatcherExt + 'static>(
+  webview_manager: &crate::WebviewManager<D>,
+  mut options: WindowConfig,
+  callback: String,
+  error: String,
+) {
+  let webview_manager_ = webview_manager.clone();
+  crate::execute_promise(
+    webview_manager,
+    async move {
+      options.menu = None;
+      webview_manager_
+        .current_webview()?
+        .narrow_ipc_access(&mut options);
+      webview_manager_.create_window(options).map(|_| ())
+    },
+    callback,
+    error,
+  )
+  .await;
+}
+
+/// Closes a window.
+#[cfg(close_window)]
+pub async fn close_window<D: crate::ApplicationDispatcherExt + 'static>(
+  webview_manager: &crate::WebviewManager<D>,
+  label: Option<String>,
+  callback: String,
+  error: String,
+) {
+  let webview_manager_ = webview_manager.clone();
+  crate::execute_promise(
+    webview_manager,
+    async move {
+      let label = label.unwrap_or_else(|| webview_manager_.current_window_label().to_string());
+      webview_manager_.close_window(&label)
+    },
+    callback,
+    error,
+  )
+  .await;
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{
+    api::config::{WindowConfig, WindowUrl},
+    flavors::{mock::MockMessage, Mock},
+    test::{run, wait_for, wait_for_script, TauriContext},
+    AppBuilder, WindowMessage,
+  };
+
+  #[test]
+  fn sends_window_messages() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new());
+
+    let commands = vec![
+      (
+        r#"{"cmd":"setSize","width":400,"height":300}"#,
+        WindowMessage::SetSize {
+          width: 400.0,
+          height: 300.0,
+        },
+      ),
+      (
+        r#"{"cmd":"setMinSize","minWidth":200}"#,
+        WindowMessage::SetMinSize {
+          min_width: Some(200.0),
+          min_height: None,
+        },
+      ),
+      (
+        r#"{"cmd":"setAlwaysOnTop","alwaysOnTop":true}"#,
+        WindowMessage::SetAlwaysOnTop(true),
+      ),
+      (r#"{"cmd":"maximize"}"#, WindowMessage::Maximize),
+      (r#"{"cmd":"startDragging"}"#, WindowMessage::StartDragging),
+    ];
+
+    for (command, expected) in commands {
+      handle
+        .invoke(0, format!(r#"{{"module":"Window","message":{}}}"#, command))
+        .unwrap();
+      let expected = MockMessage::Window(expected);
+      assert!(
+        wait_for(&handle, 0, |message| *message == expected),
+        "{} didn't send {:?}",
+        command,
+        expected
+      );
+    }
+  }
+
+  #[test]
+  fn narrows_the_ipc_access_of_created_windows() {
+    let handle = run(
+      AppBuilder::<Mock, TauriContext>::new().setup(|webview_manager| async move {
+        webview_manager
+          .create_window(WindowConfig {
+            label: "remote".to_string(),
+            url: WindowUrl::Custom("https://tauri.studio/docs".to_string()),
+            trusted_origins: vec!["https://tauri.studio".to_string()],
+            remote_api: vec!["Window".to_string()],
+            ..Default::default()
+          })
+          .unwrap();
+        webview_manager.current_webview().unwrap().eval("created");
+      }),
+    );
+    handle.invoke(0, r#"{"cmd":"__initialized"}"#).unwrap();
+    assert!(wait_for_script(&handle, 0, "created"));
+
+    // the remote page asks for more access than it has
+    handle
+      .invoke(
+        1,
+        r#"{"module":"Window","message":{"cmd":"createWindow","options":{"label":"child","url":"https://tauri.studio/child","trustedOrigins":["https://tauri.studio","https://example.com"],"remoteApi":["Window","Fs"],"menu":[{"id":"quit","title":"Quit"}]},"callback":"childCreated","error":"childFailed"}}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(&handle, 1, "childCreated"));
+    assert_eq!(handle.windows()[2].attributes.menu, None);
+
+    handle
+      .invoke(
+        2,
+        r#"{"module":"Fs","message":{"cmd":"readDir","path":""},"callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      2,
+      "the `Fs` API can't be called from the remote origin `https://tauri.studio`"
+    ));
+    handle.navigate(2, "https://example.com/");
+    handle
+      .invoke(
+        2,
+        r#"{"module":"Window","message":{"cmd":"maximize"},"callback":"resolve","error":"reject"}"#,
+      )
+      .unwrap();
+    assert!(wait_for_script(
+      &handle,
+      2,
+      "IPC calls from the `https://example.com` origin are not allowed"
+    ));
+  }
+}
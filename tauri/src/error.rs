@@ -7,9 +7,21 @@ pub enum Error {
   /// Failed to create window.
   #[error("failed to create window")]
   CreateWindow,
+  /// The webview runtime doesn't provide the feature.
+  #[error("{0} is not supported by the webview runtime")]
+  UnsupportedByRuntime(&'static str),
+  /// The event loop stopped, so it can't handle the messages of the windows.
+  #[error("the event loop is closed")]
+  EventLoopClosed,
   /// Can't access webview dispatcher because the webview was closed or not found.
   #[error("webview not found: invalid label or it was closed")]
   WebviewNotFound,
+  /// A window with the same label is already open.
+  #[error("a window with label `{0}` already exists")]
+  WindowLabelAlreadyExists(String),
+  /// The state of the given type was never managed by the app.
+  #[error("state of type `{0}` is not managed, call `AppBuilder::manage` with it")]
+  StateNotManaged(&'static str),
   /// Embedded asset not found.
   #[error("asset not found: {0}")]
   AssetNotFound(String),
@@ -22,9 +34,88 @@ pub enum Error {
   /// Unknown API type.
   #[error("unknown API: {0:?}")]
   UnknownApi(Option<serde_json::Error>),
+  /// No command is registered with the given name.
+  #[error("unknown command `{0}`")]
+  UnknownCommand(String),
+  /// A command with the same name was already registered.
+  #[error("command `{0}` is already registered")]
+  CommandAlreadyRegistered(String),
+  /// A plugin with the same name was already registered.
+  #[error("plugin `{0}` is already registered")]
+  PluginAlreadyRegistered(&'static str),
+  /// A protocol with the same URI scheme was already registered.
+  #[error("URI scheme `{0}` is already registered")]
+  UriSchemeAlreadyRegistered(String),
+  /// No protocol is registered with the given URI scheme.
+  #[error("no protocol is registered with the URI scheme `{0}`")]
+  UnknownUriScheme(String),
+  /// The accelerator of a global shortcut is invalid.
+  #[error("invalid accelerator: {0}")]
+  InvalidAccelerator(#[from] crate::global_shortcut::AcceleratorParseError),
+  /// The global shortcut is already registered.
+  #[error("global shortcut `{0}` is already registered")]
+  GlobalShortcutAlreadyRegistered(String),
+  /// The OS didn't register the global shortcut, e.g. because another app uses it.
+  #[error("failed to register global shortcut `{0}`")]
+  RegisterGlobalShortcut(String),
+  /// The global shortcut isn't registered.
+  #[error("global shortcut `{0}` is not registered")]
+  GlobalShortcutNotRegistered(String),
+  /// The arguments of the command are invalid, or the named argument if known.
+  #[error(
+    "invalid {} for command `{0}`: {2}",
+    .1.as_ref().map(|arg| format!("argument `{}`", arg)).unwrap_or_else(|| "args".to_string())
+  )]
+  InvalidArgs(String, Option<String>, serde_json::Error),
+  /// The page that invoked the app was loaded from an origin the window doesn't trust.
+  #[error(
+    "IPC calls from the `{0}` origin are not allowed, add it to the window `trustedOrigins`"
+  )]
+  IpcOriginNotTrusted(String),
+  /// A trusted remote origin called an API module it can't access.
+  #[error(
+    "the `{0}` API can't be called from the remote origin `{1}`, add it to the window `remoteApi`"
+  )]
+  RemoteApiNotAllowed(String, String),
+  /// The path isn't on the file system scope.
+  #[error(
+    "path `{}` is not allowed on the file system scope, add it to `tauri > fs > scope`",
+    .0.display()
+  )]
+  PathNotAllowed(std::path::PathBuf),
+  /// The shell command isn't on the shell scope.
+  #[error("shell command `{0}` is not allowed, add it to `tauri > shell > scope`")]
+  ShellCommandNotAllowed(String),
+  /// The shell command was called with the wrong number of arguments.
+  #[error("shell command `{0}` expects {1} arguments, got {2}")]
+  InvalidShellArgs(String, usize, usize),
+  /// An argument of the shell command doesn't match its validator.
+  #[error("argument {1} of shell command `{0}` doesn't match the `{2}` validator")]
+  InvalidShellArg(String, usize, String),
+  /// A validator of the shell scope isn't a valid regex.
+  #[error("invalid shell argument validator `{0}`: {1}")]
+  InvalidShellValidator(String, regex::Error),
+  /// The URL isn't on the HTTP scope.
+  #[error("URL `{0}` is not allowed, add it to `tauri > http > scope`")]
+  UrlNotAllowed(String),
+  /// The HTTP request skips the TLS certificate verification without the config allowing it.
+  #[error("the `acceptInvalidCerts` option requires `tauri > http > dangerousAllowInvalidCerts`")]
+  InvalidCertsNotAllowed,
+  /// The store name can't be used as a file name.
+  #[error("invalid store name `{0}`, it can only contain ASCII letters, digits, `-`, `_` and `.`")]
+  InvalidStoreName(String),
+  /// A store with the same name was already registered.
+  #[error("store `{0}` is already registered")]
+  StoreAlreadyRegistered(String),
+  /// The store file was saved by a newer version of the app.
+  #[error("store `{0}` was saved with the unsupported version {1}")]
+  StoreVersionNotSupported(String, u32),
   /// Failed to execute tauri API.
   #[error("failed to execute API: {0}")]
   FailedToExecuteApi(#[from] tauri_api::Error),
+  /// A task spawned on the async runtime panicked or was cancelled.
+  #[error("async task failed: {0}")]
+  AsyncTask(#[from] crate::async_runtime::JoinError),
   /// IO error.
   #[error("{0}")]
   Io(#[from] std::io::Error),
@@ -35,10 +126,6 @@ pub enum Error {
 
 impl From<serde_json::Error> for Error {
   fn from(error: serde_json::Error) -> Self {
-    if error.to_string().contains("unknown variant") {
-      Self::UnknownApi(Some(error))
-    } else {
-      Self::Json(error)
-    }
+    Self::Json(error)
   }
 }
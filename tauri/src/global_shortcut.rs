@@ -0,0 +1,425 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+/// The modifier keys of an accelerator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+  /// The Control key.
+  pub ctrl: bool,
+  /// The Alt key, Option on macOS.
+  pub alt: bool,
+  /// The Shift key.
+  pub shift: bool,
+  /// The Super key, Command on macOS and Windows on Windows.
+  pub super_key: bool,
+}
+
+/// The main key of an accelerator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+  /// A letter, digit or punctuation key. Letters are uppercase.
+  Char(char),
+  /// A function key, from `F1` to `F24`.
+  F(u8),
+  /// The space bar.
+  Space,
+  /// The Tab key.
+  Tab,
+  /// The Enter key.
+  Enter,
+  /// The Escape key.
+  Escape,
+  /// The Backspace key.
+  Backspace,
+  /// The Delete key.
+  Delete,
+  /// The Insert key.
+  Insert,
+  /// The Home key.
+  Home,
+  /// The End key.
+  End,
+  /// The Page Up key.
+  PageUp,
+  /// The Page Down key.
+  PageDown,
+  /// The up arrow key.
+  Up,
+  /// The down arrow key.
+  Down,
+  /// The left arrow key.
+  Left,
+  /// The right arrow key.
+  Right,
+}
+
+/// A keyboard shortcut, e.g. `CmdOrCtrl+Shift+K`.
+///
+/// The modifiers come first and the key last, separated by `+`. The names are case insensitive
+/// and `CmdOrCtrl` stands for Command on macOS and Control elsewhere. The `+` key is named `Plus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+  /// The modifier keys.
+  pub modifiers: Modifiers,
+  /// The main key.
+  pub key: Key,
+}
+
+/// An error parsing an accelerator. The positions are byte offsets in the accelerator string.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AcceleratorParseError {
+  /// The accelerator is empty.
+  #[error("the accelerator is empty")]
+  Empty,
+  /// There's nothing between two `+` separators, or before or after one.
+  #[error("missing key or modifier at position {position}")]
+  EmptyToken {
+    /// The position of the missing token.
+    position: usize,
+  },
+  /// The token isn't a known modifier or key.
+  #[error("unknown key or modifier `{token}` at position {position}")]
+  UnknownToken {
+    /// The unknown token.
+    token: String,
+    /// The position of the token.
+    position: usize,
+  },
+  /// The modifier is used more than once.
+  #[error("modifier `{modifier}` at position {position} is used more than once")]
+  DuplicateModifier {
+    /// The repeated modifier.
+    modifier: String,
+    /// The position of the repeated modifier.
+    position: usize,
+  },
+  /// A token follows the key.
+  #[error("`{token}` at position {position} follows the key, which must come last")]
+  TokenAfterKey {
+    /// The token following the key.
+    token: String,
+    /// The position of the token.
+    position: usize,
+  },
+  /// The accelerator only has modifiers.
+  #[error("the accelerator has no key")]
+  MissingKey,
+}
+
+enum Modifier {
+  Ctrl,
+  Alt,
+  Shift,
+  Super,
+}
+
+impl Modifier {
+  fn flag(self, modifiers: &mut Modifiers) -> &mut bool {
+    match self {
+      Self::Ctrl => &mut modifiers.ctrl,
+      Self::Alt => &mut modifiers.alt,
+      Self::Shift => &mut modifiers.shift,
+      Self::Super => &mut modifiers.super_key,
+    }
+  }
+}
+
+enum Token {
+  Modifier(Modifier),
+  Key(Key),
+}
+
+fn parse_token(token: &str) -> Option<Token> {
+  let lowercase = token.to_lowercase();
+  let modifier = match lowercase.as_str() {
+    "ctrl" | "control" => Modifier::Ctrl,
+    "alt" | "option" => Modifier::Alt,
+    "shift" => Modifier::Shift,
+    "super" | "cmd" | "command" | "meta" => Modifier::Super,
+    "cmdorctrl" | "commandorcontrol" if cfg!(target_os = "macos") => Modifier::Super,
+    "cmdorctrl" | "commandorcontrol" => Modifier::Ctrl,
+    _ => return parse_key(&lowercase).map(Token::Key),
+  };
+  Some(Token::Modifier(modifier))
+}
+
+fn parse_key(key: &str) -> Option<Key> {
+  let mut chars = key.chars();
+  if let (Some(c), None) = (chars.next(), chars.next()) {
+    return if c.is_ascii_alphanumeric() || ",.;'[]\\/`-=".contains(c) {
+      Some(Key::Char(c.to_ascii_uppercase()))
+    } else {
+      None
+    };
+  }
+  let key = match key {
+    "plus" => Key::Char('+'),
+    "space" => Key::Space,
+    "tab" => Key::Tab,
+    "enter" | "return" => Key::Enter,
+    "escape" | "esc" => Key::Escape,
+    "backspace" => Key::Backspace,
+    "delete" | "del" => Key::Delete,
+    "insert" => Key::Insert,
+    "home" => Key::Home,
+    "end" => Key::End,
+    "pageup" => Key::PageUp,
+    "pagedown" => Key::PageDown,
+    "up" => Key::Up,
+    "down" => Key::Down,
+    "left" => Key::Left,
+    "right" => Key::Right,
+    function_key => {
+      let number: u8 = function_key.strip_prefix('f')?.parse().ok()?;
+      if (1..=24).contains(&number) {
+        Key::F(number)
+      } else {
+        return None;
+      }
+    }
+  };
+  Some(key)
+}
+
+impl FromStr for Accelerator {
+  type Err = AcceleratorParseError;
+
+  fn from_str(accelerator: &str) -> Result<Self, Self::Err> {
+    if accelerator.trim().is_empty() {
+      return Err(AcceleratorParseError::Empty);
+    }
+    let mut modifiers = Modifiers::default();
+    let mut key = None;
+    let mut position = 0;
+    for raw_token in accelerator.split('+') {
+      let token = raw_token.trim();
+      let token_position = position + raw_token.find(token).unwrap_or(0);
+      position += raw_token.len() + 1;
+      if token.is_empty() {
+        return Err(AcceleratorParseError::EmptyToken {
+          position: token_position,
+        });
+      }
+      if key.is_some() {
+        return Err(AcceleratorParseError::TokenAfterKey {
+          token: token.to_string(),
+          position: token_position,
+        });
+      }
+      match parse_token(token) {
+        Some(Token::Modifier(modifier)) => {
+          let pressed = modifier.flag(&mut modifiers);
+          if *pressed {
+            return Err(AcceleratorParseError::DuplicateModifier {
+              modifier: token.to_string(),
+              position: token_position,
+            });
+          }
+          *pressed = true;
+        }
+        Some(Token::Key(k)) => key = Some(k),
+        None => {
+          return Err(AcceleratorParseError::UnknownToken {
+            token: token.to_string(),
+            position: token_position,
+          })
+        }
+      }
+    }
+    key
+      .map(|key| Self { modifiers, key })
+      .ok_or(AcceleratorParseError::MissingKey)
+  }
+}
+
+impl fmt::Display for Key {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Char('+') => write!(f, "Plus"),
+      Self::Char(c) => write!(f, "{}", c),
+      Self::F(number) => write!(f, "F{}", number),
+      key => write!(f, "{:?}", key),
+    }
+  }
+}
+
+/// Formats the accelerator in its canonical form, e.g. `Ctrl+Shift+K`.
+impl fmt::Display for Accelerator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let modifiers = [
+      (self.modifiers.ctrl, "Ctrl"),
+      (self.modifiers.alt, "Alt"),
+      (self.modifiers.shift, "Shift"),
+      (self.modifiers.super_key, "Super"),
+    ];
+    for (_, name) in modifiers.iter().filter(|(pressed, _)| *pressed) {
+      write!(f, "{}+", name)?;
+    }
+    write!(f, "{}", self.key)
+  }
+}
+
+/// The registered global shortcuts and their handlers.
+pub(crate) struct ShortcutRegistry<H>(HashMap<Accelerator, H>);
+
+impl<H> Default for ShortcutRegistry<H> {
+  fn default() -> Self {
+    Self(HashMap::new())
+  }
+}
+
+impl<H> ShortcutRegistry<H> {
+  /// Registers the handler of the accelerator.
+  /// Fails if the accelerator is already registered.
+  pub(crate) fn register(&mut self, accelerator: Accelerator, handler: H) -> crate::Result<()> {
+    if self.0.contains_key(&accelerator) {
+      return Err(crate::Error::GlobalShortcutAlreadyRegistered(
+        accelerator.to_string(),
+      ));
+    }
+    self.0.insert(accelerator, handler);
+    Ok(())
+  }
+
+  /// Unregisters the accelerator, returning its handler.
+  /// Fails if the accelerator isn't registered.
+  pub(crate) fn unregister(&mut self, accelerator: &Accelerator) -> crate::Result<H> {
+    self
+      .0
+      .remove(accelerator)
+      .ok_or_else(|| crate::Error::GlobalShortcutNotRegistered(accelerator.to_string()))
+  }
+
+  /// Whether the accelerator is registered.
+  pub(crate) fn is_registered(&self, accelerator: &Accelerator) -> bool {
+    self.0.contains_key(accelerator)
+  }
+
+  /// The handler of the accelerator, if it's registered.
+  pub(crate) fn get(&self, accelerator: &Accelerator) -> Option<&H> {
+    self.0.get(accelerator)
+  }
+
+  /// Unregisters the accelerators whose handler matches the predicate, returning them.
+  pub(crate) fn unregister_matching<F: Fn(&H) -> bool>(
+    &mut self,
+    predicate: F,
+  ) -> Vec<Accelerator> {
+    let accelerators: Vec<Accelerator> = self
+      .0
+      .iter()
+      .filter(|(_, handler)| predicate(handler))
+      .map(|(accelerator, _)| *accelerator)
+      .collect();
+    for accelerator in &accelerators {
+      self.0.remove(accelerator);
+    }
+    accelerators
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Accelerator, AcceleratorParseError, Key, Modifiers, ShortcutRegistry};
+
+  fn parse(accelerator: &str) -> Result<Accelerator, AcceleratorParseError> {
+    accelerator.parse()
+  }
+
+  #[test]
+  fn parses_accelerators() {
+    let accelerator = parse("CmdOrCtrl+Shift+K").unwrap();
+    assert_eq!(accelerator.key, Key::Char('K'));
+    assert!(accelerator.modifiers.shift);
+    assert_eq!(accelerator.modifiers.super_key, cfg!(target_os = "macos"));
+    assert_eq!(accelerator.modifiers.ctrl, !cfg!(target_os = "macos"));
+
+    assert_eq!(
+      parse("alt + f12").unwrap(),
+      Accelerator {
+        modifiers: Modifiers {
+          alt: true,
+          ..Default::default()
+        },
+        key: Key::F(12),
+      }
+    );
+    assert_eq!(parse("Ctrl+Plus").unwrap().key, Key::Char('+'));
+    assert_eq!(parse("PageDown").unwrap().key, Key::PageDown);
+    assert_eq!(
+      parse("shift+ctrl+super+alt+esc").unwrap().to_string(),
+      "Ctrl+Alt+Shift+Super+Escape"
+    );
+    assert_eq!(parse("Ctrl+Plus").unwrap().to_string(), "Ctrl+Plus");
+  }
+
+  #[test]
+  fn reports_parse_errors() {
+    assert_eq!(parse(" "), Err(AcceleratorParseError::Empty));
+    assert_eq!(
+      parse("Ctrl++K"),
+      Err(AcceleratorParseError::EmptyToken { position: 5 })
+    );
+    assert_eq!(
+      parse("Ctrl+Shift+"),
+      Err(AcceleratorParseError::EmptyToken { position: 11 })
+    );
+    assert_eq!(
+      parse("Ctrl+Hyper+K"),
+      Err(AcceleratorParseError::UnknownToken {
+        token: "Hyper".to_string(),
+        position: 5
+      })
+    );
+    assert_eq!(
+      parse("Ctrl+F25"),
+      Err(AcceleratorParseError::UnknownToken {
+        token: "F25".to_string(),
+        position: 5
+      })
+    );
+    assert_eq!(
+      parse("Ctrl+Control+K"),
+      Err(AcceleratorParseError::DuplicateModifier {
+        modifier: "Control".to_string(),
+        position: 5
+      })
+    );
+    assert_eq!(
+      parse("K+Shift"),
+      Err(AcceleratorParseError::TokenAfterKey {
+        token: "Shift".to_string(),
+        position: 2
+      })
+    );
+    assert_eq!(parse("Ctrl+Shift"), Err(AcceleratorParseError::MissingKey));
+  }
+
+  #[test]
+  fn tracks_registered_shortcuts() {
+    let mut registry = ShortcutRegistry::default();
+    let accelerator = parse("Ctrl+K").unwrap();
+    registry.register(accelerator, 1).unwrap();
+    assert!(registry.is_registered(&parse("control+k").unwrap()));
+    assert!(matches!(
+      registry.register(accelerator, 2),
+      Err(crate::Error::GlobalShortcutAlreadyRegistered(a)) if a == "Ctrl+K"
+    ));
+    assert_eq!(registry.get(&accelerator), Some(&1));
+
+    assert_eq!(registry.unregister(&accelerator).unwrap(), 1);
+    assert!(!registry.is_registered(&accelerator));
+    assert!(matches!(
+      registry.unregister(&accelerator),
+      Err(crate::Error::GlobalShortcutNotRegistered(_))
+    ));
+
+    registry.register(accelerator, 1).unwrap();
+    registry.register(parse("Ctrl+L").unwrap(), 2).unwrap();
+    assert_eq!(
+      registry.unregister_matching(|handler| *handler == 1),
+      vec![accelerator]
+    );
+    assert!(!registry.is_registered(&accelerator));
+    assert!(registry.is_registered(&parse("Ctrl+L").unwrap()));
+  }
+}
@@ -9,7 +9,7 @@
 /// The embedded server helpers.
 #[cfg(embedded_server)]
 pub mod server;
-/// The Tauri-specific settings for your app e.g. notification permission status.
+/// The Tauri-specific settings for your app e.g. notification permission status, and the persistent key-value stores.
 pub mod settings;
 
 /// The webview application entry.
@@ -21,6 +21,11 @@ mod error;
 pub mod plugin;
 /// The salt helpers.
 mod salt;
+/// The scopes of the API endpoints.
+mod scope;
+/// The fixtures and helpers shared by the tests.
+#[cfg(test)]
+mod test;
 /// Webview interface.
 mod webview;
 
@@ -29,21 +34,39 @@ pub use error::Error;
 /// Tauri result type.
 pub type Result<T> = std::result::Result<T, Error>;
 
-pub(crate) mod async_runtime;
+/// The async runtime running the Tauri tasks.
+pub mod async_runtime;
+/// The global keyboard shortcuts.
+pub mod global_shortcut;
 
 /// A task to run on the main thread.
 pub type SyncTask = Box<dyn FnOnce() + Send>;
 
 pub use app::*;
 pub use tauri_api as api;
-pub use tauri_macros::FromTauriContext;
+pub use tauri_api::menu::{Menu, MenuEntry, MenuItem, NativeMenuItem, Submenu};
+pub use tauri_macros::{command, generate_handler, FromTauriContext};
 pub use webview::{
-  ApplicationDispatcherExt, ApplicationExt, Callback, WebviewBuilderExt, WindowBuilderExt,
+  ApplicationDispatcherExt, ApplicationExt, Callback, CloseRequest, GlobalShortcutHandler,
+  GlobalShortcutMessage, Message, SystemTray, SystemTrayEvent, SystemTrayEventHandler,
+  SystemTrayMessage, UriSchemeProtocol, UriSchemeResponse, WebviewBuilderExt, WindowBuilderExt,
+  WindowEvent, WindowEventHandler, WindowMessage,
 };
 
 /// The Tauri webview implementations.
 pub mod flavors {
+  #[cfg(any(test, feature = "mock"))]
+  pub use super::webview::mock::MockApplication as Mock;
   pub use super::webview::wry::WryApplication as Wry;
+
+  /// Helpers to inspect and drive the [`Mock`] flavor on tests.
+  #[cfg(any(test, feature = "mock"))]
+  pub mod mock {
+    pub use crate::webview::mock::{
+      MockDispatcher, MockHandle, MockMessage, MockUriSchemeProtocols, MockWebviewAttributes,
+      MockWindow, MockWindowAttributes, MockWindowId,
+    };
+  }
 }
 
 use std::process::Stdio;
@@ -121,7 +144,12 @@ pub async fn call<D: ApplicationDispatcherExt>(
 ) {
   execute_promise(
     webview_manager,
-    async move { api::command::get_output(command, args, Stdio::piped()).map_err(|e| e.into()) },
+    async move {
+      // the command blocks until it exits
+      async_runtime::spawn_blocking(move || api::command::get_output(command, args, Stdio::piped()))
+        .await?
+        .map_err(|e| e.into())
+    },
     callback,
     error,
   )
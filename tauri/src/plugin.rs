@@ -4,7 +4,7 @@ use crate::{
 
 use futures::future::join_all;
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 /// The plugin interface.
 #[async_trait::async_trait]
@@ -35,7 +35,11 @@ pub trait Plugin<D: ApplicationDispatcherExt + 'static>: Send + Sync {
   #[allow(unused_variables)]
   async fn ready(&mut self, webview_manager: WebviewManager<D>) {}
 
+  /// Callback invoked before the app exits, to release the plugin resources.
+  async fn exit(&mut self) {}
+
   /// Add invoke_handler API extension commands.
+  /// Receives the payloads invoked with `plugin:<name>|<command>`, with the `cmd` set to `<command>`.
   #[allow(unused_variables)]
   async fn extend_api(
     &mut self,
@@ -46,28 +50,78 @@ pub trait Plugin<D: ApplicationDispatcherExt + 'static>: Send + Sync {
   }
 }
 
+/// A registered plugin, locked while one of its hooks runs.
+type PluginHandle<D> = Arc<Mutex<Box<dyn Plugin<D> + Sync + Send>>>;
+
+/// The registered plugins, in registration order and indexed by name.
+///
+/// The hooks run on handles cloned out of the collection, so a plugin can exit the app
+/// or reach the other plugins while the collection isn't locked.
+pub struct PluginCollection<D: ApplicationDispatcherExt + 'static> {
+  plugins: Vec<PluginHandle<D>>,
+  index: HashMap<&'static str, usize>,
+}
+
+impl<D: ApplicationDispatcherExt + 'static> Default for PluginCollection<D> {
+  fn default() -> Self {
+    Self {
+      plugins: Vec::new(),
+      index: HashMap::new(),
+    }
+  }
+}
+
+impl<D: ApplicationDispatcherExt + 'static> PluginCollection<D> {
+  /// Adds a plugin, failing if a plugin with the same name is already registered.
+  pub(crate) fn add(
+    &mut self,
+    plugin: impl Plugin<D> + Sync + Send + 'static,
+  ) -> crate::Result<()> {
+    let name = plugin.name();
+    if self.index.contains_key(name) {
+      return Err(crate::Error::PluginAlreadyRegistered(name));
+    }
+    self.index.insert(name, self.plugins.len());
+    self.plugins.push(Arc::new(Mutex::new(Box::new(plugin))));
+    Ok(())
+  }
+
+  fn get(&self, name: &str) -> Option<PluginHandle<D>> {
+    let index = *self.index.get(name)?;
+    self.plugins.get(index).cloned()
+  }
+}
+
 /// Plugin collection type.
-pub type PluginStore<D> = Arc<Mutex<Vec<Box<dyn Plugin<D> + Sync + Send>>>>;
+pub type PluginStore<D> = Arc<Mutex<PluginCollection<D>>>;
+
+/// Clones the handles of the plugins, releasing the store lock before their hooks run.
+async fn handles<D: ApplicationDispatcherExt + 'static>(
+  store: &PluginStore<D>,
+) -> Vec<PluginHandle<D>> {
+  store.lock().await.plugins.clone()
+}
 
-/// Registers a plugin.
+/// Registers a plugin, failing if a plugin with the same name is already registered.
 pub async fn register<D: ApplicationDispatcherExt + 'static>(
   store: &PluginStore<D>,
   plugin: impl Plugin<D> + Sync + Send + 'static,
-) {
-  let mut plugins = store.lock().await;
-  plugins.push(Box::new(plugin));
+) -> crate::Result<()> {
+  store.lock().await.add(plugin)
 }
 
 pub(crate) async fn initialize<D: ApplicationDispatcherExt + 'static>(
   store: &PluginStore<D>,
   plugins_config: PluginConfig,
 ) -> crate::Result<()> {
-  let mut plugins = store.lock().await;
-  let mut futures = Vec::new();
-  for plugin in plugins.iter_mut() {
-    let plugin_config = plugins_config.get(plugin.name());
-    futures.push(plugin.initialize(plugin_config));
-  }
+  let futures = handles(store).await.into_iter().map(|plugin| {
+    let plugins_config = &plugins_config;
+    async move {
+      let mut plugin = plugin.lock().await;
+      let plugin_config = plugins_config.get(plugin.name());
+      plugin.initialize(plugin_config).await
+    }
+  });
 
   for res in join_all(futures).await {
     res?;
@@ -79,11 +133,10 @@ pub(crate) async fn initialize<D: ApplicationDispatcherExt + 'static>(
 pub(crate) async fn initialization_script<D: ApplicationDispatcherExt + 'static>(
   store: &PluginStore<D>,
 ) -> String {
-  let mut plugins = store.lock().await;
-  let mut futures = Vec::new();
-  for plugin in plugins.iter_mut() {
-    futures.push(plugin.initialization_script());
-  }
+  let futures = handles(store)
+    .await
+    .into_iter()
+    .map(|plugin| async move { plugin.lock().await.initialization_script().await });
 
   let mut initialization_script = String::new();
   for res in join_all(futures).await {
@@ -101,11 +154,10 @@ pub(crate) async fn created<D: ApplicationDispatcherExt + 'static>(
   store: &PluginStore<D>,
   webview_manager: &crate::WebviewManager<D>,
 ) {
-  let mut plugins = store.lock().await;
-  let mut futures = Vec::new();
-  for plugin in plugins.iter_mut() {
-    futures.push(plugin.created(webview_manager.clone()));
-  }
+  let futures = handles(store)
+    .await
+    .into_iter()
+    .map(|plugin| async move { plugin.lock().await.created(webview_manager.clone()).await });
   join_all(futures).await;
 }
 
@@ -113,30 +165,37 @@ pub(crate) async fn ready<D: ApplicationDispatcherExt + 'static>(
   store: &PluginStore<D>,
   webview_manager: &crate::WebviewManager<D>,
 ) {
-  let mut plugins = store.lock().await;
-  let mut futures = Vec::new();
-  for plugin in plugins.iter_mut() {
-    futures.push(plugin.ready(webview_manager.clone()));
-  }
+  let futures = handles(store)
+    .await
+    .into_iter()
+    .map(|plugin| async move { plugin.lock().await.ready(webview_manager.clone()).await });
   join_all(futures).await;
 }
 
+pub(crate) async fn exit<D: ApplicationDispatcherExt + 'static>(store: &PluginStore<D>) {
+  let futures = handles(store)
+    .await
+    .into_iter()
+    .map(|plugin| async move { plugin.lock().await.exit().await });
+  join_all(futures).await;
+}
+
+/// Passes the payload to the plugin with the given name.
 pub(crate) async fn extend_api<D: ApplicationDispatcherExt + 'static>(
   store: &PluginStore<D>,
   webview_manager: &crate::WebviewManager<D>,
-  arg: &str,
-) -> crate::Result<bool> {
-  let mut plugins = store.lock().await;
-  for ext in plugins.iter_mut() {
-    match ext.extend_api(webview_manager.clone(), arg).await {
-      Ok(_) => {
-        return Ok(true);
-      }
-      Err(e) => match e {
-        crate::Error::UnknownApi(_) => {}
-        _ => return Err(e),
-      },
+  plugin_name: &str,
+  payload: &str,
+) -> crate::Result<()> {
+  let plugin = store.lock().await.get(plugin_name);
+  match plugin {
+    Some(plugin) => {
+      plugin
+        .lock()
+        .await
+        .extend_api(webview_manager.clone(), payload)
+        .await
     }
+    None => Err(crate::Error::UnknownApi(None)),
   }
-  Ok(false)
 }
@@ -1,6 +1,5 @@
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-use lazy_static::lazy_static;
 use uuid::Uuid;
 
 /// A salt definition.
@@ -9,47 +8,54 @@ struct Salt {
   one_time: bool,
 }
 
-lazy_static! {
-  static ref SALTS: Mutex<Vec<Salt>> = Mutex::new(vec![]);
-}
+/// The salts generated by an app.
+#[derive(Clone, Default)]
+pub(crate) struct SaltStore(Arc<Mutex<Vec<Salt>>>);
 
-/// Generates a one time Salt and returns its string representation.
-pub fn generate() -> String {
-  let salt = Uuid::new_v4();
-  SALTS
-    .lock()
-    .expect("Failed to lock Salt mutex: generate()")
-    .push(Salt {
-      value: salt.to_string(),
-      one_time: true,
-    });
-  salt.to_string()
-}
+impl SaltStore {
+  /// Generates a one time Salt and returns its string representation.
+  pub(crate) fn generate(&self) -> String {
+    let salt = Uuid::new_v4();
+    self
+      .0
+      .lock()
+      .expect("Failed to lock Salt mutex: generate()")
+      .push(Salt {
+        value: salt.to_string(),
+        one_time: true,
+      });
+    salt.to_string()
+  }
 
-/// Generates a static Salt and returns its string representation.
-#[allow(dead_code)]
-pub fn generate_static() -> String {
-  let salt = Uuid::new_v4();
-  SALTS
-    .lock()
-    .expect("Failed to lock SALT mutex: generate_static()")
-    .push(Salt {
-      value: salt.to_string(),
-      one_time: false,
-    });
-  salt.to_string()
-}
+  /// Generates a static Salt and returns its string representation.
+  #[allow(dead_code)]
+  pub(crate) fn generate_static(&self) -> String {
+    let salt = Uuid::new_v4();
+    self
+      .0
+      .lock()
+      .expect("Failed to lock SALT mutex: generate_static()")
+      .push(Salt {
+        value: salt.to_string(),
+        one_time: false,
+      });
+    salt.to_string()
+  }
 
-/// Checks if the given Salt representation is valid.
-pub fn is_valid(salt: String) -> bool {
-  let mut salts = SALTS.lock().expect("Failed to lock Salt mutex: is_valid()");
-  match salts.iter().position(|s| s.value == salt) {
-    Some(index) => {
-      if salts[index].one_time {
-        salts.remove(index);
+  /// Checks if the given Salt representation is valid.
+  pub(crate) fn is_valid(&self, salt: String) -> bool {
+    let mut salts = self
+      .0
+      .lock()
+      .expect("Failed to lock Salt mutex: is_valid()");
+    match salts.iter().position(|s| s.value == salt) {
+      Some(index) => {
+        if salts[index].one_time {
+          salts.remove(index);
+        }
+        true
       }
-      true
+      None => false,
     }
-    None => false,
   }
 }
@@ -0,0 +1,11 @@
+mod fs;
+#[cfg(http_request)]
+mod http;
+#[cfg(execute)]
+mod shell;
+
+pub(crate) use fs::FsScope;
+#[cfg(http_request)]
+pub(crate) use http::HttpScope;
+#[cfg(execute)]
+pub(crate) use shell::ShellScope;
@@ -0,0 +1,282 @@
+use crate::api::path::{resolve_path, BaseDirectory};
+
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
+
+/// Matches the path against the glob pattern.
+/// `*` matches any sequence of characters in a path segment, `?` matches a single one,
+/// `**` matches any sequence of characters across segments and `**/` matches any sequence of directories.
+fn glob_matches(pattern: &[char], path: &[char]) -> bool {
+  match pattern.split_first() {
+    None => path.is_empty(),
+    Some(('*', rest)) if rest.first() == Some(&'*') => {
+      let rest = &rest[1..];
+      (rest.first() == Some(&'/') && glob_matches(&rest[1..], path))
+        || (0..=path.len()).any(|i| glob_matches(rest, &path[i..]))
+    }
+    Some(('*', rest)) => (0..=path.len())
+      .take_while(|&i| i == 0 || path[i - 1] != '/')
+      .any(|i| glob_matches(rest, &path[i..])),
+    Some(('?', rest)) => match path.split_first() {
+      Some((c, path)) => *c != '/' && glob_matches(rest, path),
+      None => false,
+    },
+    Some((c, rest)) => match path.split_first() {
+      Some((p, path)) => p == c && glob_matches(rest, path),
+      None => false,
+    },
+  }
+}
+
+/// The path as a string with `/` separators, used to match the glob patterns.
+fn normalize(path: &Path) -> String {
+  let path = path.to_string_lossy();
+  if MAIN_SEPARATOR == '/' {
+    path.into_owned()
+  } else {
+    path.replace(MAIN_SEPARATOR, "/")
+  }
+}
+
+/// Canonicalizes the path, resolving its `..` components and symbolic links.
+/// The path doesn't need to exist: its missing components are appended to its canonicalized existing ancestor,
+/// so they can't contain `..` components.
+fn canonicalize(path: &Path) -> Option<PathBuf> {
+  let path = if path.is_absolute() {
+    path.to_path_buf()
+  } else {
+    std::env::current_dir().ok()?.join(path)
+  };
+  let mut existing = path.as_path();
+  let mut missing = Vec::new();
+  loop {
+    if let Ok(mut canonical) = existing.canonicalize() {
+      canonical.extend(missing.iter().rev());
+      return Some(canonical);
+    }
+    match existing.components().next_back() {
+      Some(Component::Normal(name)) => missing.push(name),
+      Some(Component::CurDir) => {}
+      _ => return None,
+    }
+    existing = existing.parent()?;
+  }
+}
+
+/// Canonicalizes the parent of the path, leaving its last component as is,
+/// e.g. so removing a symbolic link doesn't remove its target.
+fn canonicalize_parent(path: &Path) -> Option<PathBuf> {
+  match (path.parent(), path.file_name()) {
+    (Some(parent), Some(name)) => Some(canonicalize(parent)?.join(name)),
+    // e.g. `..`, that can't be left unresolved
+    _ => canonicalize(path),
+  }
+}
+
+/// Expands the base directory variable of the pattern, e.g. `$APP/**`, and canonicalizes its literal prefix.
+/// Returns `None` if the variable is unknown or its directory can't be resolved on this platform.
+fn expand(pattern: &str) -> Option<String> {
+  let pattern = if pattern.starts_with('$') {
+    let variable_end = pattern
+      .find(|c: char| c == '/' || c == '\\')
+      .unwrap_or(pattern.len());
+    let directory = BaseDirectory::from_variable(&pattern[..variable_end])?;
+    let directory = normalize(&resolve_path("", Some(directory)).ok()?);
+    format!(
+      "{}{}",
+      directory.trim_end_matches('/'),
+      normalize(Path::new(&pattern[variable_end..]))
+    )
+  } else {
+    normalize(Path::new(pattern))
+  };
+
+  // the segments before the first wildcard are a path we can canonicalize
+  let literal_end = match pattern.find(|c: char| c == '*' || c == '?') {
+    Some(wildcard) => pattern[..wildcard].rfind('/').unwrap_or(0),
+    None => pattern.len(),
+  };
+  if literal_end == 0 {
+    return Some(pattern);
+  }
+  let prefix = canonicalize(Path::new(&pattern[..literal_end]))?;
+  Some(format!(
+    "{}{}",
+    normalize(&prefix).trim_end_matches('/'),
+    &pattern[literal_end..]
+  ))
+}
+
+/// The paths the file system API can access, defined by the `tauri > fs > scope` glob patterns.
+#[derive(Debug, Clone)]
+pub(crate) struct FsScope {
+  patterns: Vec<Vec<char>>,
+}
+
+impl FsScope {
+  /// Creates the scope from the configured glob patterns.
+  /// The patterns referencing a directory that can't be resolved don't match any path.
+  pub(crate) fn new(scope: &[String]) -> Self {
+    Self {
+      patterns: scope
+        .iter()
+        .filter_map(|pattern| expand(pattern))
+        .map(|pattern| pattern.chars().collect())
+        .collect(),
+    }
+  }
+
+  /// Whether the canonicalized path matches a pattern of the scope.
+  fn is_allowed(&self, path: &Path) -> bool {
+    let normalized: Vec<char> = normalize(path).chars().collect();
+    self.patterns.iter().any(|pattern| {
+      glob_matches(pattern, &normalized)
+        // `dir/**` also matches the directory itself
+        || (pattern.ends_with(&['/', '*', '*'])
+          && glob_matches(&pattern[..pattern.len() - 3], &normalized))
+    })
+  }
+
+  /// Checks whether the path is on the scope, returning the path to run the operation on,
+  /// with its parent canonicalized and its last component left as is.
+  ///
+  /// If the path is a symbolic link, both the link and its target must be on the scope,
+  /// since the operations can either apply to the link or follow it.
+  pub(crate) fn check<P: AsRef<Path>>(&self, path: P) -> crate::Result<PathBuf> {
+    let path = path.as_ref();
+    let not_allowed = || crate::Error::PathNotAllowed(path.to_path_buf());
+    let unresolved = canonicalize_parent(path).ok_or_else(not_allowed)?;
+    let is_symlink = std::fs::symlink_metadata(&unresolved)
+      .map(|metadata| metadata.file_type().is_symlink())
+      .unwrap_or(false);
+    let canonical = if is_symlink {
+      // the target of a dangling link can't be canonicalized, so it can't be checked
+      unresolved.canonicalize().map_err(|_| not_allowed())?
+    } else {
+      unresolved.clone()
+    };
+    if self.is_allowed(&unresolved) && self.is_allowed(&canonical) {
+      Ok(unresolved)
+    } else {
+      Err(not_allowed())
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{glob_matches, FsScope};
+  use std::{fs, path::PathBuf};
+
+  fn matches(pattern: &str, path: &str) -> bool {
+    glob_matches(
+      &pattern.chars().collect::<Vec<_>>(),
+      &path.chars().collect::<Vec<_>>(),
+    )
+  }
+
+  #[test]
+  fn matches_glob_patterns() {
+    assert!(matches("/app/*.csv", "/app/data.csv"));
+    assert!(!matches("/app/*.csv", "/app/data.json"));
+    assert!(!matches("/app/*.csv", "/app/nested/data.csv"));
+    assert!(matches("/app/?.txt", "/app/a.txt"));
+    assert!(!matches("/app/?.txt", "/app/ab.txt"));
+    assert!(matches("/app/**", "/app/nested/data.csv"));
+    assert!(matches("/app/**/*.csv", "/app/data.csv"));
+    assert!(matches("/app/**/*.csv", "/app/a/b/data.csv"));
+    assert!(!matches("/app/**/*.csv", "/app/a/b/data.json"));
+    assert!(!matches("/app/**", "/application/data.csv"));
+  }
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tauri-fs-scope-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("allowed/nested")).unwrap();
+    fs::create_dir_all(dir.join("denied")).unwrap();
+    fs::write(dir.join("allowed/nested/data.csv"), "").unwrap();
+    fs::write(dir.join("denied/secret.txt"), "").unwrap();
+    dir
+  }
+
+  #[test]
+  fn checks_paths() {
+    let dir = temp_dir("check");
+    let scope = FsScope::new(&[
+      format!("{}/allowed/**", dir.display()),
+      format!("{}/*.csv", dir.display()),
+    ]);
+
+    let allowed = dir.join("allowed/nested/data.csv");
+    assert_eq!(
+      scope.check(&allowed).unwrap(),
+      allowed.canonicalize().unwrap()
+    );
+    assert!(scope.check(dir.join("allowed")).is_ok());
+    // paths that don't exist yet, e.g. the target of a write
+    assert!(scope.check(dir.join("allowed/new/file.txt")).is_ok());
+    assert!(scope.check(dir.join("export.csv")).is_ok());
+
+    assert!(matches!(
+      scope.check(dir.join("denied/secret.txt")),
+      Err(crate::Error::PathNotAllowed(_))
+    ));
+    assert!(scope.check(dir.join("export.json")).is_err());
+    // traversals are resolved before matching the patterns
+    assert!(scope
+      .check(dir.join("allowed/nested/../../denied/secret.txt"))
+      .is_err());
+    assert!(scope
+      .check(dir.join("allowed/missing/../../denied/new.txt"))
+      .is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn checks_symbolic_links_and_their_targets() {
+    use std::os::unix::fs::symlink;
+
+    let dir = temp_dir("symlink");
+    let scope = FsScope::new(&[format!("{}/allowed/**", dir.display())]);
+    let allowed = dir.join("allowed").canonicalize().unwrap();
+
+    // the operations run on the link itself, e.g. removing it doesn't remove its target
+    symlink(allowed.join("nested/data.csv"), allowed.join("link.csv")).unwrap();
+    assert_eq!(
+      scope.check(allowed.join("link.csv")).unwrap(),
+      allowed.join("link.csv")
+    );
+
+    // links to the paths outside of the scope, or outside of the scope themselves, are denied
+    symlink(dir.join("denied/secret.txt"), allowed.join("secret.txt")).unwrap();
+    assert!(scope.check(allowed.join("secret.txt")).is_err());
+    symlink(allowed.join("nested/data.csv"), dir.join("denied/link.csv")).unwrap();
+    assert!(scope.check(dir.join("denied/link.csv")).is_err());
+    // the target of a dangling link can't be checked
+    symlink(dir.join("denied/new.txt"), allowed.join("dangling.txt")).unwrap();
+    assert!(scope.check(allowed.join("dangling.txt")).is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn empty_scope_denies_everything() {
+    let dir = temp_dir("empty");
+    let scope = FsScope::new(&[]);
+    assert!(scope.check(dir.join("allowed/nested/data.csv")).is_err());
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn expands_base_directories() {
+    let home = match crate::api::path::home_dir() {
+      Some(home) => home,
+      None => return,
+    };
+    let scope = FsScope::new(&["$HOME/*.txt".to_string(), "$UNKNOWN/**".to_string()]);
+    assert_eq!(scope.patterns.len(), 1);
+    assert!(scope.check(home.join("tauri-fs-scope.txt")).is_ok());
+    assert!(scope.check(home.join("tauri-fs-scope.csv")).is_err());
+  }
+}
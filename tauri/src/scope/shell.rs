@@ -0,0 +1,169 @@
+use crate::api::config::{ShellAllowedArg, ShellAllowedCommand};
+
+use regex::Regex;
+
+/// An argument of a scope command, with its validator compiled.
+#[derive(Debug)]
+enum ScopeArg {
+  Fixed(String),
+  Var { validator: String, regex: Regex },
+}
+
+/// A command of the shell scope.
+#[derive(Debug)]
+struct ScopeCommand {
+  name: String,
+  cmd: String,
+  args: Vec<ScopeArg>,
+}
+
+/// The commands the shell API can execute, defined by the `tauri > shell > scope` configuration.
+#[derive(Debug)]
+pub(crate) struct ShellScope {
+  commands: Vec<ScopeCommand>,
+}
+
+impl ShellScope {
+  /// Creates the scope from the configured commands, compiling their argument validators.
+  pub(crate) fn new(commands: &[ShellAllowedCommand]) -> crate::Result<Self> {
+    let mut scope_commands = Vec::with_capacity(commands.len());
+    for command in commands {
+      let mut args = Vec::with_capacity(command.args.len());
+      for arg in &command.args {
+        args.push(match arg {
+          ShellAllowedArg::Fixed(value) => ScopeArg::Fixed(value.clone()),
+          ShellAllowedArg::Var { validator } => ScopeArg::Var {
+            // the validator must match the whole argument
+            regex: Regex::new(&format!("^(?:{})$", validator))
+              .map_err(|e| crate::Error::InvalidShellValidator(validator.clone(), e))?,
+            validator: validator.clone(),
+          },
+        });
+      }
+      scope_commands.push(ScopeCommand {
+        name: command.name.clone(),
+        cmd: command.cmd.clone(),
+        args,
+      });
+    }
+    Ok(Self {
+      commands: scope_commands,
+    })
+  }
+
+  /// Validates the API call of the command with the given name,
+  /// returning the program to execute and its arguments.
+  ///
+  /// The given arguments are the values of the validated arguments of the command, in order.
+  pub(crate) fn prepare(
+    &self,
+    name: &str,
+    args: Vec<String>,
+  ) -> crate::Result<(String, Vec<String>)> {
+    let command = self
+      .commands
+      .iter()
+      .find(|command| command.name == name)
+      .ok_or_else(|| crate::Error::ShellCommandNotAllowed(name.to_string()))?;
+
+    let expected = command
+      .args
+      .iter()
+      .filter(|arg| matches!(arg, ScopeArg::Var { .. }))
+      .count();
+    if args.len() != expected {
+      return Err(crate::Error::InvalidShellArgs(
+        name.to_string(),
+        expected,
+        args.len(),
+      ));
+    }
+
+    let mut values = args.into_iter().enumerate();
+    let mut command_args = Vec::with_capacity(command.args.len());
+    for arg in &command.args {
+      match arg {
+        ScopeArg::Fixed(value) => command_args.push(value.clone()),
+        ScopeArg::Var { validator, regex } => {
+          let (index, value) = values.next().expect("arguments count already checked");
+          if !regex.is_match(&value) {
+            return Err(crate::Error::InvalidShellArg(
+              name.to_string(),
+              index,
+              validator.clone(),
+            ));
+          }
+          command_args.push(value);
+        }
+      }
+    }
+
+    Ok((command.cmd.clone(), command_args))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::ShellScope;
+  use crate::api::config::{ShellAllowedArg, ShellAllowedCommand};
+
+  fn commands() -> Vec<ShellAllowedCommand> {
+    vec![ShellAllowedCommand {
+      name: "checkout".to_string(),
+      cmd: "git".to_string(),
+      args: vec![
+        ShellAllowedArg::Fixed("checkout".to_string()),
+        ShellAllowedArg::Var {
+          validator: "[\\w-]+".to_string(),
+        },
+      ],
+    }]
+  }
+
+  #[test]
+  fn prepares_allowed_commands() {
+    let scope = ShellScope::new(&commands()).unwrap();
+    assert_eq!(
+      scope
+        .prepare("checkout", vec!["feat-scope".to_string()])
+        .unwrap(),
+      (
+        "git".to_string(),
+        vec!["checkout".to_string(), "feat-scope".to_string()]
+      )
+    );
+  }
+
+  #[test]
+  fn rejects_invalid_calls() {
+    let scope = ShellScope::new(&commands()).unwrap();
+    assert!(matches!(
+      scope.prepare("git", vec![]),
+      Err(crate::Error::ShellCommandNotAllowed(name)) if name == "git"
+    ));
+    assert!(matches!(
+      scope.prepare("checkout", vec![]),
+      Err(crate::Error::InvalidShellArgs(name, 1, 0)) if name == "checkout"
+    ));
+    // the validator must match the whole argument
+    assert!(matches!(
+      scope.prepare("checkout", vec!["main; rm -rf ~".to_string()]),
+      Err(crate::Error::InvalidShellArg(name, 0, _)) if name == "checkout"
+    ));
+  }
+
+  #[test]
+  fn fails_on_invalid_validators() {
+    let commands = vec![ShellAllowedCommand {
+      name: "checkout".to_string(),
+      cmd: "git".to_string(),
+      args: vec![ShellAllowedArg::Var {
+        validator: "[\\w-+".to_string(),
+      }],
+    }];
+    assert!(matches!(
+      ShellScope::new(&commands),
+      Err(crate::Error::InvalidShellValidator(validator, _)) if validator == "[\\w-+"
+    ));
+  }
+}
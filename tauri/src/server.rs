@@ -2,8 +2,12 @@ use std::io::Read;
 use tauri_api::assets::{AssetFetch, Assets};
 use tiny_http::{Response, StatusCode};
 
-/// Returns the HTTP response of the given asset path.
-pub fn asset_response(path: &str, assets: &'static Assets) -> Response<impl Read> {
+/// Returns the HTTP response of the given asset path, with the `Content-Security-Policy` header if a CSP is given.
+pub fn asset_response(
+  path: &str,
+  assets: &'static Assets,
+  csp: Option<&str>,
+) -> Response<impl Read> {
   let (asset, _) = assets
     .get(path, AssetFetch::Compress)
     .unwrap_or_else(|| panic!("Could not read asset {}", path));
@@ -18,22 +22,20 @@ pub fn asset_response(path: &str, assets: &'static Assets) -> Response<impl Read
   headers.push(content_encoding);
 
   // Content-Type
-  let mime = if path.ends_with(".svg") {
-    "Content-Type: image/svg+xml"
-  } else if path.ends_with(".css") {
-    "Content-Type: text/css"
-  } else if path.ends_with(".html") {
-    "Content-Type: text/html"
-  } else if path.ends_with(".js") {
-    "Content-Type: text/javascript"
-  } else {
-    "Content-Type: application/octet-stream"
-  };
+  let mime = format!("Content-Type: {}", crate::app::protocol::mime_type(path));
 
   let content_type = mime
     .parse()
     .unwrap_or_else(|_| panic!("Could not add {} header", mime));
   headers.push(content_type);
 
+  // Content-Security-Policy, a valid header value since `FromTauriContext` validates it at build time
+  if let Some(csp) = csp {
+    let content_security_policy = format!("Content-Security-Policy: {}", csp)
+      .parse()
+      .expect("the CSP isn't a valid header value");
+    headers.push(content_security_policy);
+  }
+
   Response::new(StatusCode(200), headers, asset, None, None)
 }
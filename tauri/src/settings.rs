@@ -8,6 +8,12 @@ use tauri_api::{
   file::read_string,
   path::{resolve_path, BaseDirectory},
 };
+use uuid::Uuid;
+
+mod store;
+
+pub(crate) use store::StoreRegistry;
+pub use store::{Store, StoreBuilder, StoreChange, StoreValues, STORE_CHANGE_EVENT};
 
 /// Tauri Settings.
 #[derive(Default, Deserialize, Serialize)]
@@ -22,19 +28,42 @@ fn get_settings_path() -> tauri_api::Result<PathBuf> {
   resolve_path(".tauri-settings.json", Some(BaseDirectory::App))
 }
 
-/// Write the settings to the file system.
-pub(crate) fn write_settings(settings: Settings) -> crate::Result<()> {
-  let settings_path = get_settings_path()?;
-  let settings_folder = Path::new(&settings_path).parent().unwrap();
-  if !settings_folder.exists() {
-    std::fs::create_dir(settings_folder)?;
+/// Writes the file atomically: the contents are written to a temporary file next to it, then renamed over it,
+/// so a crash can't leave a partially written file.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> crate::Result<()> {
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
   }
-  File::create(settings_path)
-    .map_err(|e| e.into())
-    .and_then(|mut f| {
-      f.write_all(serde_json::to_string(&settings)?.as_bytes())
-        .map_err(|e| e.into())
+  let file_name = path.file_name().ok_or_else(|| {
+    std::io::Error::new(
+      std::io::ErrorKind::InvalidInput,
+      format!("`{}` isn't a file path", path.display()),
+    )
+  })?;
+  // a unique name, so concurrent writes of the same path don't share the temporary file
+  let temp_path = path.with_file_name(format!(
+    ".{}.{}.tmp",
+    file_name.to_string_lossy(),
+    Uuid::new_v4()
+  ));
+  let result = File::create(&temp_path)
+    .and_then(|mut file| {
+      file.write_all(contents)?;
+      file.sync_all()
     })
+    .and_then(|_| std::fs::rename(&temp_path, path));
+  if result.is_err() {
+    let _ = std::fs::remove_file(&temp_path);
+  }
+  result.map_err(Into::into)
+}
+
+/// Write the settings to the file system.
+pub(crate) fn write_settings(settings: Settings) -> crate::Result<()> {
+  write_atomic(
+    &get_settings_path()?,
+    serde_json::to_string(&settings)?.as_bytes(),
+  )
 }
 
 /// Reads the settings from the file system.
@@ -0,0 +1,662 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use tauri_api::path::{resolve_path, BaseDirectory};
+
+use std::{
+  collections::{BTreeMap, HashMap},
+  path::{Path, PathBuf},
+  sync::{Arc, Condvar, Mutex},
+  time::{Duration, Instant},
+};
+
+use super::write_atomic;
+
+/// The event emitted to the Rust and JS listeners when a store changes, with a [`StoreChange`] payload.
+pub const STORE_CHANGE_EVENT: &str = "store-change";
+
+/// The values of a store.
+pub type StoreValues = Map<String, JsonValue>;
+
+type Migration = Box<dyn Fn(&mut StoreValues) -> crate::Result<()> + Send + Sync>;
+type ChangeListener = Arc<dyn Fn(&StoreChange) + Send + Sync>;
+
+/// A change of a store, the payload of the [`STORE_CHANGE_EVENT`] event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoreChange {
+  /// The name of the store.
+  pub store: String,
+  /// The key that changed, or `None` if the store was cleared.
+  pub key: Option<String>,
+  /// The new value of the key, or `None` if it was deleted.
+  pub value: Option<JsonValue>,
+}
+
+/// The store file.
+#[derive(Deserialize, Serialize)]
+struct StoreFile {
+  #[serde(default)]
+  version: u32,
+  #[serde(default)]
+  values: StoreValues,
+}
+
+/// Checks that the store name can be used as a file name, so stores can't be read or written outside of their directory.
+fn validate_name(name: &str) -> crate::Result<()> {
+  let valid = !name.is_empty()
+    && !name.starts_with('.')
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+  if valid {
+    Ok(())
+  } else {
+    Err(crate::Error::InvalidStoreName(name.to_string()))
+  }
+}
+
+/// The store builder, defining how a store is saved and migrated.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use tauri::settings::StoreBuilder;
+/// let builder = StoreBuilder::new("preferences")
+///   .autosave(Duration::from_millis(500))
+///   // version 1 renamed the `dark` boolean to a `theme` string
+///   .migration(1, |values| {
+///     if let Some(dark) = values.remove("dark") {
+///       let theme = if dark.as_bool().unwrap_or(false) { "dark" } else { "light" };
+///       values.insert("theme".to_string(), theme.into());
+///     }
+///     Ok(())
+///   });
+/// ```
+pub struct StoreBuilder {
+  name: String,
+  dir: Option<PathBuf>,
+  autosave: Option<Duration>,
+  migrations: BTreeMap<u32, Migration>,
+}
+
+impl StoreBuilder {
+  /// Creates the builder of the store with the given name.
+  /// The name can only contain ASCII letters, digits, `-`, `_` and `.`, and can't start with a `.`.
+  pub fn new(name: impl Into<String>) -> Self {
+    Self {
+      name: name.into(),
+      dir: None,
+      autosave: None,
+      migrations: Default::default(),
+    }
+  }
+
+  /// Sets the directory of the store file, defaults to the `stores` folder of the app directory.
+  pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.dir = Some(dir.into());
+    self
+  }
+
+  /// Saves the store when it doesn't change for the given duration.
+  /// Without autosave, the store is saved with [`Store::save`].
+  pub fn autosave(mut self, delay: Duration) -> Self {
+    self.autosave = Some(delay);
+    self
+  }
+
+  /// Adds a migration to the given version of the store schema.
+  ///
+  /// The store version is its highest migration version. When the store is loaded from a file saved with an older version,
+  /// the newer migrations run in order and the migrated store is saved.
+  pub fn migration<F: Fn(&mut StoreValues) -> crate::Result<()> + Send + Sync + 'static>(
+    mut self,
+    version: u32,
+    migration: F,
+  ) -> Self {
+    self.migrations.insert(version, Box::new(migration));
+    self
+  }
+
+  /// The name of the store.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Loads the store from its file, running its migrations.
+  pub fn build(self) -> crate::Result<Store> {
+    validate_name(&self.name)?;
+    let dir = match self.dir {
+      Some(dir) => dir,
+      None => resolve_path("stores", Some(BaseDirectory::App))?,
+    };
+    let path = dir.join(format!("{}.json", self.name));
+    let version = self.migrations.keys().next_back().copied().unwrap_or(0);
+
+    let (values, migrated) = if path.exists() {
+      let file: StoreFile = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+      if file.version > version {
+        return Err(crate::Error::StoreVersionNotSupported(
+          self.name,
+          file.version,
+        ));
+      }
+      let mut values = file.values;
+      let mut migrated = false;
+      for (_, migration) in self.migrations.range(file.version + 1..) {
+        migration(&mut values)?;
+        migrated = true;
+      }
+      (values, migrated)
+    } else {
+      (Default::default(), false)
+    };
+
+    let store = Store {
+      inner: Arc::new(StoreInner {
+        name: self.name,
+        path,
+        version,
+        autosave: self.autosave,
+        values: Mutex::new(values),
+        listeners: Default::default(),
+        autosave_deadline: Default::default(),
+        saves: Default::default(),
+        saved: Default::default(),
+      }),
+    };
+    if migrated {
+      store.save()?;
+    }
+    Ok(store)
+  }
+}
+
+struct StoreInner {
+  name: String,
+  path: PathBuf,
+  version: u32,
+  autosave: Option<Duration>,
+  values: Mutex<StoreValues>,
+  listeners: Mutex<Vec<ChangeListener>>,
+  /// When the pending autosave runs, `None` if no autosave is pending.
+  autosave_deadline: Mutex<Option<Instant>>,
+  /// The number of saves of the store file, locked while it's written.
+  saves: Mutex<usize>,
+  /// Notified when the store file was saved.
+  saved: Condvar,
+}
+
+/// A persistent key-value store, saved as JSON in its file.
+///
+/// The stores opened with [`WebviewManager::store`](crate::WebviewManager::store) emit the [`STORE_CHANGE_EVENT`] event on every change.
+#[derive(Clone)]
+pub struct Store {
+  inner: Arc<StoreInner>,
+}
+
+impl Store {
+  /// The name of the store.
+  pub fn name(&self) -> &str {
+    &self.inner.name
+  }
+
+  /// The path of the store file.
+  pub fn path(&self) -> &Path {
+    &self.inner.path
+  }
+
+  /// The version of the store schema, its highest migration version.
+  pub fn version(&self) -> u32 {
+    self.inner.version
+  }
+
+  /// Gets the value of the given key, deserialized to the given type.
+  pub fn get<T: DeserializeOwned>(&self, key: &str) -> crate::Result<Option<T>> {
+    let value = self.inner.values.lock().unwrap().get(key).cloned();
+    match value {
+      Some(value) => Ok(Some(serde_json::from_value(value)?)),
+      None => Ok(None),
+    }
+  }
+
+  /// Sets the value of the given key.
+  pub fn set<T: Serialize>(&self, key: impl Into<String>, value: T) -> crate::Result<()> {
+    let key = key.into();
+    let value = serde_json::to_value(value)?;
+    let previous = self
+      .inner
+      .values
+      .lock()
+      .unwrap()
+      .insert(key.clone(), value.clone());
+    if previous.as_ref() != Some(&value) {
+      self.changed(StoreChange {
+        store: self.inner.name.clone(),
+        key: Some(key),
+        value: Some(value),
+      });
+    }
+    Ok(())
+  }
+
+  /// Deletes the given key, returning whether the store had it.
+  pub fn delete(&self, key: &str) -> bool {
+    let deleted = self.inner.values.lock().unwrap().remove(key).is_some();
+    if deleted {
+      self.changed(StoreChange {
+        store: self.inner.name.clone(),
+        key: Some(key.to_string()),
+        value: None,
+      });
+    }
+    deleted
+  }
+
+  /// The keys of the store.
+  pub fn keys(&self) -> Vec<String> {
+    self.inner.values.lock().unwrap().keys().cloned().collect()
+  }
+
+  /// Deletes all keys of the store.
+  pub fn clear(&self) {
+    let cleared = {
+      let mut values = self.inner.values.lock().unwrap();
+      let cleared = !values.is_empty();
+      values.clear();
+      cleared
+    };
+    if cleared {
+      self.changed(StoreChange {
+        store: self.inner.name.clone(),
+        key: None,
+        value: None,
+      });
+    }
+  }
+
+  /// Writes the store to its file, atomically.
+  pub fn save(&self) -> crate::Result<()> {
+    let mut saves = self.inner.saves.lock().unwrap();
+    let contents = serde_json::to_vec(&StoreFile {
+      version: self.inner.version,
+      values: self.inner.values.lock().unwrap().clone(),
+    })?;
+    write_atomic(&self.inner.path, &contents)?;
+    *saves += 1;
+    self.inner.saved.notify_all();
+    Ok(())
+  }
+
+  /// Waits until the store file was saved the given number of times, returning whether it was before the timeout.
+  #[cfg(test)]
+  pub(crate) fn wait_for_saves(&self, count: usize, timeout: Duration) -> bool {
+    let saves = self.inner.saves.lock().unwrap();
+    let (_saves, result) = self
+      .inner
+      .saved
+      .wait_timeout_while(saves, timeout, |saves| *saves < count)
+      .unwrap();
+    !result.timed_out()
+  }
+
+  /// Adds a listener called on every change of the store.
+  pub(crate) fn on_change<F: Fn(&StoreChange) + Send + Sync + 'static>(&self, listener: F) {
+    self
+      .inner
+      .listeners
+      .lock()
+      .unwrap()
+      .push(Arc::new(listener));
+  }
+
+  /// Saves the store now if an autosave is pending.
+  pub(crate) fn flush(&self) -> crate::Result<()> {
+    if self
+      .inner
+      .autosave_deadline
+      .lock()
+      .unwrap()
+      .take()
+      .is_some()
+    {
+      self.save()
+    } else {
+      Ok(())
+    }
+  }
+
+  fn changed(&self, change: StoreChange) {
+    let listeners = self.inner.listeners.lock().unwrap().clone();
+    // the listeners run without the lock, so they can add listeners and update the store
+    for listener in listeners {
+      listener(&change);
+    }
+    self.schedule_autosave();
+  }
+
+  /// Saves the store once it doesn't change for the autosave delay.
+  fn schedule_autosave(&self) {
+    let delay = match self.inner.autosave {
+      Some(delay) => delay,
+      None => return,
+    };
+    let already_scheduled = self
+      .inner
+      .autosave_deadline
+      .lock()
+      .unwrap()
+      .replace(Instant::now() + delay)
+      .is_some();
+    if already_scheduled {
+      return;
+    }
+
+    let store = self.clone();
+    std::thread::spawn(move || {
+      // every change postpones the save
+      loop {
+        let wait = match *store.inner.autosave_deadline.lock().unwrap() {
+          Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+          // flushed
+          None => return,
+        };
+        if wait == Duration::from_secs(0) {
+          break;
+        }
+        std::thread::sleep(wait);
+      }
+      // the autosave has no caller to report its errors to, the next save retries
+      let _ = store.flush();
+    });
+  }
+}
+
+/// The stores of the app, opened on first use.
+#[derive(Default)]
+pub(crate) struct StoreRegistry {
+  /// The builders of the stores registered with [`AppBuilder::store`](crate::AppBuilder::store), until they are opened.
+  builders: Mutex<HashMap<String, StoreBuilder>>,
+  stores: Mutex<HashMap<String, Store>>,
+}
+
+impl StoreRegistry {
+  /// Registers the builder of a store, failing if its name is invalid or already registered.
+  pub(crate) fn register(&self, builder: StoreBuilder) -> crate::Result<()> {
+    validate_name(builder.name())?;
+    let mut builders = self.builders.lock().unwrap();
+    if builders.contains_key(builder.name()) {
+      return Err(crate::Error::StoreAlreadyRegistered(
+        builder.name().to_string(),
+      ));
+    }
+    builders.insert(builder.name().to_string(), builder);
+    Ok(())
+  }
+
+  /// Gets the store with the given name, opening it with its registered builder or the default options.
+  /// `on_open` is called with the store when it's opened.
+  pub(crate) fn open<F: FnOnce(&Store)>(&self, name: &str, on_open: F) -> crate::Result<Store> {
+    let mut stores = self.stores.lock().unwrap();
+    if let Some(store) = stores.get(name) {
+      return Ok(store.clone());
+    }
+    let builder = self
+      .builders
+      .lock()
+      .unwrap()
+      .remove(name)
+      .unwrap_or_else(|| StoreBuilder::new(name));
+    let store = builder.build()?;
+    on_open(&store);
+    stores.insert(name.to_string(), store.clone());
+    Ok(store)
+  }
+
+  /// Saves the stores with a pending autosave.
+  pub(crate) fn flush(&self) -> crate::Result<()> {
+    for store in self.stores.lock().unwrap().values() {
+      store.flush()?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{StoreBuilder, StoreChange, StoreRegistry};
+  use crate::test::TIMEOUT;
+  use serde::{Deserialize, Serialize};
+  use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+  };
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tauri-store-{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+  }
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Window {
+    width: u32,
+    height: u32,
+  }
+
+  #[test]
+  fn persists_values() {
+    let dir = temp_dir("persist");
+    let store = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+    store.set("theme", "dark").unwrap();
+    store
+      .set(
+        "window",
+        Window {
+          width: 800,
+          height: 600,
+        },
+      )
+      .unwrap();
+    store.set("count", 1).unwrap();
+    assert!(store.delete("count"));
+    assert!(!store.delete("count"));
+    store.save().unwrap();
+    // the temporary file was renamed
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    let store = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+    assert_eq!(
+      store.get::<String>("theme").unwrap(),
+      Some("dark".to_string())
+    );
+    assert_eq!(
+      store.get::<Window>("window").unwrap(),
+      Some(Window {
+        width: 800,
+        height: 600
+      })
+    );
+    assert_eq!(store.get::<u32>("count").unwrap(), None);
+    assert!(store.get::<u32>("theme").is_err());
+    assert_eq!(
+      store.keys(),
+      vec!["theme".to_string(), "window".to_string()]
+    );
+    store.clear();
+    assert!(store.keys().is_empty());
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn notifies_changes() {
+    let dir = temp_dir("changes");
+    let store = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let changes_ = changes.clone();
+    store.on_change(move |change| changes_.lock().unwrap().push(change.clone()));
+
+    store.set("theme", "dark").unwrap();
+    // unchanged values don't notify
+    store.set("theme", "dark").unwrap();
+    store.delete("theme");
+    store.set("theme", "light").unwrap();
+    store.clear();
+
+    let change = |key: Option<&str>, value: Option<&str>| StoreChange {
+      store: "settings".to_string(),
+      key: key.map(ToString::to_string),
+      value: value.map(Into::into),
+    };
+    assert_eq!(
+      *changes.lock().unwrap(),
+      vec![
+        change(Some("theme"), Some("dark")),
+        change(Some("theme"), None),
+        change(Some("theme"), Some("light")),
+        change(None, None),
+      ]
+    );
+  }
+
+  #[test]
+  fn listeners_can_update_the_store() {
+    let dir = temp_dir("listeners");
+    let store = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+    let store_ = store.clone();
+    store.on_change(move |change| {
+      if change.key.as_deref() == Some("theme") {
+        store_.set("themeChanged", true).unwrap();
+        store_.on_change(|_| {});
+      }
+    });
+
+    store.set("theme", "dark").unwrap();
+    assert_eq!(store.get::<bool>("themeChanged").unwrap(), Some(true));
+  }
+
+  #[test]
+  fn autosaves_after_the_last_change() {
+    let dir = temp_dir("autosave");
+    let store = StoreBuilder::new("settings")
+      .dir(&dir)
+      .autosave(Duration::from_millis(200))
+      .build()
+      .unwrap();
+    // every change postpones the autosave
+    for count in 0..5 {
+      store.set("count", count).unwrap();
+    }
+    assert!(!store.path().exists());
+    // the first save has the last value
+    assert!(store.wait_for_saves(1, TIMEOUT));
+    let saved = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+    assert_eq!(saved.get::<u32>("count").unwrap(), Some(4));
+
+    // flushing saves the pending changes right away
+    store.set("count", 5).unwrap();
+    store.flush().unwrap();
+    let saved = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+    assert_eq!(saved.get::<u32>("count").unwrap(), Some(5));
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn saves_concurrently() {
+    let dir = temp_dir("concurrent");
+    // the stores opened by separate registries write the same file
+    let threads: Vec<_> = (0..4)
+      .map(|_| {
+        let store = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+        std::thread::spawn(move || {
+          for count in 0..20 {
+            store.set("count", count).unwrap();
+            store.save().unwrap();
+          }
+        })
+      })
+      .collect();
+    for thread in threads {
+      thread.join().unwrap();
+    }
+    // the temporary files were renamed
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    let saved = StoreBuilder::new("settings").dir(&dir).build().unwrap();
+    assert_eq!(saved.get::<u32>("count").unwrap(), Some(19));
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn migrates_old_versions() {
+    let dir = temp_dir("migrations");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+      dir.join("settings.json"),
+      r#"{"version":1,"values":{"dark":true,"size":12}}"#,
+    )
+    .unwrap();
+    let builder = || {
+      StoreBuilder::new("settings")
+        .dir(&dir)
+        .migration(1, |_| panic!("the store is already migrated to version 1"))
+        .migration(2, |values| {
+          let dark = values.remove("dark").and_then(|dark| dark.as_bool());
+          let theme = if dark == Some(true) { "dark" } else { "light" };
+          values.insert("theme".to_string(), theme.into());
+          Ok(())
+        })
+        .migration(3, |values| {
+          values.remove("size");
+          Ok(())
+        })
+    };
+
+    let store = builder().build().unwrap();
+    assert_eq!(store.version(), 3);
+    assert_eq!(store.keys(), vec!["theme".to_string()]);
+    assert_eq!(
+      store.get::<String>("theme").unwrap(),
+      Some("dark".to_string())
+    );
+    // the migrated store was saved
+    let contents = std::fs::read_to_string(store.path()).unwrap();
+    assert_eq!(contents, r#"{"version":3,"values":{"theme":"dark"}}"#);
+
+    // stores saved by a newer version of the app can't be loaded
+    assert!(matches!(
+      StoreBuilder::new("settings").dir(&dir).build(),
+      Err(crate::Error::StoreVersionNotSupported(name, 3)) if name == "settings"
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn registers_stores() {
+    let dir = temp_dir("registry");
+    let registry = StoreRegistry::default();
+    registry
+      .register(StoreBuilder::new("settings").dir(&dir))
+      .unwrap();
+    assert!(matches!(
+      registry.register(StoreBuilder::new("settings")),
+      Err(crate::Error::StoreAlreadyRegistered(name)) if name == "settings"
+    ));
+    assert!(matches!(
+      registry.register(StoreBuilder::new("../settings")),
+      Err(crate::Error::InvalidStoreName(_))
+    ));
+
+    let mut opened = 0;
+    let store = registry.open("settings", |_| opened += 1).unwrap();
+    assert_eq!(store.path(), dir.join("settings.json"));
+    registry.open("settings", |_| opened += 1).unwrap();
+    assert_eq!(opened, 1);
+    assert!(matches!(
+      registry.open(".hidden", |_| {}),
+      Err(crate::Error::InvalidStoreName(_))
+    ));
+  }
+}
@@ -0,0 +1,49 @@
+use crate::{
+  flavors::{
+    mock::{MockHandle, MockMessage, MockWindowId},
+    Mock,
+  },
+  AppBuilder, FromTauriContext,
+};
+use tauri_api::private::AsTauriContext;
+
+use std::time::Duration;
+
+/// The context of the fixture app.
+#[derive(FromTauriContext)]
+#[config_path = "test/fixture/src-tauri/tauri.conf.json"]
+pub(crate) struct TauriContext;
+
+/// The context of the fixture app with a system tray.
+#[derive(FromTauriContext)]
+#[config_path = "test/fixture/src-tauri/tray.conf.json"]
+pub(crate) struct TrayContext;
+
+/// How long the tests wait for a message before failing.
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Builds and runs the app, returning the handle of its mock application.
+pub(crate) fn run<C: AsTauriContext>(builder: AppBuilder<Mock, C>) -> MockHandle {
+  builder.build().unwrap().run();
+  MockHandle::current().expect("mock application not created")
+}
+
+/// Waits until a message matching the predicate is sent to the given window.
+pub(crate) fn wait_for<F: Fn(&MockMessage) -> bool>(
+  handle: &MockHandle,
+  window: MockWindowId,
+  predicate: F,
+) -> bool {
+  handle
+    .wait_for_message(window, TIMEOUT, predicate)
+    .is_some()
+}
+
+/// Waits until a script containing the given content is evaluated on the given window.
+pub(crate) fn wait_for_script(handle: &MockHandle, window: MockWindowId, content: &str) -> bool {
+  wait_for(
+    handle,
+    window,
+    |message| matches!(message, MockMessage::EvalScript(js) if js.contains(content)),
+  )
+}
@@ -1,6 +1,21 @@
+#[cfg(any(test, feature = "mock"))]
+pub(crate) mod mock;
 pub(crate) mod wry;
 
-pub use crate::{api::config::WindowConfig, plugin::PluginStore};
+pub use crate::{
+  api::{config::WindowConfig, menu::Menu},
+  global_shortcut::Accelerator,
+};
+
+use serde::Serialize;
+
+use std::{
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+};
 
 /// An event to be posted to the webview event loop.
 pub enum Event {
@@ -8,12 +23,249 @@ pub enum Event {
   Run(crate::SyncTask),
 }
 
+/// A message to be sent to a window.
 pub enum Message {
+  /// Evaluates a script on the webview.
   EvalScript(String),
-  SetWindowTitle(String),
+  /// Updates the window.
+  Window(WindowMessage),
+  /// Posts an event to the event loop.
   Event(Event),
+  /// Updates the system tray.
+  SystemTray(SystemTrayMessage),
+  /// Registers or unregisters a global shortcut.
+  /// The runtimes apply the registrations before `send_message` returns, so it reports whether the OS
+  /// registered the shortcut. The unregistrations can be applied later.
+  GlobalShortcut(GlobalShortcutMessage),
+  /// Stops the event loop and exits the process with the given code.
+  Exit(i32),
+}
+
+/// A runtime update of a window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowMessage {
+  /// Updates the window title.
+  SetTitle(String),
+  /// Sets whether the window is resizable or not.
+  SetResizable(bool),
+  /// Resizes the window.
+  SetSize {
+    /// The new window width.
+    width: f64,
+    /// The new window height.
+    height: f64,
+  },
+  /// Sets the window min size. `None` removes the constraint.
+  SetMinSize {
+    /// The min window width.
+    min_width: Option<f64>,
+    /// The min window height.
+    min_height: Option<f64>,
+  },
+  /// Sets the window max size. `None` removes the constraint.
+  SetMaxSize {
+    /// The max window width.
+    max_width: Option<f64>,
+    /// The max window height.
+    max_height: Option<f64>,
+  },
+  /// Moves the window.
+  SetPosition {
+    /// The horizontal position of the window's top left corner.
+    x: f64,
+    /// The vertical position of the window's top left corner.
+    y: f64,
+  },
+  /// Enters or leaves fullscreen.
+  SetFullscreen(bool),
+  /// Sets whether the window has borders and bars.
+  SetDecorations(bool),
+  /// Sets whether the window is always on top of other windows.
+  SetAlwaysOnTop(bool),
+  /// Minimizes the window.
+  Minimize,
+  /// Restores a minimized window.
+  Unminimize,
+  /// Maximizes the window.
+  Maximize,
+  /// Restores a maximized window.
+  Unmaximize,
+  /// Shows the window.
+  Show,
+  /// Hides the window.
+  Hide,
+  /// Centers the window on its monitor.
+  Center,
+  /// Starts moving the window with the cursor, used by custom title bars.
+  StartDragging,
+  /// Closes the window.
+  Close,
+  /// Sets whether the menu item with the given id can be clicked or not.
+  SetMenuItemEnabled {
+    /// The menu item id.
+    id: String,
+    /// Whether the item can be clicked or not.
+    enabled: bool,
+  },
+  /// Checks or unchecks the menu item with the given id.
+  SetMenuItemChecked {
+    /// The menu item id.
+    id: String,
+    /// Whether the item is checked or not.
+    checked: bool,
+  },
+  /// Updates the title of the menu item with the given id.
+  SetMenuItemTitle {
+    /// The menu item id.
+    id: String,
+    /// The new item title.
+    title: String,
+  },
+}
+
+/// A runtime update of the system tray.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SystemTrayMessage {
+  /// Updates the tray icon.
+  SetIcon(PathBuf),
+  /// Updates the tooltip shown when hovering the tray icon.
+  SetTooltip(String),
+  /// Replaces the tray menu.
+  SetMenu(Menu),
+}
+
+/// A registration update of the global shortcuts, sent to the OS.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GlobalShortcutMessage {
+  /// Starts listening to the shortcut.
+  Register(Accelerator),
+  /// Stops listening to the shortcut.
+  Unregister(Accelerator),
+}
+
+/// Handles the global shortcuts pressed by the user.
+pub type GlobalShortcutHandler = Box<dyn Fn(&Accelerator) + Send + Sync>;
+
+/// An event of the system tray, reported by the runtime.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SystemTrayEvent {
+  /// The tray icon was clicked with the left mouse button.
+  LeftClick,
+  /// The tray icon was clicked with the right mouse button.
+  RightClick,
+  /// The tray icon was double clicked.
+  DoubleClick,
+  /// The tray menu item with the given id was clicked.
+  MenuItemClick {
+    /// The menu item id.
+    id: String,
+  },
+}
+
+/// Handles the events of the system tray.
+pub type SystemTrayEventHandler = Box<dyn Fn(&SystemTrayEvent) + Send + Sync>;
+
+/// The system tray icon of the app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemTray {
+  /// The icon path.
+  pub icon: PathBuf,
+  /// The tooltip shown when hovering the icon.
+  pub tooltip: Option<String>,
+  /// The menu shown when the icon is clicked.
+  pub menu: Option<Menu>,
+}
+
+impl SystemTray {
+  /// Creates a system tray with the given icon.
+  /// Relative paths are resolved from the resource directory.
+  pub fn new(icon: impl Into<PathBuf>) -> Self {
+    Self {
+      icon: icon.into(),
+      tooltip: None,
+      menu: None,
+    }
+  }
+
+  /// Sets the tooltip shown when hovering the icon.
+  pub fn tooltip(mut self, tooltip: impl Into<String>) -> Self {
+    self.tooltip = Some(tooltip.into());
+    self
+  }
+
+  /// Sets the menu shown when the icon is clicked.
+  pub fn menu(mut self, menu: Menu) -> Self {
+    self.menu = Some(menu);
+    self
+  }
+}
+
+/// Resolves a system tray icon path, relative paths being resolved from the resource directory.
+pub(crate) fn resolve_icon_path(icon: PathBuf) -> crate::Result<PathBuf> {
+  if icon.is_absolute() {
+    Ok(icon)
+  } else {
+    crate::api::path::resolve_path(icon, Some(crate::api::path::BaseDirectory::Resource))
+      .map_err(Into::into)
+  }
+}
+
+/// An event of a window, reported by the runtime.
+#[derive(Debug, Clone)]
+pub enum WindowEvent {
+  /// The user requested the window to be closed, e.g. with the title bar close button.
+  CloseRequested(CloseRequest),
+  /// The window was destroyed.
+  Destroyed,
+  /// The window was resized.
+  Resized {
+    /// The new window width.
+    width: f64,
+    /// The new window height.
+    height: f64,
+  },
+  /// The window was moved.
+  Moved {
+    /// The horizontal position of the window's top left corner.
+    x: f64,
+    /// The vertical position of the window's top left corner.
+    y: f64,
+  },
+  /// The window gained (`true`) or lost (`false`) focus.
+  Focused(bool),
+  /// The window scale factor changed, e.g. when it was moved to a monitor with a different DPI.
+  ScaleFactorChanged {
+    /// The new scale factor.
+    scale_factor: f64,
+    /// The new window width.
+    width: f64,
+    /// The new window height.
+    height: f64,
+  },
+  /// The menu item with the given id was clicked.
+  MenuItemClicked(String),
+}
+
+/// A request to close a window.
+#[derive(Debug, Clone, Default)]
+pub struct CloseRequest(Arc<AtomicBool>);
+
+impl CloseRequest {
+  /// Prevents the window from being closed.
+  pub fn prevent_close(&self) {
+    self.0.store(true, Ordering::Relaxed)
+  }
+
+  /// Whether a handler prevented the window from being closed.
+  pub fn is_close_prevented(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
 }
 
+/// Handles the events of a window.
+pub type WindowEventHandler = Box<dyn Fn(&WindowEvent) + Send + Sync>;
+
 /// The window builder.
 pub trait WindowBuilderExt: Sized {
   /// The window type.
@@ -71,6 +323,9 @@ pub trait WindowBuilderExt: Sized {
   /// Whether the window should always be on top of other windows.
   fn always_on_top(self, always_on_top: bool) -> Self;
 
+  /// The window menu bar.
+  fn menu(self, menu: Menu) -> Self;
+
   /// build the window.
   fn finish(self) -> crate::Result<Self::Window>;
 }
@@ -114,6 +369,9 @@ impl<T: WindowBuilderExt> From<&WindowConfig> for WindowBuilder<T> {
     if let Some(y) = config.y {
       window = window.y(y);
     }
+    if let Some(menu) = &config.menu {
+      window = window.menu(menu.clone());
+    }
 
     Self(window)
   }
@@ -133,22 +391,69 @@ pub trait WebviewBuilderExt: Sized {
   /// Sets the init script.
   fn initialization_script(self, init: &str) -> Self;
 
+  /// Registers a custom URI scheme protocol, answering the webview requests to `<uri_scheme>://` URIs.
+  fn register_uri_scheme_protocol(self, uri_scheme: String, protocol: UriSchemeProtocol) -> Self;
+
   /// Builds the webview instance.
   fn finish(self) -> crate::Result<Self::Webview>;
 }
 
+/// The response to a custom URI scheme request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UriSchemeResponse {
+  /// The response body.
+  pub body: Vec<u8>,
+  /// The MIME type of the body, e.g. `text/html`.
+  pub mime_type: String,
+}
+
+impl UriSchemeResponse {
+  /// Creates a response with the given body and MIME type.
+  pub fn new(body: Vec<u8>, mime_type: impl Into<String>) -> Self {
+    Self {
+      body,
+      mime_type: mime_type.into(),
+    }
+  }
+}
+
+/// A custom URI scheme protocol, called with the requested URI, e.g. `my-scheme://localhost/path/to/file.html`.
+pub type UriSchemeProtocol = Arc<dyn Fn(&str) -> crate::Result<UriSchemeResponse> + Send + Sync>;
+
 /// Binds the given callback to a global variable on the window object.
 pub struct Callback<D> {
   /// Function name to bind.
   pub name: String,
-  /// Function callback handler.
-  pub function: Box<dyn FnMut(&D, i32, Vec<String>) -> i32 + Send>,
+  /// Function callback handler, called with the URL of the calling page if the runtime reports it.
+  pub function: Box<dyn FnMut(&D, Option<&str>, i32, Vec<String>) -> i32 + Send>,
 }
 
 /// Webview dispatcher. A thread-safe handle to the webview API.
 pub trait ApplicationDispatcherExt: Clone + Send + Sync + Sized {
+  /// The webview builder.
+  type WebviewBuilder: WebviewBuilderExt;
+  /// The window builder.
+  type WindowBuilder: WindowBuilderExt;
+
+  /// Creates a new window and its webview while the application is running.
+  /// Returns the dispatcher of the new window.
+  ///
+  /// Fails with [`Error::UnsupportedByRuntime`](crate::Error::UnsupportedByRuntime)
+  /// if the runtime can't add windows to a running application.
+  fn create_webview(
+    &self,
+    window_builder: Self::WindowBuilder,
+    webview_builder: Self::WebviewBuilder,
+    callbacks: Vec<Callback<Self>>,
+    window_event_handler: WindowEventHandler,
+  ) -> crate::Result<Self>;
+
   /// Sends a message to the window.
-  fn send_message(&self, message: Message);
+  ///
+  /// Fails with [`Error::UnsupportedByRuntime`](crate::Error::UnsupportedByRuntime)
+  /// if the runtime can't handle the message.
+  /// Scripts, events and exit requests are handled by every runtime.
+  fn send_message(&self, message: Message) -> crate::Result<()>;
 }
 
 /// The application interface.
@@ -161,10 +466,10 @@ pub trait ApplicationExt: Sized {
   /// The window type.
   type Window;
   /// The message dispatcher.
-  type Dispatcher: ApplicationDispatcherExt;
-
-  /// Returns the static plugin collection.
-  fn plugin_store() -> &'static PluginStore<Self::Dispatcher>;
+  type Dispatcher: ApplicationDispatcherExt<
+    WebviewBuilder = Self::WebviewBuilder,
+    WindowBuilder = Self::WindowBuilder,
+  >;
 
   /// Creates a new application.
   fn new() -> crate::Result<Self>;
@@ -172,15 +477,32 @@ pub trait ApplicationExt: Sized {
   /// Gets the message dispatcher for the given window.
   fn dispatcher(&self, window: &Self::Window) -> Self::Dispatcher;
 
+  /// Gets a message dispatcher that isn't bound to a window, e.g. for apps without windows.
+  /// It ignores the window and script messages.
+  fn app_dispatcher(&self) -> Self::Dispatcher;
+
+  /// Creates the system tray icon.
+  /// Its events are reported to the given handler.
+  fn create_system_tray(
+    &mut self,
+    system_tray: SystemTray,
+    system_tray_event_handler: SystemTrayEventHandler,
+  ) -> crate::Result<()>;
+
+  /// Sets the handler of the registered global shortcuts.
+  fn set_global_shortcut_handler(&mut self, global_shortcut_handler: GlobalShortcutHandler);
+
   /// Creates a new window.
   fn create_window(&self, window_builder: Self::WindowBuilder) -> crate::Result<Self::Window>;
 
   /// Creates a new webview.
+  /// The window events are reported to the given handler, if the runtime reports them.
   fn create_webview(
     &mut self,
     webview_builder: Self::WebviewBuilder,
     window: Self::Window,
     callbacks: Vec<Callback<Self::Dispatcher>>,
+    window_event_handler: WindowEventHandler,
   ) -> crate::Result<()>;
 
   /// Run the application.
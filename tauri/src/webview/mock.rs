@@ -0,0 +1,833 @@
+use super::{
+  Accelerator, ApplicationDispatcherExt, ApplicationExt, Callback, Event, GlobalShortcutHandler,
+  GlobalShortcutMessage, Menu, Message, SystemTray, SystemTrayEvent, SystemTrayEventHandler,
+  SystemTrayMessage, UriSchemeProtocol, UriSchemeResponse, WebviewBuilderExt, WindowBuilderExt,
+  WindowEvent, WindowEventHandler, WindowMessage,
+};
+
+use std::{
+  cell::RefCell,
+  collections::{HashMap, HashSet},
+  fmt,
+  sync::{Arc, Condvar, Mutex},
+  time::{Duration, Instant},
+};
+
+/// The identifier of a mock window.
+pub type MockWindowId = usize;
+
+/// The attributes a mock window was built with.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MockWindowAttributes {
+  /// The horizontal position of the window's top left corner.
+  pub x: Option<f64>,
+  /// The vertical position of the window's top left corner.
+  pub y: Option<f64>,
+  /// Window width.
+  pub width: f64,
+  /// Window height.
+  pub height: f64,
+  /// Window min width.
+  pub min_width: Option<f64>,
+  /// Window min height.
+  pub min_height: Option<f64>,
+  /// Window max width.
+  pub max_width: Option<f64>,
+  /// Window max height.
+  pub max_height: Option<f64>,
+  /// Whether the window is resizable or not.
+  pub resizable: bool,
+  /// The title of the window.
+  pub title: String,
+  /// Whether the window starts in fullscreen or not.
+  pub fullscreen: bool,
+  /// Whether the window starts maximized or not.
+  pub maximized: bool,
+  /// Whether the window is visible or not.
+  pub visible: bool,
+  /// Whether the window is transparent or not.
+  pub transparent: bool,
+  /// Whether the window has borders and bars.
+  pub decorations: bool,
+  /// Whether the window is always on top of other windows.
+  pub always_on_top: bool,
+  /// The window menu bar, updated by the menu item messages.
+  pub menu: Option<Menu>,
+}
+
+impl WindowBuilderExt for MockWindowAttributes {
+  type Window = Self;
+
+  fn new() -> Self {
+    Default::default()
+  }
+
+  fn x(mut self, x: f64) -> Self {
+    self.x = Some(x);
+    self
+  }
+
+  fn y(mut self, y: f64) -> Self {
+    self.y = Some(y);
+    self
+  }
+
+  fn width(mut self, width: f64) -> Self {
+    self.width = width;
+    self
+  }
+
+  fn height(mut self, height: f64) -> Self {
+    self.height = height;
+    self
+  }
+
+  fn min_width(mut self, min_width: f64) -> Self {
+    self.min_width = Some(min_width);
+    self
+  }
+
+  fn min_height(mut self, min_height: f64) -> Self {
+    self.min_height = Some(min_height);
+    self
+  }
+
+  fn max_width(mut self, max_width: f64) -> Self {
+    self.max_width = Some(max_width);
+    self
+  }
+
+  fn max_height(mut self, max_height: f64) -> Self {
+    self.max_height = Some(max_height);
+    self
+  }
+
+  fn resizable(mut self, resizable: bool) -> Self {
+    self.resizable = resizable;
+    self
+  }
+
+  fn title(mut self, title: String) -> Self {
+    self.title = title;
+    self
+  }
+
+  fn fullscreen(mut self, fullscreen: bool) -> Self {
+    self.fullscreen = fullscreen;
+    self
+  }
+
+  fn maximized(mut self, maximized: bool) -> Self {
+    self.maximized = maximized;
+    self
+  }
+
+  fn visible(mut self, visible: bool) -> Self {
+    self.visible = visible;
+    self
+  }
+
+  fn transparent(mut self, transparent: bool) -> Self {
+    self.transparent = transparent;
+    self
+  }
+
+  fn decorations(mut self, decorations: bool) -> Self {
+    self.decorations = decorations;
+    self
+  }
+
+  fn always_on_top(mut self, always_on_top: bool) -> Self {
+    self.always_on_top = always_on_top;
+    self
+  }
+
+  fn menu(mut self, menu: Menu) -> Self {
+    self.menu = Some(menu);
+    self
+  }
+
+  fn finish(self) -> crate::Result<Self::Window> {
+    Ok(self)
+  }
+}
+
+/// The custom URI scheme protocols of a mock webview, compared by their schemes.
+#[derive(Default, Clone)]
+pub struct MockUriSchemeProtocols(HashMap<String, UriSchemeProtocol>);
+
+impl MockUriSchemeProtocols {
+  /// Whether a protocol is registered with the given scheme.
+  pub fn contains(&self, uri_scheme: &str) -> bool {
+    self.0.contains_key(uri_scheme)
+  }
+}
+
+impl fmt::Debug for MockUriSchemeProtocols {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_set().entries(self.0.keys()).finish()
+  }
+}
+
+impl PartialEq for MockUriSchemeProtocols {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.len() == other.0.len() && self.0.keys().all(|uri_scheme| other.contains(uri_scheme))
+  }
+}
+
+/// The attributes a mock webview was built with.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MockWebviewAttributes {
+  /// The webview url.
+  pub url: Option<String>,
+  /// The initialization scripts, in the order they were added.
+  pub initialization_scripts: Vec<String>,
+  /// The custom URI scheme protocols.
+  pub uri_scheme_protocols: MockUriSchemeProtocols,
+}
+
+impl WebviewBuilderExt for MockWebviewAttributes {
+  type Webview = Self;
+
+  fn new() -> Self {
+    Default::default()
+  }
+
+  fn url(mut self, url: String) -> Self {
+    self.url.replace(url);
+    self
+  }
+
+  fn initialization_script(mut self, init: &str) -> Self {
+    self.initialization_scripts.push(init.to_string());
+    self
+  }
+
+  fn register_uri_scheme_protocol(
+    mut self,
+    uri_scheme: String,
+    protocol: UriSchemeProtocol,
+  ) -> Self {
+    self.uri_scheme_protocols.0.insert(uri_scheme, protocol);
+    self
+  }
+
+  fn finish(self) -> crate::Result<Self::Webview> {
+    Ok(self)
+  }
+}
+
+/// A window created by the mock application.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockWindow {
+  /// The window identifier.
+  pub id: MockWindowId,
+  /// The window attributes.
+  pub attributes: MockWindowAttributes,
+  /// The webview attached to the window, if it was already created.
+  pub webview: Option<MockWebviewAttributes>,
+  /// The URL of the page loaded in the webview, reported to its callbacks.
+  pub location: Option<String>,
+}
+
+/// A message sent to a mock window, recorded by the mock dispatcher.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockMessage {
+  /// A script was evaluated.
+  EvalScript(String),
+  /// The window was updated.
+  Window(WindowMessage),
+  /// An event was sent to the event loop. `Event::Run` tasks are executed before recording it.
+  Event,
+  /// The system tray was updated.
+  SystemTray(SystemTrayMessage),
+  /// A global shortcut was registered or unregistered.
+  GlobalShortcut(GlobalShortcutMessage),
+  /// The application was asked to exit with the given code. The process keeps running.
+  Exit(i32),
+}
+
+#[derive(Default)]
+struct MockState {
+  windows: Mutex<Vec<MockWindow>>,
+  messages: Mutex<Vec<(Option<MockWindowId>, MockMessage)>>,
+  messages_changed: Condvar,
+  /// The webview callbacks by window, indexed by name so a running callback doesn't block the others.
+  callbacks: Mutex<HashMap<MockWindowId, HashMap<String, Arc<Mutex<Callback<MockDispatcher>>>>>>,
+  window_event_handlers: Mutex<HashMap<MockWindowId, Arc<dyn Fn(&WindowEvent) + Send + Sync>>>,
+  system_tray: Mutex<Option<SystemTray>>,
+  system_tray_event_handler: Mutex<Option<Arc<dyn Fn(&SystemTrayEvent) + Send + Sync>>>,
+  global_shortcuts: Mutex<HashSet<Accelerator>>,
+  /// The global shortcuts used by other apps, that the OS doesn't register.
+  unavailable_global_shortcuts: Mutex<HashSet<Accelerator>>,
+  global_shortcut_handler: Mutex<Option<Arc<dyn Fn(&Accelerator) + Send + Sync>>>,
+}
+
+impl MockState {
+  fn create_window(&self, window_builder: MockWindowAttributes) -> crate::Result<MockWindowId> {
+    let mut windows = self.windows.lock().unwrap();
+    let id = windows.len();
+    windows.push(MockWindow {
+      id,
+      attributes: window_builder.finish()?,
+      webview: None,
+      location: None,
+    });
+    Ok(id)
+  }
+
+  fn create_webview(
+    &self,
+    webview_builder: MockWebviewAttributes,
+    window: MockWindowId,
+    callbacks: Vec<Callback<MockDispatcher>>,
+    window_event_handler: WindowEventHandler,
+  ) -> crate::Result<()> {
+    {
+      let mut windows = self.windows.lock().unwrap();
+      let window = windows.get_mut(window).ok_or(crate::Error::CreateWebview)?;
+      let webview = webview_builder.finish()?;
+      window.location = webview.url.clone();
+      window.webview.replace(webview);
+    }
+    self.callbacks.lock().unwrap().insert(
+      window,
+      callbacks
+        .into_iter()
+        .map(|callback| (callback.name.clone(), Arc::new(Mutex::new(callback))))
+        .collect(),
+    );
+    self
+      .window_event_handlers
+      .lock()
+      .unwrap()
+      .insert(window, window_event_handler.into());
+    Ok(())
+  }
+
+  /// Applies a menu item update to the menu of the window.
+  fn update_menu_item(&self, window: MockWindowId, message: &WindowMessage) {
+    let mut windows = self.windows.lock().unwrap();
+    let item = match message {
+      WindowMessage::SetMenuItemEnabled { id, .. }
+      | WindowMessage::SetMenuItemChecked { id, .. }
+      | WindowMessage::SetMenuItemTitle { id, .. } => windows
+        .get_mut(window)
+        .and_then(|window| window.attributes.menu.as_mut())
+        .and_then(|menu| menu.item_mut(id)),
+      _ => None,
+    };
+    if let Some(item) = item {
+      match message {
+        WindowMessage::SetMenuItemEnabled { enabled, .. } => item.enabled = *enabled,
+        WindowMessage::SetMenuItemChecked { checked, .. } => item.checked = Some(*checked),
+        WindowMessage::SetMenuItemTitle { title, .. } => item.title = title.clone(),
+        _ => {}
+      }
+    }
+  }
+
+  /// Applies an update to the system tray.
+  fn update_system_tray(&self, message: &SystemTrayMessage) {
+    if let Some(system_tray) = self.system_tray.lock().unwrap().as_mut() {
+      match message {
+        SystemTrayMessage::SetIcon(icon) => system_tray.icon = icon.clone(),
+        SystemTrayMessage::SetTooltip(tooltip) => system_tray.tooltip = Some(tooltip.clone()),
+        SystemTrayMessage::SetMenu(menu) => system_tray.menu = Some(menu.clone()),
+      }
+    }
+  }
+
+  /// Applies a registration update to the global shortcuts listened to.
+  /// Fails to register the shortcuts used by other apps.
+  fn update_global_shortcuts(&self, message: &GlobalShortcutMessage) -> crate::Result<()> {
+    let mut global_shortcuts = self.global_shortcuts.lock().unwrap();
+    match message {
+      GlobalShortcutMessage::Register(accelerator) => {
+        if self
+          .unavailable_global_shortcuts
+          .lock()
+          .unwrap()
+          .contains(accelerator)
+        {
+          return Err(crate::Error::RegisterGlobalShortcut(
+            accelerator.to_string(),
+          ));
+        }
+        global_shortcuts.insert(*accelerator);
+      }
+      GlobalShortcutMessage::Unregister(accelerator) => {
+        global_shortcuts.remove(accelerator);
+      }
+    }
+    Ok(())
+  }
+
+  /// Destroys the window, reporting the `Destroyed` event.
+  fn destroy_window(&self, window: MockWindowId) {
+    self.callbacks.lock().unwrap().remove(&window);
+    let handler = self.window_event_handlers.lock().unwrap().remove(&window);
+    if let Some(handler) = handler {
+      handler(&WindowEvent::Destroyed);
+    }
+  }
+}
+
+thread_local! {
+  static CURRENT: RefCell<Option<MockHandle>> = RefCell::new(None);
+}
+
+/// A handle to inspect and drive a mock application.
+#[derive(Clone)]
+pub struct MockHandle {
+  state: Arc<MockState>,
+}
+
+impl MockHandle {
+  /// Gets the handle of the last mock application created on the current thread.
+  pub fn current() -> Option<Self> {
+    CURRENT.with(|current| current.borrow().clone())
+  }
+
+  /// The windows created by the application.
+  pub fn windows(&self) -> Vec<MockWindow> {
+    self.state.windows.lock().unwrap().clone()
+  }
+
+  /// The messages sent to the application, in the order they were received.
+  /// The messages sent with the app dispatcher aren't bound to a window.
+  pub fn messages(&self) -> Vec<(Option<MockWindowId>, MockMessage)> {
+    self.state.messages.lock().unwrap().clone()
+  }
+
+  /// The system tray created by the application, with the updates it received.
+  pub fn system_tray(&self) -> Option<SystemTray> {
+    self.state.system_tray.lock().unwrap().clone()
+  }
+
+  /// Reports an event to the system tray, like the OS does.
+  pub fn send_system_tray_event(&self, event: SystemTrayEvent) {
+    let handler = self.state.system_tray_event_handler.lock().unwrap().clone();
+    if let Some(handler) = handler {
+      handler(&event);
+    }
+  }
+
+  /// Presses a global shortcut, like the user does.
+  /// Returns `false` if the shortcut isn't registered.
+  pub fn press_global_shortcut(&self, accelerator: &Accelerator) -> bool {
+    if !self
+      .state
+      .global_shortcuts
+      .lock()
+      .unwrap()
+      .contains(accelerator)
+    {
+      return false;
+    }
+    let handler = self.state.global_shortcut_handler.lock().unwrap().clone();
+    if let Some(handler) = handler {
+      handler(accelerator);
+    }
+    true
+  }
+
+  /// Makes another app use the global shortcut, so the OS doesn't register it for the application.
+  pub fn use_global_shortcut_elsewhere(&self, accelerator: Accelerator) {
+    self
+      .state
+      .unavailable_global_shortcuts
+      .lock()
+      .unwrap()
+      .insert(accelerator);
+  }
+
+  /// Calls the `__TAURI_INVOKE_HANDLER__` callback of the given window with the given payload,
+  /// like the webview does when JS calls `window.__TAURI_INVOKE_HANDLER__(payload)`.
+  pub fn invoke(&self, window: MockWindowId, payload: impl Into<String>) -> crate::Result<()> {
+    self.call(window, "__TAURI_INVOKE_HANDLER__", vec![payload.into()])
+  }
+
+  /// Loads the given URL in the webview of the given window, like following a link.
+  pub fn navigate(&self, window: MockWindowId, url: impl Into<String>) {
+    if let Some(window) = self.state.windows.lock().unwrap().get_mut(window) {
+      window.location.replace(url.into());
+    }
+  }
+
+  /// Requests the URI from the webview of the given window, like the webview does for custom URI schemes.
+  pub fn request(&self, window: MockWindowId, uri: &str) -> crate::Result<UriSchemeResponse> {
+    let uri_scheme = uri.splitn(2, "://").next().unwrap_or_default();
+    let protocol = self
+      .state
+      .windows
+      .lock()
+      .unwrap()
+      .get(window)
+      .and_then(|window| window.webview.as_ref())
+      .ok_or(crate::Error::WebviewNotFound)?
+      .uri_scheme_protocols
+      .0
+      .get(uri_scheme)
+      .cloned()
+      .ok_or_else(|| crate::Error::UnknownUriScheme(uri_scheme.to_string()))?;
+    protocol(uri)
+  }
+
+  /// Calls a callback bound to the given window from the page loaded in its webview.
+  ///
+  /// The callback can use the mock application, e.g. to call the callbacks of other windows.
+  pub fn call(&self, window: MockWindowId, name: &str, args: Vec<String>) -> crate::Result<()> {
+    let dispatcher = MockDispatcher {
+      state: self.state.clone(),
+      current_window: Some(window),
+    };
+    let location = self
+      .state
+      .windows
+      .lock()
+      .unwrap()
+      .get(window)
+      .and_then(|window| window.location.clone());
+    let callback = self
+      .state
+      .callbacks
+      .lock()
+      .unwrap()
+      .get(&window)
+      .and_then(|callbacks| callbacks.get(name))
+      .cloned()
+      .ok_or(crate::Error::WebviewNotFound)?;
+    let mut callback = callback.lock().unwrap();
+    (callback.function)(&dispatcher, location.as_deref(), 0, args);
+    Ok(())
+  }
+
+  /// Reports an event to the given window, like the OS does.
+  /// Returns `false` if the default behavior was prevented, e.g. the window wasn't closed.
+  ///
+  /// A `CloseRequested` event that isn't prevented destroys the window.
+  pub fn send_window_event(&self, window: MockWindowId, event: WindowEvent) -> bool {
+    if let WindowEvent::Destroyed = event {
+      self.state.destroy_window(window);
+      return true;
+    }
+    let handler = self
+      .state
+      .window_event_handlers
+      .lock()
+      .unwrap()
+      .get(&window)
+      .cloned();
+    if let Some(handler) = handler {
+      handler(&event);
+    }
+    match event {
+      WindowEvent::CloseRequested(request) if request.is_close_prevented() => false,
+      WindowEvent::CloseRequested(_) => {
+        self.state.destroy_window(window);
+        true
+      }
+      _ => true,
+    }
+  }
+
+  /// Waits until a message matching the predicate is sent to the given window.
+  /// Returns `None` if the timeout elapses first.
+  pub fn wait_for_message<F: Fn(&MockMessage) -> bool>(
+    &self,
+    window: MockWindowId,
+    timeout: Duration,
+    predicate: F,
+  ) -> Option<MockMessage> {
+    self.wait_for(Some(window), timeout, predicate)
+  }
+
+  /// Waits until a message matching the predicate is sent with the app dispatcher.
+  /// Returns `None` if the timeout elapses first.
+  pub fn wait_for_app_message<F: Fn(&MockMessage) -> bool>(
+    &self,
+    timeout: Duration,
+    predicate: F,
+  ) -> Option<MockMessage> {
+    self.wait_for(None, timeout, predicate)
+  }
+
+  fn wait_for<F: Fn(&MockMessage) -> bool>(
+    &self,
+    window: Option<MockWindowId>,
+    timeout: Duration,
+    predicate: F,
+  ) -> Option<MockMessage> {
+    let deadline = Instant::now() + timeout;
+    let mut messages = self.state.messages.lock().unwrap();
+    loop {
+      if let Some((_, message)) = messages
+        .iter()
+        .find(|(id, message)| *id == window && predicate(message))
+      {
+        return Some(message.clone());
+      }
+      let now = Instant::now();
+      if now >= deadline {
+        return None;
+      }
+      messages = self
+        .state
+        .messages_changed
+        .wait_timeout(messages, deadline - now)
+        .unwrap()
+        .0;
+    }
+  }
+}
+
+/// The mock message dispatcher.
+#[derive(Clone)]
+pub struct MockDispatcher {
+  state: Arc<MockState>,
+  /// The window of the dispatcher, `None` for the app dispatcher.
+  current_window: Option<MockWindowId>,
+}
+
+impl ApplicationDispatcherExt for MockDispatcher {
+  type WebviewBuilder = MockWebviewAttributes;
+  type WindowBuilder = MockWindowAttributes;
+
+  fn create_webview(
+    &self,
+    window_builder: Self::WindowBuilder,
+    webview_builder: Self::WebviewBuilder,
+    callbacks: Vec<Callback<Self>>,
+    window_event_handler: WindowEventHandler,
+  ) -> crate::Result<Self> {
+    let window = self.state.create_window(window_builder)?;
+    self
+      .state
+      .create_webview(webview_builder, window, callbacks, window_event_handler)?;
+    Ok(Self {
+      state: self.state.clone(),
+      current_window: Some(window),
+    })
+  }
+
+  fn send_message(&self, message: Message) -> crate::Result<()> {
+    let message = match (message, self.current_window) {
+      (Message::EvalScript(js), Some(_)) => MockMessage::EvalScript(js),
+      (Message::Window(message), Some(window)) => {
+        self.state.update_menu_item(window, &message);
+        MockMessage::Window(message)
+      }
+      // the app dispatcher isn't bound to a window
+      (Message::EvalScript(_), None) | (Message::Window(_), None) => return Ok(()),
+      (Message::Event(event), _) => {
+        match event {
+          Event::Run(task) => task(),
+        }
+        MockMessage::Event
+      }
+      (Message::SystemTray(message), _) => {
+        self.state.update_system_tray(&message);
+        MockMessage::SystemTray(message)
+      }
+      (Message::GlobalShortcut(message), _) => {
+        self.state.update_global_shortcuts(&message)?;
+        MockMessage::GlobalShortcut(message)
+      }
+      (Message::Exit(code), _) => MockMessage::Exit(code),
+    };
+    self
+      .state
+      .messages
+      .lock()
+      .unwrap()
+      .push((self.current_window, message.clone()));
+    self.state.messages_changed.notify_all();
+    if let (MockMessage::Window(WindowMessage::Close), Some(window)) =
+      (&message, self.current_window)
+    {
+      self.state.destroy_window(window);
+    }
+    Ok(())
+  }
+}
+
+/// An in-memory application that doesn't need a display.
+///
+/// Every message sent to its windows is recorded and `Event::Run` tasks are executed inline.
+/// Window and system tray events are reported with [`MockHandle::send_window_event`]
+/// and [`MockHandle::send_system_tray_event`], and global shortcuts are pressed with
+/// [`MockHandle::press_global_shortcut`].
+/// Unlike the other flavors, `run` doesn't block:
+/// use [`MockHandle::current`] to inspect the application and call its webview callbacks.
+pub struct MockApplication {
+  state: Arc<MockState>,
+}
+
+impl ApplicationExt for MockApplication {
+  type WebviewBuilder = MockWebviewAttributes;
+  type WindowBuilder = MockWindowAttributes;
+  type Window = MockWindowId;
+  type Dispatcher = MockDispatcher;
+
+  fn new() -> crate::Result<Self> {
+    let state = Arc::new(MockState::default());
+    CURRENT.with(|current| {
+      current.borrow_mut().replace(MockHandle {
+        state: state.clone(),
+      })
+    });
+    Ok(Self { state })
+  }
+
+  fn dispatcher(&self, window: &Self::Window) -> Self::Dispatcher {
+    MockDispatcher {
+      state: self.state.clone(),
+      current_window: Some(*window),
+    }
+  }
+
+  fn app_dispatcher(&self) -> Self::Dispatcher {
+    MockDispatcher {
+      state: self.state.clone(),
+      current_window: None,
+    }
+  }
+
+  fn create_system_tray(
+    &mut self,
+    system_tray: SystemTray,
+    system_tray_event_handler: SystemTrayEventHandler,
+  ) -> crate::Result<()> {
+    self.state.system_tray.lock().unwrap().replace(system_tray);
+    self
+      .state
+      .system_tray_event_handler
+      .lock()
+      .unwrap()
+      .replace(system_tray_event_handler.into());
+    Ok(())
+  }
+
+  fn set_global_shortcut_handler(&mut self, global_shortcut_handler: GlobalShortcutHandler) {
+    self
+      .state
+      .global_shortcut_handler
+      .lock()
+      .unwrap()
+      .replace(global_shortcut_handler.into());
+  }
+
+  fn create_window(&self, window_builder: Self::WindowBuilder) -> crate::Result<Self::Window> {
+    self.state.create_window(window_builder)
+  }
+
+  fn create_webview(
+    &mut self,
+    webview_builder: Self::WebviewBuilder,
+    window: Self::Window,
+    callbacks: Vec<Callback<Self::Dispatcher>>,
+    window_event_handler: WindowEventHandler,
+  ) -> crate::Result<()> {
+    self
+      .state
+      .create_webview(webview_builder, window, callbacks, window_event_handler)
+  }
+
+  fn run(self) {}
+}
+
+#[cfg(test)]
+mod test {
+  use super::{
+    MockApplication, MockHandle, MockMessage, MockWebviewAttributes, MockWindowAttributes,
+  };
+  use crate::{
+    flavors::Mock,
+    test::{run, TauriContext, TIMEOUT},
+    AppBuilder, ApplicationExt, Callback, WebviewBuilderExt, WindowBuilderExt, WindowMessage,
+  };
+  use serde::Deserialize;
+  use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  };
+
+  #[derive(Deserialize)]
+  struct SetTitle {
+    title: String,
+  }
+
+  #[test]
+  fn creates_windows_from_config() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new());
+    let windows = handle.windows();
+    assert_eq!(windows.len(), 1);
+    assert_eq!(windows[0].attributes.title, "Tauri App");
+    let webview = windows[0].webview.clone().expect("webview not created");
+    assert_eq!(webview.url, Some("http://localhost:4000".to_string()));
+    assert!(webview
+      .initialization_scripts
+      .iter()
+      .any(|script| script.contains(r#"window.__TAURI__.currentWindow = { label: "main" }"#)));
+  }
+
+  #[test]
+  fn runs_invoke_handler() {
+    let handle = run(AppBuilder::<Mock, TauriContext>::new().invoke_handler(
+      |webview_manager, arg| async move {
+        let payload: SetTitle = serde_json::from_str(&arg)?;
+        webview_manager.current_webview()?.set_title(&payload.title)
+      },
+    ));
+    handle.invoke(0, r#"{"title":"new title"}"#).unwrap();
+    let message = handle.wait_for_message(0, TIMEOUT, |message| {
+      matches!(message, MockMessage::Window(_))
+    });
+    assert_eq!(
+      message,
+      Some(MockMessage::Window(WindowMessage::SetTitle(
+        "new title".to_string()
+      )))
+    );
+    assert!(handle.invoke(1, "{}").is_err());
+  }
+
+  #[test]
+  fn calls_back_into_the_application() {
+    let mut application = MockApplication::new().unwrap();
+    let handle = MockHandle::current().expect("mock application not created");
+    let window = application
+      .create_window(MockWindowAttributes::new())
+      .unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_ = calls.clone();
+    let handle_ = handle.clone();
+    application
+      .create_webview(
+        MockWebviewAttributes::new(),
+        window,
+        vec![
+          Callback {
+            name: "outer".to_string(),
+            function: Box::new(move |_, _, _, _| {
+              handle_.call(window, "inner", Vec::new()).unwrap();
+              0
+            }),
+          },
+          Callback {
+            name: "inner".to_string(),
+            function: Box::new(move |_, _, _, _| {
+              calls_.fetch_add(1, Ordering::SeqCst);
+              0
+            }),
+          },
+        ],
+        Box::new(|_| {}),
+      )
+      .unwrap();
+
+    handle.call(window, "outer", Vec::new()).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+  }
+}
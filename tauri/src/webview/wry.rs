@@ -1,204 +1,1208 @@
 use super::{
-  ApplicationDispatcherExt, ApplicationExt, Callback, Event, Message, WebviewBuilderExt,
-  WindowBuilderExt,
+  Accelerator, ApplicationDispatcherExt, ApplicationExt, Callback, CloseRequest, Event,
+  GlobalShortcutHandler, GlobalShortcutMessage, Menu, Message, SystemTray, SystemTrayEvent,
+  SystemTrayEventHandler, SystemTrayMessage, UriSchemeProtocol, WebviewBuilderExt,
+  WindowBuilderExt, WindowEvent, WindowEventHandler, WindowMessage,
+};
+use crate::{
+  api::{
+    menu::{MenuEntry, MenuItem, NativeMenuItem},
+    rpc::format_js_string,
+  },
+  global_shortcut::Key,
 };
 
-use wry::{ApplicationDispatcher, ApplicationExt as _, WebviewMessage, WindowExt, WindowMessage};
+use serde_json::Value as JsonValue;
+use wry::{
+  application::{
+    accelerator::{Accelerator as WryAccelerator, AcceleratorId},
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition},
+    event::{Event as WryEvent, TrayEvent, WindowEvent as WryWindowEvent},
+    event_loop::{ControlFlow, EventLoop, EventLoopProxy, EventLoopWindowTarget},
+    global_shortcut::{GlobalShortcut, ShortcutManager},
+    keyboard::{KeyCode, ModifiersState},
+    menu::{
+      ContextMenu, CustomMenuItem, MenuBar, MenuId, MenuItem as WryNativeMenuItem,
+      MenuItemAttributes, MenuType,
+    },
+    system_tray::{SystemTray as WrySystemTray, SystemTrayBuilder},
+    window::{Fullscreen, Window, WindowBuilder, WindowId},
+  },
+  webview::{WebView, WebViewBuilder},
+};
 
-use once_cell::sync::Lazy;
+use std::{
+  collections::HashMap,
+  path::PathBuf,
+  sync::{
+    mpsc::{channel, Sender},
+    Arc, Mutex,
+  },
+  thread::ThreadId,
+};
 
-use crate::plugin::PluginStore;
+/// The window attributes, applied when the window is built on the event loop thread.
+#[derive(Debug, Clone)]
+pub struct WryWindowAttributes {
+  x: Option<f64>,
+  y: Option<f64>,
+  width: f64,
+  height: f64,
+  min_width: Option<f64>,
+  min_height: Option<f64>,
+  max_width: Option<f64>,
+  max_height: Option<f64>,
+  resizable: bool,
+  title: String,
+  fullscreen: bool,
+  maximized: bool,
+  visible: bool,
+  transparent: bool,
+  decorations: bool,
+  always_on_top: bool,
+  menu: Option<Menu>,
+}
+
+impl Default for WryWindowAttributes {
+  fn default() -> Self {
+    Self {
+      x: None,
+      y: None,
+      width: 800.0,
+      height: 600.0,
+      min_width: None,
+      min_height: None,
+      max_width: None,
+      max_height: None,
+      resizable: true,
+      title: String::new(),
+      fullscreen: false,
+      maximized: false,
+      visible: true,
+      transparent: false,
+      decorations: true,
+      always_on_top: false,
+      menu: None,
+    }
+  }
+}
 
-use std::sync::{Arc, Mutex};
+/// The window builder.
+#[derive(Default)]
+pub struct WryWindowBuilder {
+  attributes: WryWindowAttributes,
+}
 
-impl WindowBuilderExt for wry::AppWindowAttributes {
-  type Window = Self;
+impl WindowBuilderExt for WryWindowBuilder {
+  type Window = WryWindowAttributes;
 
   fn new() -> Self {
     Default::default()
   }
 
   fn x(mut self, x: f64) -> Self {
-    self.x = Some(x);
+    self.attributes.x = Some(x);
     self
   }
 
   fn y(mut self, y: f64) -> Self {
-    self.y = Some(y);
+    self.attributes.y = Some(y);
     self
   }
 
   fn width(mut self, width: f64) -> Self {
-    self.width = width;
+    self.attributes.width = width;
     self
   }
 
   fn height(mut self, height: f64) -> Self {
-    self.height = height;
+    self.attributes.height = height;
     self
   }
 
   fn min_width(mut self, min_width: f64) -> Self {
-    self.min_width = Some(min_width);
+    self.attributes.min_width = Some(min_width);
     self
   }
 
   fn min_height(mut self, min_height: f64) -> Self {
-    self.min_height = Some(min_height);
+    self.attributes.min_height = Some(min_height);
     self
   }
 
   fn max_width(mut self, max_width: f64) -> Self {
-    self.max_width = Some(max_width);
+    self.attributes.max_width = Some(max_width);
     self
   }
 
   fn max_height(mut self, max_height: f64) -> Self {
-    self.max_height = Some(max_height);
+    self.attributes.max_height = Some(max_height);
     self
   }
 
   fn resizable(mut self, resizable: bool) -> Self {
-    self.resizable = resizable;
+    self.attributes.resizable = resizable;
     self
   }
 
   fn title(mut self, title: String) -> Self {
-    self.title = title;
+    self.attributes.title = title;
     self
   }
 
   fn fullscreen(mut self, fullscreen: bool) -> Self {
-    self.fullscreen = fullscreen;
+    self.attributes.fullscreen = fullscreen;
     self
   }
 
   fn maximized(mut self, maximized: bool) -> Self {
-    self.maximized = maximized;
+    self.attributes.maximized = maximized;
     self
   }
 
   fn visible(mut self, visible: bool) -> Self {
-    self.visible = visible;
+    self.attributes.visible = visible;
     self
   }
 
   fn transparent(mut self, transparent: bool) -> Self {
-    self.transparent = transparent;
+    self.attributes.transparent = transparent;
     self
   }
 
   fn decorations(mut self, decorations: bool) -> Self {
-    self.decorations = decorations;
+    self.attributes.decorations = decorations;
     self
   }
 
   fn always_on_top(mut self, always_on_top: bool) -> Self {
-    self.always_on_top = always_on_top;
+    self.attributes.always_on_top = always_on_top;
     self
   }
 
-  /// build the window.
+  fn menu(mut self, menu: Menu) -> Self {
+    self.attributes.menu = Some(menu);
+    self
+  }
+
+  /// build the window attributes, failing if an accelerator of the menu can't be registered.
   fn finish(self) -> crate::Result<Self::Window> {
-    Ok(self)
+    if let Some(menu) = &self.attributes.menu {
+      check_menu_accelerators(&menu.items)?;
+    }
+    Ok(self.attributes)
   }
 }
 
+/// The webview attributes, applied when the webview is built on the event loop thread.
+#[derive(Default, Clone)]
+pub struct WryWebviewAttributes {
+  url: Option<String>,
+  initialization_scripts: Vec<String>,
+  uri_scheme_protocols: Vec<(String, UriSchemeProtocol)>,
+}
+
 /// The webview builder.
-impl WebviewBuilderExt for wry::WebViewAttributes {
+#[derive(Default)]
+pub struct WryWebviewBuilder {
+  attributes: WryWebviewAttributes,
+}
+
+impl WebviewBuilderExt for WryWebviewBuilder {
   /// The webview object that this builder creates.
-  type Webview = Self;
+  type Webview = WryWebviewAttributes;
 
   fn new() -> Self {
     Default::default()
   }
 
   fn url(mut self, url: String) -> Self {
-    self.url.replace(url);
+    self.attributes.url.replace(url);
     self
   }
 
   fn initialization_script(mut self, init: &str) -> Self {
-    self.initialization_script.push(init.to_string());
+    self
+      .attributes
+      .initialization_scripts
+      .push(init.to_string());
+    self
+  }
+
+  fn register_uri_scheme_protocol(
+    mut self,
+    uri_scheme: String,
+    protocol: UriSchemeProtocol,
+  ) -> Self {
+    self
+      .attributes
+      .uri_scheme_protocols
+      .push((uri_scheme, protocol));
     self
   }
 
   fn finish(self) -> crate::Result<Self::Webview> {
-    Ok(self)
+    Ok(self.attributes)
+  }
+}
+
+/// The menu items of a window or of the system tray, keyed by the id of their wry item.
+type MenuItems = HashMap<MenuId, (String, CustomMenuItem)>;
+
+/// The wry menus built from a [`Menu`]: the window menu bars and the system tray menus.
+trait WryMenu {
+  fn new() -> Self;
+
+  fn add_item(&mut self, item: MenuItemAttributes<'_>) -> CustomMenuItem;
+
+  fn add_submenu(&mut self, title: &str, enabled: bool, submenu: Self);
+
+  fn add_separator(&mut self);
+}
+
+impl WryMenu for MenuBar {
+  fn new() -> Self {
+    MenuBar::new()
+  }
+
+  fn add_item(&mut self, item: MenuItemAttributes<'_>) -> CustomMenuItem {
+    MenuBar::add_item(self, item)
+  }
+
+  fn add_submenu(&mut self, title: &str, enabled: bool, submenu: Self) {
+    MenuBar::add_submenu(self, title, enabled, submenu)
+  }
+
+  fn add_separator(&mut self) {
+    self.add_native_item(WryNativeMenuItem::Separator);
+  }
+}
+
+impl WryMenu for ContextMenu {
+  fn new() -> Self {
+    ContextMenu::new()
+  }
+
+  fn add_item(&mut self, item: MenuItemAttributes<'_>) -> CustomMenuItem {
+    ContextMenu::add_item(self, item)
+  }
+
+  fn add_submenu(&mut self, title: &str, enabled: bool, submenu: Self) {
+    ContextMenu::add_submenu(self, title, enabled, submenu)
+  }
+
+  fn add_separator(&mut self) {
+    self.add_native_item(WryNativeMenuItem::Separator);
+  }
+}
+
+/// Builds a wry menu with the given entries, collecting its items.
+fn build_menu<M: WryMenu>(entries: &[MenuEntry], items: &mut MenuItems) -> crate::Result<M> {
+  let mut menu = M::new();
+  for entry in entries {
+    match entry {
+      MenuEntry::Submenu(submenu) => {
+        let submenu_menu = build_menu(&submenu.items, items)?;
+        menu.add_submenu(&submenu.title, submenu.enabled, submenu_menu);
+      }
+      MenuEntry::Item(item) => {
+        let id = MenuId::new(&item.id);
+        let mut attributes = MenuItemAttributes::new(&item.title)
+          .with_id(id)
+          .with_enabled(item.enabled);
+        if let Some(checked) = item.checked {
+          attributes = attributes.with_selected(checked);
+        }
+        if let Some(accelerator) = menu_item_accelerator(item)? {
+          attributes = attributes.with_accelerators(&accelerator);
+        }
+        items.insert(id, (item.id.clone(), menu.add_item(attributes)));
+      }
+      MenuEntry::Native(NativeMenuItem::Separator) => menu.add_separator(),
+    }
+  }
+  Ok(menu)
+}
+
+fn menu_item_accelerator(item: &MenuItem) -> crate::Result<Option<WryAccelerator>> {
+  item
+    .accelerator
+    .as_ref()
+    .map(|accelerator| wry_accelerator(&accelerator.parse()?))
+    .transpose()
+}
+
+/// Checks that the accelerators of the menu items can be registered with wry.
+fn check_menu_accelerators(entries: &[MenuEntry]) -> crate::Result<()> {
+  for entry in entries {
+    match entry {
+      MenuEntry::Submenu(submenu) => check_menu_accelerators(&submenu.items)?,
+      MenuEntry::Item(item) => {
+        menu_item_accelerator(item)?;
+      }
+      MenuEntry::Native(_) => {}
+    }
+  }
+  Ok(())
+}
+
+/// Updates the menu item targeted by a window message. Returns `false` if it's not a menu message.
+fn update_menu_item(menu_items: &mut MenuItems, message: &WindowMessage) -> bool {
+  let id = match message {
+    WindowMessage::SetMenuItemEnabled { id, .. }
+    | WindowMessage::SetMenuItemChecked { id, .. }
+    | WindowMessage::SetMenuItemTitle { id, .. } => id,
+    _ => return false,
+  };
+  if let Some((_, item)) = menu_items.get_mut(&MenuId::new(id)) {
+    match message {
+      WindowMessage::SetMenuItemEnabled { enabled, .. } => item.set_enabled(*enabled),
+      WindowMessage::SetMenuItemChecked { checked, .. } => item.set_selected(*checked),
+      WindowMessage::SetMenuItemTitle { title, .. } => item.set_title(title),
+      _ => {}
+    }
+  }
+  true
+}
+
+/// Maps an accelerator to the wry accelerator interface.
+///
+/// The `Plus` key can't be registered with wry, which doesn't have a key code for it.
+fn wry_accelerator(accelerator: &Accelerator) -> crate::Result<WryAccelerator> {
+  let mut modifiers = ModifiersState::empty();
+  let flags = [
+    (accelerator.modifiers.ctrl, ModifiersState::CONTROL),
+    (accelerator.modifiers.alt, ModifiersState::ALT),
+    (accelerator.modifiers.shift, ModifiersState::SHIFT),
+    (accelerator.modifiers.super_key, ModifiersState::SUPER),
+  ];
+  for (pressed, modifier) in flags.iter() {
+    if *pressed {
+      modifiers |= *modifier;
+    }
+  }
+  let key = key_code(accelerator.key).ok_or(crate::Error::UnsupportedByRuntime(
+    "the `Plus` key in accelerators",
+  ))?;
+  Ok(WryAccelerator::new(modifiers, key))
+}
+
+fn key_code(key: Key) -> Option<KeyCode> {
+  let key_code = match key {
+    Key::Char(c) => match c {
+      'A' => KeyCode::KeyA,
+      'B' => KeyCode::KeyB,
+      'C' => KeyCode::KeyC,
+      'D' => KeyCode::KeyD,
+      'E' => KeyCode::KeyE,
+      'F' => KeyCode::KeyF,
+      'G' => KeyCode::KeyG,
+      'H' => KeyCode::KeyH,
+      'I' => KeyCode::KeyI,
+      'J' => KeyCode::KeyJ,
+      'K' => KeyCode::KeyK,
+      'L' => KeyCode::KeyL,
+      'M' => KeyCode::KeyM,
+      'N' => KeyCode::KeyN,
+      'O' => KeyCode::KeyO,
+      'P' => KeyCode::KeyP,
+      'Q' => KeyCode::KeyQ,
+      'R' => KeyCode::KeyR,
+      'S' => KeyCode::KeyS,
+      'T' => KeyCode::KeyT,
+      'U' => KeyCode::KeyU,
+      'V' => KeyCode::KeyV,
+      'W' => KeyCode::KeyW,
+      'X' => KeyCode::KeyX,
+      'Y' => KeyCode::KeyY,
+      'Z' => KeyCode::KeyZ,
+      '0' => KeyCode::Digit0,
+      '1' => KeyCode::Digit1,
+      '2' => KeyCode::Digit2,
+      '3' => KeyCode::Digit3,
+      '4' => KeyCode::Digit4,
+      '5' => KeyCode::Digit5,
+      '6' => KeyCode::Digit6,
+      '7' => KeyCode::Digit7,
+      '8' => KeyCode::Digit8,
+      '9' => KeyCode::Digit9,
+      ',' => KeyCode::Comma,
+      '.' => KeyCode::Period,
+      ';' => KeyCode::Semicolon,
+      '\'' => KeyCode::Quote,
+      '[' => KeyCode::BracketLeft,
+      ']' => KeyCode::BracketRight,
+      '\\' => KeyCode::Backslash,
+      '/' => KeyCode::Slash,
+      '`' => KeyCode::Backquote,
+      '-' => KeyCode::Minus,
+      '=' => KeyCode::Equal,
+      _ => return None,
+    },
+    Key::F(number) => match number {
+      1 => KeyCode::F1,
+      2 => KeyCode::F2,
+      3 => KeyCode::F3,
+      4 => KeyCode::F4,
+      5 => KeyCode::F5,
+      6 => KeyCode::F6,
+      7 => KeyCode::F7,
+      8 => KeyCode::F8,
+      9 => KeyCode::F9,
+      10 => KeyCode::F10,
+      11 => KeyCode::F11,
+      12 => KeyCode::F12,
+      13 => KeyCode::F13,
+      14 => KeyCode::F14,
+      15 => KeyCode::F15,
+      16 => KeyCode::F16,
+      17 => KeyCode::F17,
+      18 => KeyCode::F18,
+      19 => KeyCode::F19,
+      20 => KeyCode::F20,
+      21 => KeyCode::F21,
+      22 => KeyCode::F22,
+      23 => KeyCode::F23,
+      24 => KeyCode::F24,
+      _ => return None,
+    },
+    Key::Space => KeyCode::Space,
+    Key::Tab => KeyCode::Tab,
+    Key::Enter => KeyCode::Enter,
+    Key::Escape => KeyCode::Escape,
+    Key::Backspace => KeyCode::Backspace,
+    Key::Delete => KeyCode::Delete,
+    Key::Insert => KeyCode::Insert,
+    Key::Home => KeyCode::Home,
+    Key::End => KeyCode::End,
+    Key::PageUp => KeyCode::PageUp,
+    Key::PageDown => KeyCode::PageDown,
+    Key::Up => KeyCode::ArrowUp,
+    Key::Down => KeyCode::ArrowDown,
+    Key::Left => KeyCode::ArrowLeft,
+    Key::Right => KeyCode::ArrowRight,
+  };
+  Some(key_code)
+}
+
+/// The size constraint of a window, `None` if neither dimension is constrained.
+fn size_constraint(
+  width: Option<f64>,
+  height: Option<f64>,
+  unconstrained: f64,
+) -> Option<LogicalSize<f64>> {
+  if width.is_none() && height.is_none() {
+    None
+  } else {
+    Some(LogicalSize::new(
+      width.unwrap_or(unconstrained),
+      height.unwrap_or(unconstrained),
+    ))
+  }
+}
+
+fn fullscreen(fullscreen: bool) -> Option<Fullscreen> {
+  if fullscreen {
+    Some(Fullscreen::Borderless(None))
+  } else {
+    None
+  }
+}
+
+/// A window built by wry, with the items of its menu bar.
+pub struct WryWindow {
+  inner: Window,
+  transparent: bool,
+  menu_items: MenuItems,
+}
+
+/// Builds a window on the event loop thread.
+fn build_window(
+  attributes: WryWindowAttributes,
+  event_loop: &EventLoopWindowTarget<WryMessage>,
+) -> crate::Result<WryWindow> {
+  let mut window_builder = WindowBuilder::new()
+    .with_title(attributes.title)
+    .with_inner_size(LogicalSize::new(attributes.width, attributes.height))
+    .with_resizable(attributes.resizable)
+    .with_fullscreen(fullscreen(attributes.fullscreen))
+    .with_maximized(attributes.maximized)
+    .with_visible(attributes.visible)
+    .with_transparent(attributes.transparent)
+    .with_decorations(attributes.decorations)
+    .with_always_on_top(attributes.always_on_top);
+  if let Some(min_size) = size_constraint(attributes.min_width, attributes.min_height, 0.0) {
+    window_builder = window_builder.with_min_inner_size(min_size);
+  }
+  if let Some(max_size) = size_constraint(attributes.max_width, attributes.max_height, f64::MAX) {
+    window_builder = window_builder.with_max_inner_size(max_size);
+  }
+  let mut menu_items = MenuItems::new();
+  if let Some(menu) = &attributes.menu {
+    let menu_bar = build_menu::<MenuBar>(&menu.items, &mut menu_items)?;
+    window_builder = window_builder.with_menu(menu_bar);
+  }
+  let window = window_builder
+    .build(event_loop)
+    .map_err(|_| crate::Error::CreateWindow)?;
+  if attributes.x.is_some() || attributes.y.is_some() {
+    window.set_outer_position(LogicalPosition::new(
+      attributes.x.unwrap_or(0.0),
+      attributes.y.unwrap_or(0.0),
+    ));
+  }
+  Ok(WryWindow {
+    inner: window,
+    transparent: attributes.transparent,
+    menu_items,
+  })
+}
+
+/// Binds the callbacks to global functions of the page, calling them through the wry RPC interface.
+fn callbacks_script<D>(callbacks: &[Callback<D>]) -> String {
+  callbacks
+    .iter()
+    .map(|callback| {
+      format!(
+        "window[{name}] = function () {{ window.rpc.notify({name}, ...arguments) }};",
+        name = format_js_string(&callback.name)
+      )
+    })
+    .collect()
+}
+
+/// Calls the callback named by an RPC request of a page, with the request params as arguments.
+/// Returns `false` if no callback has that name.
+///
+/// wry doesn't report the page that called the callback, so the callbacks don't get its URL.
+fn call_callback<D>(
+  callbacks: &HashMap<String, Mutex<Callback<D>>>,
+  dispatcher: &D,
+  method: &str,
+  params: Option<JsonValue>,
+) -> bool {
+  let callback = match callbacks.get(method) {
+    Some(callback) => callback,
+    None => return false,
+  };
+  let args = match params {
+    Some(JsonValue::Array(params)) => params,
+    Some(param) => vec![param],
+    None => Vec::new(),
+  }
+  .into_iter()
+  .map(|param| match param {
+    JsonValue::String(param) => param,
+    param => param.to_string(),
+  })
+  .collect();
+  let mut callback = callback.lock().unwrap();
+  (callback.function)(dispatcher, None, 0, args);
+  true
+}
+
+/// Maps a window event to the window event interface, with the sizes and positions in logical pixels.
+///
+/// wry reports the window destruction before the window is dropped, so `Destroyed`
+/// is reported by the event loop once the webview is dropped instead.
+fn window_event(event: &WryWindowEvent<'_>, scale_factor: f64) -> Option<WindowEvent> {
+  let event = match event {
+    WryWindowEvent::CloseRequested => WindowEvent::CloseRequested(CloseRequest::default()),
+    WryWindowEvent::Resized(size) => {
+      let size = size.to_logical::<f64>(scale_factor);
+      WindowEvent::Resized {
+        width: size.width,
+        height: size.height,
+      }
+    }
+    WryWindowEvent::Moved(position) => {
+      let position = position.to_logical::<f64>(scale_factor);
+      WindowEvent::Moved {
+        x: position.x,
+        y: position.y,
+      }
+    }
+    WryWindowEvent::Focused(focused) => WindowEvent::Focused(*focused),
+    WryWindowEvent::ScaleFactorChanged {
+      scale_factor,
+      new_inner_size,
+    } => {
+      let size = new_inner_size.to_logical::<f64>(*scale_factor);
+      WindowEvent::ScaleFactorChanged {
+        scale_factor: *scale_factor,
+        width: size.width,
+        height: size.height,
+      }
+    }
+    _ => return None,
+  };
+  Some(event)
+}
+
+/// Reports a wry window event to the window event handler.
+/// Returns `true` if the window must be closed: it's a close request that the handler didn't prevent.
+fn forward_window_event(
+  handler: &WindowEventHandler,
+  event: &WryWindowEvent<'_>,
+  scale_factor: f64,
+) -> bool {
+  match window_event(event, scale_factor) {
+    Some(event) => {
+      handler(&event);
+      matches!(event, WindowEvent::CloseRequested(request) if !request.is_close_prevented())
+    }
+    None => false,
+  }
+}
+
+/// A webview created by wry, with the handler of its window events.
+struct WryWebview {
+  inner: WebView,
+  menu_items: MenuItems,
+  window_event_handler: WindowEventHandler,
+}
+
+/// Builds the webview of a window on the event loop thread.
+fn build_webview(
+  window: WryWindow,
+  attributes: WryWebviewAttributes,
+  callbacks: Vec<Callback<WryDispatcher>>,
+  window_event_handler: WindowEventHandler,
+  context: &DispatcherContext,
+) -> crate::Result<(WindowId, WryWebview)> {
+  let window_id = window.inner.id();
+  let mut webview_builder = WebViewBuilder::new(window.inner)
+    .map_err(|_| crate::Error::CreateWebview)?
+    .with_transparent(window.transparent)
+    .with_initialization_script(&callbacks_script(&callbacks));
+  for script in &attributes.initialization_scripts {
+    webview_builder = webview_builder.with_initialization_script(script);
+  }
+  for (uri_scheme, protocol) in attributes.uri_scheme_protocols {
+    webview_builder = webview_builder.with_custom_protocol(uri_scheme, move |uri| {
+      protocol(uri)
+        .map(|response| (response.body, response.mime_type))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()).into())
+    });
+  }
+  if let Some(url) = &attributes.url {
+    webview_builder = webview_builder
+      .with_url(url)
+      .map_err(|_| crate::Error::CreateWebview)?;
+  }
+  let callbacks: HashMap<String, Mutex<Callback<WryDispatcher>>> = callbacks
+    .into_iter()
+    .map(|callback| (callback.name.clone(), Mutex::new(callback)))
+    .collect();
+  let context = context.clone();
+  let webview = webview_builder
+    .with_rpc_handler(move |window, request| {
+      let dispatcher = WryDispatcher {
+        context: context.clone(),
+        current_window: Some(window.id()),
+      };
+      call_callback(&callbacks, &dispatcher, &request.method, request.params);
+      None
+    })
+    .build()
+    .map_err(|_| crate::Error::CreateWebview)?;
+  Ok((
+    window_id,
+    WryWebview {
+      inner: webview,
+      menu_items: window.menu_items,
+      window_event_handler,
+    },
+  ))
+}
+
+/// The system tray icon, with the items of its menu.
+struct WrySystemTrayIcon {
+  inner: WrySystemTray,
+  menu_items: MenuItems,
+  handler: SystemTrayEventHandler,
+}
+
+/// The system tray icon in the format wry expects: a path on Linux and the file content elsewhere.
+#[cfg(target_os = "linux")]
+fn system_tray_icon(icon: PathBuf) -> crate::Result<PathBuf> {
+  Ok(icon)
+}
+
+/// The system tray icon in the format wry expects: a path on Linux and the file content elsewhere.
+#[cfg(not(target_os = "linux"))]
+fn system_tray_icon(icon: PathBuf) -> crate::Result<Vec<u8>> {
+  std::fs::read(icon).map_err(Into::into)
+}
+
+/// Builds the system tray icon.
+///
+/// The tooltip isn't shown on Linux, where wry has no tooltip API.
+fn build_system_tray(
+  system_tray: SystemTray,
+  handler: SystemTrayEventHandler,
+  event_loop: &EventLoopWindowTarget<WryMessage>,
+) -> crate::Result<WrySystemTrayIcon> {
+  let mut menu_items = MenuItems::new();
+  let menu = system_tray
+    .menu
+    .map(|menu| build_menu::<ContextMenu>(&menu.items, &mut menu_items))
+    .transpose()?;
+  #[allow(unused_mut)]
+  let mut system_tray_builder = SystemTrayBuilder::new(system_tray_icon(system_tray.icon)?, menu);
+  #[cfg(not(target_os = "linux"))]
+  if let Some(tooltip) = &system_tray.tooltip {
+    system_tray_builder = system_tray_builder.with_tooltip(tooltip);
   }
+  let tray = system_tray_builder
+    .build(event_loop)
+    .map_err(|_| crate::Error::UnsupportedByRuntime("system trays on this platform"))?;
+  Ok(WrySystemTrayIcon {
+    inner: tray,
+    menu_items,
+    handler,
+  })
+}
+
+/// A message posted to the wry event loop.
+enum WryMessage {
+  /// A message sent to the given window.
+  Window(WindowId, WindowMessage),
+  /// Evaluates a script on the webview of the given window.
+  EvalScript(WindowId, String),
+  /// Creates a window with its webview, answering with the window id.
+  CreateWebview(Box<PendingWebview>, Sender<crate::Result<WindowId>>),
+  /// Runs the given closure.
+  Run(crate::SyncTask),
+  /// Updates the system tray.
+  SystemTray(SystemTrayMessage),
+  /// Registers a global shortcut, answering whether the OS registered it.
+  RegisterGlobalShortcut(Accelerator, Sender<crate::Result<()>>),
+  /// Unregisters a global shortcut.
+  UnregisterGlobalShortcut(Accelerator),
+  /// Exits the process with the given code.
+  Exit(i32),
 }
 
+/// A window and its webview, created by the event loop.
+struct PendingWebview {
+  window_attributes: WryWindowAttributes,
+  webview_attributes: WryWebviewAttributes,
+  callbacks: Vec<Callback<WryDispatcher>>,
+  window_event_handler: WindowEventHandler,
+}
+
+/// The handle the dispatchers use to post messages to the event loop.
+#[derive(Clone)]
+struct DispatcherContext {
+  proxy: Arc<Mutex<EventLoopProxy<WryMessage>>>,
+  /// The thread running the event loop.
+  event_loop_thread: ThreadId,
+}
+
+impl DispatcherContext {
+  fn send(&self, message: WryMessage) -> crate::Result<()> {
+    self
+      .proxy
+      .lock()
+      .unwrap()
+      .send_event(message)
+      .map_err(|_| crate::Error::EventLoopClosed)
+  }
+
+  /// Posts a message to the event loop and waits for its answer.
+  ///
+  /// The event loop thread can't wait for itself, so the request fails there with the given operation name.
+  fn request<T>(
+    &self,
+    operation: &'static str,
+    message: impl FnOnce(Sender<crate::Result<T>>) -> WryMessage,
+  ) -> crate::Result<T> {
+    if std::thread::current().id() == self.event_loop_thread {
+      return Err(crate::Error::UnsupportedByRuntime(operation));
+    }
+    let (tx, rx) = channel();
+    self.send(message(tx))?;
+    rx.recv().map_err(|_| crate::Error::EventLoopClosed)?
+  }
+}
+
+/// The wry message dispatcher.
 #[derive(Clone)]
 pub struct WryDispatcher {
-  inner: Arc<Mutex<wry::AppDispatcher<Event>>>,
-  current_window: wry::WindowId,
+  context: DispatcherContext,
+  /// The window of the dispatcher, `None` for the app dispatcher.
+  current_window: Option<WindowId>,
 }
 
-struct WryMessage(wry::Message<wry::WindowId, Event>);
+impl ApplicationDispatcherExt for WryDispatcher {
+  type WebviewBuilder = WryWebviewBuilder;
+  type WindowBuilder = WryWindowBuilder;
+
+  /// The window is created by the event loop, so this fails on the event loop thread,
+  /// e.g. from a window event handler.
+  fn create_webview(
+    &self,
+    window_builder: Self::WindowBuilder,
+    webview_builder: Self::WebviewBuilder,
+    callbacks: Vec<Callback<Self>>,
+    window_event_handler: WindowEventHandler,
+  ) -> crate::Result<Self> {
+    let pending_webview = PendingWebview {
+      window_attributes: window_builder.finish()?,
+      webview_attributes: webview_builder.finish()?,
+      callbacks,
+      window_event_handler,
+    };
+    let window_id = self
+      .context
+      .request("creating windows from the event loop thread", move |tx| {
+        WryMessage::CreateWebview(Box::new(pending_webview), tx)
+      })?;
+    Ok(Self {
+      context: self.context.clone(),
+      current_window: Some(window_id),
+    })
+  }
 
-impl From<(wry::WindowId, Message)> for WryMessage {
-  fn from((id, message): (wry::WindowId, Message)) -> Self {
-    let message = match message {
-      Message::EvalScript(js) => wry::Message::Webview(id, WebviewMessage::EvalScript(js)),
-      Message::SetWindowTitle(title) => wry::Message::Window(id, WindowMessage::SetTitle(title)),
-      Message::Event(event) => wry::Message::Custom(event),
+  /// Global shortcuts are registered by the event loop, so registering one fails on the event loop thread,
+  /// e.g. from a window event handler.
+  fn send_message(&self, message: Message) -> crate::Result<()> {
+    let message = match (message, self.current_window) {
+      (Message::EvalScript(js), Some(id)) => WryMessage::EvalScript(id, js),
+      (Message::Window(message), Some(id)) => WryMessage::Window(id, message),
+      // the app dispatcher isn't bound to a window
+      (Message::EvalScript(_), None) | (Message::Window(_), None) => return Ok(()),
+      (Message::Event(Event::Run(task)), _) => WryMessage::Run(task),
+      (Message::SystemTray(message), _) => WryMessage::SystemTray(message),
+      (Message::GlobalShortcut(GlobalShortcutMessage::Register(accelerator)), _) => {
+        return self.context.request(
+          "registering global shortcuts from the event loop thread",
+          move |tx| WryMessage::RegisterGlobalShortcut(accelerator, tx),
+        )
+      }
+      (Message::GlobalShortcut(GlobalShortcutMessage::Unregister(accelerator)), _) => {
+        WryMessage::UnregisterGlobalShortcut(accelerator)
+      }
+      (Message::Exit(code), _) => WryMessage::Exit(code),
     };
-    WryMessage(message)
+    self.context.send(message)
   }
 }
 
-impl ApplicationDispatcherExt for WryDispatcher {
-  fn send_message(&self, message: Message) {
-    let message: WryMessage = (self.current_window, message).into();
+/// Applies a window message to a wry window.
+fn update_window(window: &Window, message: WindowMessage) {
+  use WindowMessage::*;
+  match message {
+    SetTitle(title) => window.set_title(&title),
+    SetResizable(resizable) => window.set_resizable(resizable),
+    SetSize { width, height } => window.set_inner_size(LogicalSize::new(width, height)),
+    SetMinSize {
+      min_width,
+      min_height,
+    } => window.set_min_inner_size(size_constraint(min_width, min_height, 0.0)),
+    SetMaxSize {
+      max_width,
+      max_height,
+    } => window.set_max_inner_size(size_constraint(max_width, max_height, f64::MAX)),
+    SetPosition { x, y } => window.set_outer_position(LogicalPosition::new(x, y)),
+    SetFullscreen(enabled) => window.set_fullscreen(fullscreen(enabled)),
+    SetDecorations(decorations) => window.set_decorations(decorations),
+    SetAlwaysOnTop(always_on_top) => window.set_always_on_top(always_on_top),
+    Minimize => window.set_minimized(true),
+    Unminimize => window.set_minimized(false),
+    Maximize => window.set_maximized(true),
+    Unmaximize => window.set_maximized(false),
+    Show => window.set_visible(true),
+    Hide => window.set_visible(false),
+    Center => {
+      if let Some(monitor) = window.current_monitor() {
+        let monitor_position = monitor.position();
+        let monitor_size = monitor.size();
+        let window_size = window.outer_size();
+        window.set_outer_position(PhysicalPosition::new(
+          monitor_position.x
+            + (i64::from(monitor_size.width) - i64::from(window_size.width)) as i32 / 2,
+          monitor_position.y
+            + (i64::from(monitor_size.height) - i64::from(window_size.height)) as i32 / 2,
+        ));
+      }
+    }
+    StartDragging => {
+      // fails if the mouse button was already released
+      let _ = window.drag_window();
+    }
+    // handled by the event loop, which drops the webview
+    Close => {}
+    // handled with the menu items of the window
+    SetMenuItemEnabled { .. } | SetMenuItemChecked { .. } | SetMenuItemTitle { .. } => {}
+  }
+}
+
+/// The windows, system tray and global shortcuts of the app, owned by the event loop.
+struct WryState {
+  webviews: HashMap<WindowId, WryWebview>,
+  context: DispatcherContext,
+  system_tray: Option<WrySystemTrayIcon>,
+  shortcut_manager: ShortcutManager,
+  global_shortcuts: HashMap<AcceleratorId, (Accelerator, GlobalShortcut)>,
+  global_shortcut_handler: Option<GlobalShortcutHandler>,
+}
+
+impl WryState {
+  fn handle_message(
+    &mut self,
+    message: WryMessage,
+    event_loop: &EventLoopWindowTarget<WryMessage>,
+  ) {
+    match message {
+      WryMessage::Window(id, WindowMessage::Close) => self.close_window(id),
+      WryMessage::Window(id, message) => {
+        if let Some(webview) = self.webviews.get_mut(&id) {
+          if !update_menu_item(&mut webview.menu_items, &message) {
+            update_window(webview.inner.window(), message);
+          }
+        }
+      }
+      WryMessage::EvalScript(id, js) => {
+        if let Some(webview) = self.webviews.get(&id) {
+          if let Err(e) = webview.inner.evaluate_script(&js) {
+            eprintln!("failed to evaluate script: {}", e);
+          }
+        }
+      }
+      WryMessage::CreateWebview(pending_webview, tx) => {
+        let PendingWebview {
+          window_attributes,
+          webview_attributes,
+          callbacks,
+          window_event_handler,
+        } = *pending_webview;
+        let webview = build_window(window_attributes, event_loop).and_then(|window| {
+          build_webview(
+            window,
+            webview_attributes,
+            callbacks,
+            window_event_handler,
+            &self.context,
+          )
+        });
+        let _ = tx.send(webview.map(|(id, webview)| {
+          self.webviews.insert(id, webview);
+          id
+        }));
+      }
+      WryMessage::Run(task) => task(),
+      WryMessage::SystemTray(message) => {
+        if let Err(e) = self.update_system_tray(message) {
+          eprintln!("failed to update the system tray: {}", e);
+        }
+      }
+      WryMessage::RegisterGlobalShortcut(accelerator, tx) => {
+        let _ = tx.send(self.register_global_shortcut(accelerator));
+      }
+      WryMessage::UnregisterGlobalShortcut(accelerator) => {
+        let id = self
+          .global_shortcuts
+          .iter()
+          .find(|(_, (registered, _))| *registered == accelerator)
+          .map(|(id, _)| *id);
+        if let Some((_, shortcut)) = id.and_then(|id| self.global_shortcuts.remove(&id)) {
+          let _ = self.shortcut_manager.unregister(shortcut);
+        }
+      }
+      WryMessage::Exit(code) => {
+        // drop the webviews and the tray icon before exiting
+        self.webviews.clear();
+        self.system_tray.take();
+        std::process::exit(code)
+      }
+    }
+  }
+
+  fn update_system_tray(&mut self, message: SystemTrayMessage) -> crate::Result<()> {
+    let system_tray = match &mut self.system_tray {
+      Some(system_tray) => system_tray,
+      None => return Ok(()),
+    };
+    match message {
+      SystemTrayMessage::SetIcon(icon) => system_tray.inner.set_icon(system_tray_icon(icon)?),
+      #[cfg(not(target_os = "linux"))]
+      SystemTrayMessage::SetTooltip(tooltip) => system_tray.inner.set_tooltip(&tooltip),
+      #[cfg(target_os = "linux")]
+      SystemTrayMessage::SetTooltip(_) => {}
+      SystemTrayMessage::SetMenu(menu) => {
+        let mut menu_items = MenuItems::new();
+        system_tray
+          .inner
+          .set_menu(&build_menu::<ContextMenu>(&menu.items, &mut menu_items)?);
+        system_tray.menu_items = menu_items;
+      }
+    }
+    Ok(())
+  }
+
+  fn register_global_shortcut(&mut self, accelerator: Accelerator) -> crate::Result<()> {
+    let shortcut = self
+      .shortcut_manager
+      .register(wry_accelerator(&accelerator)?)
+      .map_err(|_| crate::Error::RegisterGlobalShortcut(accelerator.to_string()))?;
     self
-      .inner
-      .lock()
-      .unwrap()
-      .dispatch_message(message.0)
-      .unwrap();
+      .global_shortcuts
+      .insert(shortcut.id(), (accelerator, shortcut));
+    Ok(())
+  }
+
+  fn handle_window_event(&mut self, id: WindowId, event: &WryWindowEvent<'_>) {
+    let webview = match self.webviews.get(&id) {
+      Some(webview) => webview,
+      None => return,
+    };
+    if let WryWindowEvent::Resized(_) = event {
+      let _ = webview.inner.resize();
+    }
+    let scale_factor = webview.inner.window().scale_factor();
+    if forward_window_event(&webview.window_event_handler, event, scale_factor) {
+      self.close_window(id);
+    }
+  }
+
+  /// Drops the webview of the window, closing it, and reports the `Destroyed` event.
+  fn close_window(&mut self, id: WindowId) {
+    if let Some(webview) = self.webviews.remove(&id) {
+      let handler = webview.window_event_handler;
+      drop(webview.inner);
+      handler(&WindowEvent::Destroyed);
+    }
+  }
+
+  fn handle_menu_event(&self, window: Option<WindowId>, menu_id: MenuId, origin: MenuType) {
+    match origin {
+      MenuType::MenuBar => {
+        // the macOS menu bar is shared by the windows, so it doesn't always report one
+        let clicked = self
+          .webviews
+          .iter()
+          .filter(|(id, _)| window.map_or(true, |window| window == **id))
+          .find_map(|(_, webview)| {
+            webview
+              .menu_items
+              .get(&menu_id)
+              .map(|(item_id, _)| (webview, item_id.clone()))
+          });
+        if let Some((webview, item_id)) = clicked {
+          (webview.window_event_handler)(&WindowEvent::MenuItemClicked(item_id));
+        }
+      }
+      MenuType::ContextMenu => {
+        if let Some(system_tray) = &self.system_tray {
+          if let Some((item_id, _)) = system_tray.menu_items.get(&menu_id) {
+            (system_tray.handler)(&SystemTrayEvent::MenuItemClick {
+              id: item_id.clone(),
+            });
+          }
+        }
+      }
+    }
+  }
+
+  fn handle_system_tray_event(&self, event: TrayEvent) {
+    let event = match event {
+      TrayEvent::LeftClick => SystemTrayEvent::LeftClick,
+      TrayEvent::RightClick => SystemTrayEvent::RightClick,
+      TrayEvent::DoubleClick => SystemTrayEvent::DoubleClick,
+    };
+    if let Some(system_tray) = &self.system_tray {
+      (system_tray.handler)(&event);
+    }
+  }
+
+  fn handle_global_shortcut_event(&self, id: AcceleratorId) {
+    if let (Some((accelerator, _)), Some(handler)) = (
+      self.global_shortcuts.get(&id),
+      &self.global_shortcut_handler,
+    ) {
+      handler(accelerator);
+    }
   }
 }
 
 /// A wrapper around the wry Application interface.
+///
+/// The windows, webviews, system tray and global shortcuts are owned by the event loop,
+/// and the dispatchers post their messages to it.
+/// The window event handlers, the system tray event handler and the global shortcut handler
+/// are called on the event loop thread.
+///
+/// wry doesn't report the page that invoked the app, so the IPC calls are attributed to the
+/// page the window was created with.
 pub struct WryApplication {
-  inner: wry::Application<Event>,
-  dispatcher_handle: Arc<Mutex<wry::AppDispatcher<Event>>>,
+  event_loop: EventLoop<WryMessage>,
+  state: WryState,
 }
 
 impl ApplicationExt for WryApplication {
-  type WebviewBuilder = wry::WebViewAttributes;
-  type WindowBuilder = wry::AppWindowAttributes;
-  type Window = wry::Window;
+  type WebviewBuilder = WryWebviewBuilder;
+  type WindowBuilder = WryWindowBuilder;
+  type Window = WryWindow;
   type Dispatcher = WryDispatcher;
 
-  fn plugin_store() -> &'static PluginStore<Self::Dispatcher> {
-    static PLUGINS: Lazy<PluginStore<WryDispatcher>> = Lazy::new(Default::default);
-    &PLUGINS
-  }
-
   fn new() -> crate::Result<Self> {
-    let app = wry::Application::new().map_err(|_| crate::Error::CreateWebview)?;
-    let dispatcher = app.dispatcher();
-
+    let event_loop = EventLoop::with_user_event();
+    let context = DispatcherContext {
+      proxy: Arc::new(Mutex::new(event_loop.create_proxy())),
+      event_loop_thread: std::thread::current().id(),
+    };
+    let shortcut_manager = ShortcutManager::new(&event_loop);
     Ok(Self {
-      inner: app,
-      dispatcher_handle: Arc::new(Mutex::new(dispatcher)),
+      event_loop,
+      state: WryState {
+        webviews: Default::default(),
+        context,
+        system_tray: None,
+        shortcut_manager,
+        global_shortcuts: Default::default(),
+        global_shortcut_handler: None,
+      },
     })
   }
 
   fn dispatcher(&self, window: &Self::Window) -> Self::Dispatcher {
     WryDispatcher {
-      inner: self.dispatcher_handle.clone(),
-      current_window: window.id(),
+      context: self.state.context.clone(),
+      current_window: Some(window.inner.id()),
+    }
+  }
+
+  fn app_dispatcher(&self) -> Self::Dispatcher {
+    WryDispatcher {
+      context: self.state.context.clone(),
+      current_window: None,
     }
   }
 
+  fn create_system_tray(
+    &mut self,
+    system_tray: SystemTray,
+    system_tray_event_handler: SystemTrayEventHandler,
+  ) -> crate::Result<()> {
+    self.state.system_tray = Some(build_system_tray(
+      system_tray,
+      system_tray_event_handler,
+      &self.event_loop,
+    )?);
+    Ok(())
+  }
+
+  fn set_global_shortcut_handler(&mut self, global_shortcut_handler: GlobalShortcutHandler) {
+    self
+      .state
+      .global_shortcut_handler
+      .replace(global_shortcut_handler);
+  }
+
   fn create_window(&self, window_builder: Self::WindowBuilder) -> crate::Result<Self::Window> {
-    let window = self
-      .inner
-      .create_window(window_builder.finish()?)
-      .map_err(|_| crate::Error::CreateWindow)?;
-    Ok(window)
+    build_window(window_builder.finish()?, &self.event_loop)
   }
 
   fn create_webview(
@@ -206,39 +1210,172 @@ impl ApplicationExt for WryApplication {
     webview_builder: Self::WebviewBuilder,
     window: Self::Window,
     callbacks: Vec<Callback<Self::Dispatcher>>,
+    window_event_handler: WindowEventHandler,
   ) -> crate::Result<()> {
-    let mut wry_callbacks = Vec::new();
-    for mut callback in callbacks {
-      let dispatcher_handle = self.dispatcher_handle.clone();
-      let window_id = window.id();
-
-      let callback = wry::Callback {
-        name: callback.name.to_string(),
-        function: Box::new(move |_, seq, req| {
-          (callback.function)(
-            &WryDispatcher {
-              inner: dispatcher_handle.clone(),
-              current_window: window_id,
-            },
-            seq,
-            req,
-          )
+    let (id, webview) = build_webview(
+      window,
+      webview_builder.finish()?,
+      callbacks,
+      window_event_handler,
+      &self.state.context,
+    )?;
+    self.state.webviews.insert(id, webview);
+    Ok(())
+  }
+
+  fn run(self) {
+    let mut state = self.state;
+    self.event_loop.run(move |event, event_loop, control_flow| {
+      *control_flow = ControlFlow::Wait;
+      match event {
+        WryEvent::UserEvent(message) => state.handle_message(message, event_loop),
+        WryEvent::WindowEvent {
+          window_id, event, ..
+        } => state.handle_window_event(window_id, &event),
+        WryEvent::MenuEvent {
+          window_id,
+          menu_id,
+          origin,
+        } => state.handle_menu_event(window_id, menu_id, origin),
+        WryEvent::TrayEvent { event, .. } => state.handle_system_tray_event(event),
+        WryEvent::GlobalShortcutEvent(id) => state.handle_global_shortcut_event(id),
+        _ => {}
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{call_callback, forward_window_event, key_code, wry_accelerator, Callback};
+  use crate::{
+    global_shortcut::{Accelerator, Key},
+    WindowEvent,
+  };
+  use serde_json::json;
+  use wry::application::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::WindowEvent as WryWindowEvent,
+    keyboard::KeyCode,
+  };
+
+  use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+  };
+
+  #[test]
+  fn calls_callbacks_with_the_rpc_params() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let calls_ = calls.clone();
+    let mut callbacks = HashMap::new();
+    callbacks.insert(
+      "__TAURI_INVOKE_HANDLER__".to_string(),
+      Mutex::new(Callback::<()> {
+        name: "__TAURI_INVOKE_HANDLER__".to_string(),
+        function: Box::new(move |_, url, _, args| {
+          calls_.lock().unwrap().push((url.is_none(), args));
+          0
         }),
-      };
-      wry_callbacks.push(callback);
-    }
+      }),
+    );
 
-    self
-      .inner
-      .create_webview(window, webview_builder.finish()?, Some(wry_callbacks))
-      .map_err(|_| crate::Error::CreateWebview)?;
-    Ok(())
+    assert!(call_callback(
+      &callbacks,
+      &(),
+      "__TAURI_INVOKE_HANDLER__",
+      Some(json!([r#"{"cmd":"__initialized"}"#, 1]))
+    ));
+    assert!(!call_callback(&callbacks, &(), "unknown", None));
+    assert_eq!(
+      *calls.lock().unwrap(),
+      vec![(
+        true,
+        vec![r#"{"cmd":"__initialized"}"#.to_string(), "1".to_string()]
+      )]
+    );
   }
 
-  fn run(mut self) {
-    self.inner.set_message_handler(|message| match message {
-      Event::Run(task) => task(),
+  #[test]
+  fn forwards_window_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_ = events.clone();
+    let prevent_close = Arc::new(Mutex::new(true));
+    let prevent_close_ = prevent_close.clone();
+    let handler: crate::WindowEventHandler = Box::new(move |event| {
+      if let WindowEvent::CloseRequested(request) = event {
+        if *prevent_close_.lock().unwrap() {
+          request.prevent_close();
+        }
+      }
+      events_.lock().unwrap().push(event.clone());
     });
-    wry::Application::run(self.inner)
+
+    // the sizes and positions are reported in logical pixels
+    assert!(!forward_window_event(
+      &handler,
+      &WryWindowEvent::Resized(PhysicalSize::new(1600, 1200)),
+      2.0
+    ));
+    assert!(!forward_window_event(
+      &handler,
+      &WryWindowEvent::Moved(PhysicalPosition::new(200, 100)),
+      2.0
+    ));
+    assert!(!forward_window_event(
+      &handler,
+      &WryWindowEvent::Focused(true),
+      2.0
+    ));
+    // the window is closed unless a handler prevented it
+    assert!(!forward_window_event(
+      &handler,
+      &WryWindowEvent::CloseRequested,
+      2.0
+    ));
+    *prevent_close.lock().unwrap() = false;
+    assert!(forward_window_event(
+      &handler,
+      &WryWindowEvent::CloseRequested,
+      2.0
+    ));
+    // the event loop reports the destruction once the webview is dropped
+    assert!(!forward_window_event(
+      &handler,
+      &WryWindowEvent::Destroyed,
+      2.0
+    ));
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 5);
+    assert!(matches!(
+      events[0],
+      WindowEvent::Resized { width, height } if width == 800.0 && height == 600.0
+    ));
+    assert!(matches!(
+      events[1],
+      WindowEvent::Moved { x, y } if x == 100.0 && y == 50.0
+    ));
+    assert!(matches!(events[2], WindowEvent::Focused(true)));
+    assert!(matches!(events[3], WindowEvent::CloseRequested(_)));
+    assert!(matches!(events[4], WindowEvent::CloseRequested(_)));
+  }
+
+  #[test]
+  fn maps_accelerator_keys() {
+    assert_eq!(key_code(Key::Char('K')), Some(KeyCode::KeyK));
+    assert_eq!(key_code(Key::Char('7')), Some(KeyCode::Digit7));
+    assert_eq!(key_code(Key::Char('/')), Some(KeyCode::Slash));
+    assert_eq!(key_code(Key::F(12)), Some(KeyCode::F12));
+    assert_eq!(key_code(Key::Left), Some(KeyCode::ArrowLeft));
+    assert_eq!(key_code(Key::Char('+')), None);
+
+    let accelerator: Accelerator = "Ctrl+Shift+K".parse().unwrap();
+    assert!(wry_accelerator(&accelerator).is_ok());
+    let accelerator: Accelerator = "Alt+Plus".parse().unwrap();
+    assert!(matches!(
+      wry_accelerator(&accelerator),
+      Err(crate::Error::UnsupportedByRuntime(_))
+    ));
   }
 }
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use tauri::{
+  flavors::{
+    mock::{MockHandle, MockMessage},
+    Mock,
+  },
+  AppBuilder, ApplicationDispatcherExt, FromTauriContext, State, WebviewManager,
+};
+
+use std::{
+  sync::atomic::{AtomicUsize, Ordering},
+  time::Duration,
+};
+
+#[derive(FromTauriContext)]
+#[config_path = "test/fixture/src-tauri/tauri.conf.json"]
+struct TauriContext;
+
+struct Counter(AtomicUsize);
+
+// user types sharing their names with the injected and returned types
+mod shapes {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Deserialize)]
+  pub struct State {
+    pub side: f64,
+  }
+
+  #[derive(Serialize)]
+  pub struct Result {
+    pub area: f64,
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Greeting {
+  message: String,
+}
+
+#[tauri::command]
+fn greet(name: String, punctuation: Option<String>) -> Greeting {
+  Greeting {
+    message: format!("hello {}{}", name, punctuation.unwrap_or_default()),
+  }
+}
+
+#[tauri::command]
+async fn increment(counter: State<Counter>, amount: usize) -> Result<usize, String> {
+  if amount == 0 {
+    return Err("nothing to add".into());
+  }
+  Ok(counter.0.fetch_add(amount, Ordering::SeqCst) + amount)
+}
+
+#[tauri::command]
+fn area(square: shapes::State) -> shapes::Result {
+  shapes::Result {
+    area: square.side * square.side,
+  }
+}
+
+#[tauri::command]
+fn label<D: ApplicationDispatcherExt>(manager: WebviewManager<D>) -> Result<String, String> {
+  manager
+    .current_webview()
+    .map(|webview| webview.label().to_string())
+    .map_err(|e| e.to_string())
+}
+
+/// Invokes the command and waits for the promise to be resolved or rejected with the given JSON value.
+fn invoke(handle: &MockHandle, payload: &str, ok: bool, response: &str) -> bool {
+  handle.invoke(0, payload).unwrap();
+  let expected = format!(
+    r#"\"ok\":{},\"payload\":{}"#,
+    ok,
+    response.replace('"', r#"\""#)
+  );
+  handle
+    .wait_for_message(
+      0,
+      Duration::from_secs(5),
+      |message| matches!(message, MockMessage::EvalScript(js) if js.contains(&expected)),
+    )
+    .is_some()
+}
+
+#[test]
+fn runs_commands() {
+  AppBuilder::<Mock, TauriContext>::new()
+    .manage(Counter(AtomicUsize::new(0)))
+    .commands(tauri::generate_handler![greet, increment, area, label])
+    .build()
+    .unwrap()
+    .run();
+  let handle = MockHandle::current().unwrap();
+
+  assert!(invoke(
+    &handle,
+    r#"{"cmd":"greet","name":"tauri","punctuation":"!","callback":"ok","error":"err"}"#,
+    true,
+    r#"{"message":"hello tauri!"}"#
+  ));
+  assert!(invoke(
+    &handle,
+    r#"{"cmd":"greet","name":1,"callback":"ok","error":"err"}"#,
+    false,
+    r#""invalid argument `name` for command `greet`"#
+  ));
+  assert!(invoke(
+    &handle,
+    r#"{"cmd":"increment","amount":2,"callback":"ok","error":"err"}"#,
+    true,
+    "2"
+  ));
+  assert!(invoke(
+    &handle,
+    r#"{"cmd":"increment","amount":0,"callback":"ok","error":"err"}"#,
+    false,
+    r#""nothing to add""#
+  ));
+  // `shapes::State` is deserialized and `shapes::Result` resolves the promise
+  assert!(invoke(
+    &handle,
+    r#"{"cmd":"area","square":{"side":3},"callback":"ok","error":"err"}"#,
+    true,
+    r#"{"area":9.0}"#
+  ));
+  assert!(invoke(
+    &handle,
+    r#"{"cmd":"label","callback":"ok","error":"err"}"#,
+    true,
+    r#""main""#
+  ));
+}